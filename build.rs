@@ -32,11 +32,16 @@ fn main() {
         }
 
         println!("{:?} {}", path, relative_path);
-        let content = std::fs::read_to_string(path).unwrap();
+        // Normalize line endings, so that checkouts with `core.autocrlf` enabled
+        // embed (and hash) the same templates:
+        let content = std::fs::read_to_string(path).unwrap().replace("\r\n", "\n");
 
         files.push((relative_path, content));
     }
 
+    // Directory traversal order differs between platforms and file systems
+    files.sort();
+
     let mut files_code = Vec::new();
     for (file, content) in files {
         files_code.push(quote::quote! { (#file, #content) })
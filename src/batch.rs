@@ -0,0 +1,237 @@
+//! Generating several projects in one run, from a manifest like:
+//!
+//! ```toml
+//! output-path = "workshop"
+//! skip = ["git"]
+//!
+//! [[project]]
+//! name = "blinky"
+//! chip = "esp32c3"
+//! options = ["unstable-hal"]
+//!
+//! [[project]]
+//! name = "wifi"
+//! chip = "s3"
+//! options = ["alloc", "wifi", "unstable-hal"]
+//! ```
+//!
+//! e.g. for the exercises of a course on several chips, or to check that a
+//! set of configurations still renders. A project which fails to generate
+//! does not stop the others, the results are summarized at the end. Unlike
+//! single projects, the installed tools are not checked.
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    chip_name, config::solver, glyphs::Glyph, init_git_repo, override_variables, process_options,
+    render_template, template_variables, templates::Templates, timings::Timings, write_project,
+    Step,
+};
+
+/// The projects to generate.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct Manifest {
+    /// Directory in which to generate the projects, relative to the manifest.
+    /// The manifest's directory by default.
+    #[serde(default)]
+    output_path: Option<PathBuf>,
+    /// Post-processing steps to skip for every project
+    #[serde(default)]
+    skip: Vec<Step>,
+    #[serde(default, rename = "project")]
+    projects: Vec<Project>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Project {
+    name: String,
+    /// The chip, by any name `--chip` accepts.
+    chip: String,
+    #[serde(default)]
+    options: Vec<String>,
+    /// Overrides for the template variables
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+}
+
+/// Generate every project of the manifest at `path`, skipping the steps
+/// skipped in the manifest or with `skipped`, then print whether each one was
+/// generated. Fails if any was not. Experimental options are rejected unless
+/// `experimental` is set.
+pub fn run(
+    path: &Path,
+    templates: &Templates,
+    skipped: &[Step],
+    experimental: bool,
+) -> Result<(), Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {}: {error}", path.display()))?;
+    let manifest: Manifest = toml_edit::de::from_str(&contents)
+        .map_err(|error| format!("Invalid manifest {}: {error}", path.display()))?;
+    if manifest.projects.is_empty() {
+        return Err(format!("{} lists no `[[project]]`", path.display()).into());
+    }
+
+    let base = path.parent().unwrap_or(Path::new(""));
+    let output_path = base.join(manifest.output_path.as_deref().unwrap_or(Path::new("")));
+    let skipped = [manifest.skip.as_slice(), skipped].concat();
+
+    let mut results = Vec::new();
+    for project in &manifest.projects {
+        log::info!("Generating {}", project.name);
+        let result = generate(project, templates, &output_path, &skipped, experimental);
+        results.push((project, result));
+    }
+
+    print!("{}", summary(&results));
+
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if failed > 0 {
+        return Err(format!("{failed} of {} projects failed", results.len()).into());
+    }
+
+    Ok(())
+}
+
+/// Render a project and write it into `output_path`.
+fn generate(
+    project: &Project,
+    templates: &Templates,
+    output_path: &Path,
+    skipped: &[Step],
+    experimental: bool,
+) -> Result<PathBuf, String> {
+    let chip = chip_name::parse(&project.chip)?;
+    let mut violations = if experimental {
+        Vec::new()
+    } else {
+        solver::experimental(&project.options)
+    };
+    if let Err(invalid) = process_options(chip, &project.options) {
+        violations.extend(invalid);
+    }
+    if !violations.is_empty() {
+        let violations = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        return Err(violations.join(", "));
+    }
+
+    let mut variables = template_variables(&project.name, chip);
+    override_variables(&mut variables, &project.variables)?;
+
+    let project_dir = output_path.join(&project.name);
+    if project_dir.exists() {
+        return Err(format!("{} already exists", project_dir.display()));
+    }
+    fs::create_dir_all(&project_dir)
+        .map_err(|error| format!("Failed to create {}: {error}", project_dir.display()))?;
+
+    let files = render_template(templates, chip, &project.options, &variables);
+    let mut timings = Timings::default();
+    write_project(&project_dir, files, skipped, &mut timings).map_err(|error| error.to_string())?;
+    if !skipped.contains(&Step::Git) {
+        init_git_repo(&project_dir, &mut timings);
+    }
+
+    Ok(project_dir)
+}
+
+/// A table of the projects, their chip and where they were generated or why
+/// they were not.
+fn summary(results: &[(&Project, Result<PathBuf, String>)]) -> String {
+    let name_width = results
+        .iter()
+        .map(|(project, _)| project.name.len())
+        .max()
+        .unwrap_or(0);
+    let chip_width = results
+        .iter()
+        .map(|(project, _)| project.chip.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut summary = String::from("\nSummary\n");
+    for (project, result) in results {
+        let (glyph, outcome) = match result {
+            Ok(project_dir) => (Glyph::Ok, project_dir.display().to_string()),
+            Err(error) => (Glyph::Error, error.clone()),
+        };
+        summary.push_str(&format!(
+            "{:name_width$}  {:chip_width$}  {glyph} {outcome}\n",
+            project.name, project.chip
+        ));
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn test_batch() {
+        let dir = env::temp_dir().join(format!("esp-generate-batch-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = dir.join("projects.toml");
+        fs::write(
+            &manifest,
+            r#"
+output-path = "out"
+skip = ["fmt", "taplo", "git", "check"]
+
+[[project]]
+name = "blinky"
+chip = "c3"
+
+[[project]]
+name = "wifi"
+chip = "ESP32-S3"
+options = ["alloc", "wifi", "unstable-hal"]
+
+[[project]]
+name = "broken"
+chip = "esp32c3"
+options = ["wifl"]
+
+[[project]]
+name = "renamed"
+chip = "esp32c3"
+variables = { crate-name = "other" }
+"#,
+        )
+        .unwrap();
+
+        let error = run(&manifest, &Templates::embedded(), &[], false).unwrap_err();
+        assert_eq!(error.to_string(), "2 of 4 projects failed");
+        assert!(dir.join("out/blinky/Cargo.toml").exists());
+        assert!(fs::read_to_string(dir.join("out/wifi/Cargo.toml"))
+            .unwrap()
+            .contains("esp-wifi"));
+        assert!(!dir.join("out/broken").exists());
+        assert!(!dir.join("out/renamed").exists());
+
+        // Existing projects are not overwritten
+        fs::write(
+            &manifest,
+            "output-path = \"out\"\n[[project]]\nname = \"blinky\"\nchip = \"esp32c3\"\n",
+        )
+        .unwrap();
+        assert!(run(&manifest, &Templates::embedded(), &[Step::Git], false).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
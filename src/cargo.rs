@@ -0,0 +1,59 @@
+//! Reading the `Cargo.toml` manifest of generated projects.
+
+use std::{error::Error, fs, path::Path};
+
+use toml_edit::{DocumentMut, Item, Table, TableLike};
+
+/// The manifest of a generated project.
+pub struct CargoToml {
+    manifest: DocumentMut,
+}
+
+impl CargoToml {
+    /// Load and parse the `Cargo.toml` in the given project directory.
+    pub fn load(project_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = project_dir.join("Cargo.toml");
+        let contents = fs::read_to_string(&path)
+            .map_err(|error| format!("Failed to read {}: {error}", path.display()))?;
+
+        Self::parse(&contents)
+    }
+
+    /// Parse the contents of a manifest.
+    pub fn parse(contents: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            manifest: contents.parse()?,
+        })
+    }
+
+    /// The name of the package.
+    pub fn package_name(&self) -> Option<&str> {
+        self.manifest.get("package")?.get("name")?.as_str()
+    }
+
+    /// The `[package.metadata.esp-generate]` table, recording what the project
+    /// was generated from.
+    pub fn generator_metadata(&self) -> Option<&Table> {
+        self.manifest
+            .get("package")?
+            .get("metadata")?
+            .get("esp-generate")
+            .and_then(Item::as_table)
+    }
+
+    /// The dependencies and build dependencies with a version requirement,
+    /// as their name and requirement.
+    pub fn dependencies(&self) -> Vec<(String, String)> {
+        ["dependencies", "build-dependencies"]
+            .into_iter()
+            .filter_map(|section| self.manifest.get(section)?.as_table_like())
+            .flat_map(TableLike::iter)
+            .filter_map(|(name, item)| {
+                let version = item
+                    .as_str()
+                    .or_else(|| item.as_table_like()?.get("version")?.as_str())?;
+                Some((name.to_string(), version.to_string()))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,344 @@
+//! Detecting Cargo settings from outside of a generated project which
+//! override those of its `.cargo/config.toml`.
+//!
+//! Cargo merges the `.cargo/config.toml` of the project with those of every
+//! parent directory and of `$CARGO_HOME`, and environment variables take
+//! precedence over all of them. Settings meant for host projects, e.g. a
+//! faster linker or `-C target-cpu=native`, then break building or flashing
+//! the firmware, without pointing at where they come from.
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::{DocumentMut, Item};
+
+use crate::glyphs::Glyph;
+
+/// A setting which overrides the project's configuration.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The file or environment variable the setting comes from.
+    pub source: String,
+    /// The setting, e.g. `build.rustflags`.
+    pub setting: String,
+    /// What the setting does to the project.
+    pub effect: &'static str,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` in {} {}", self.setting, self.source, self.effect)
+    }
+}
+
+const RUSTFLAGS_ADDED: &str =
+    "is added to the flags of the project, flags meant for the host break the firmware build";
+const RUSTFLAGS_REPLACED: &str = "replaces the `build.rustflags` the firmware needs";
+const TARGET_DIR: &str =
+    "moves the build output out of `target/`, where the runner and scripts of the project look for it";
+const LINKER: &str = "replaces the linker of the target";
+const RUNNER: &str = "replaces the runner flashing the firmware";
+const TARGET: &str = "replaces the target of the project";
+
+/// The conflicting settings of the Cargo configuration file at `source`, for
+/// a project building for `target`.
+pub fn file_conflicts(contents: &str, source: &str, target: &str) -> Vec<Conflict> {
+    let Ok(config) = contents.parse::<DocumentMut>() else {
+        // Cargo reports invalid configuration files itself
+        return Vec::new();
+    };
+    let conflict = |setting: String, effect| Conflict {
+        source: source.to_string(),
+        setting,
+        effect,
+    };
+    let mut conflicts = Vec::new();
+
+    let build = config.get("build");
+    if build.and_then(|build| build.get("rustflags")).is_some() {
+        conflicts.push(conflict("build.rustflags".to_string(), RUSTFLAGS_ADDED));
+    }
+    if build.and_then(|build| build.get("target-dir")).is_some() {
+        conflicts.push(conflict("build.target-dir".to_string(), TARGET_DIR));
+    }
+
+    let targets = config.get("target").and_then(Item::as_table_like);
+    for (key, table) in targets.into_iter().flat_map(|targets| targets.iter()) {
+        let applies = match key
+            .strip_prefix("cfg(")
+            .and_then(|key| key.strip_suffix(')'))
+        {
+            Some(expression) => cfg_matches(expression, target) != Some(false),
+            None => key == target,
+        };
+        if !applies {
+            continue;
+        }
+        if table.get("rustflags").is_some() {
+            conflicts.push(conflict(
+                format!("target.{key}.rustflags"),
+                RUSTFLAGS_REPLACED,
+            ));
+        }
+        if key == target && table.get("linker").is_some() {
+            conflicts.push(conflict(format!("target.{key}.linker"), LINKER));
+        }
+    }
+
+    conflicts
+}
+
+/// Whether a `cfg(...)` expression of the Cargo configuration matches the
+/// target, as far as known: `None` if it depends on anything but the
+/// architecture, OS and family of the target, or cannot be parsed.
+fn cfg_matches(expression: &str, target: &str) -> Option<bool> {
+    match cfg_predicate(expression, target)? {
+        (matches, "") => matches,
+        _ => None,
+    }
+}
+
+/// Evaluate the predicate at the start of `input`, returning the rest.
+fn cfg_predicate<'a>(input: &'a str, target: &str) -> Option<(Option<bool>, &'a str)> {
+    let input = input.trim_start();
+    let end = input
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(input.len());
+    let (name, rest) = input.split_at(end);
+    let rest = rest.trim_start();
+
+    if let Some(mut rest) = rest.strip_prefix('(') {
+        let mut values = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(')') {
+                rest = after;
+                break;
+            }
+            let (value, after) = cfg_predicate(rest, target)?;
+            values.push(value);
+            rest = after.trim_start();
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+        }
+
+        let value = match name {
+            "all" if values.contains(&Some(false)) => Some(false),
+            "all" => values.iter().all(|v| *v == Some(true)).then_some(true),
+            "any" if values.contains(&Some(true)) => Some(true),
+            "any" => values.iter().all(|v| *v == Some(false)).then_some(false),
+            "not" if values.len() == 1 => values[0].map(|value| !value),
+            _ => return None,
+        };
+        return Some((value, rest.trim_start()));
+    }
+
+    let arch = target.split('-').next().unwrap_or_default();
+    let arch = if arch.starts_with("riscv32") {
+        "riscv32"
+    } else {
+        arch
+    };
+    let os = if target.ends_with("-espidf") {
+        "espidf"
+    } else {
+        "none"
+    };
+
+    if let Some(rest) = rest.strip_prefix('=') {
+        let (value, rest) = rest.trim_start().strip_prefix('"')?.split_once('"')?;
+        let known = match name {
+            "target_arch" => Some(arch),
+            "target_os" => Some(os),
+            "target_family" => Some(if os == "espidf" { "unix" } else { "" }),
+            _ => None,
+        };
+        return Some((known.map(|known| known == value), rest.trim_start()));
+    }
+
+    let value = match name {
+        "unix" => Some(os == "espidf"),
+        "windows" => Some(false),
+        _ => None,
+    };
+    Some((value, rest))
+}
+
+/// The conflicting environment variables among `vars`, for a project building
+/// for `target`.
+pub fn env_conflicts(vars: &[(String, String)], target: &str) -> Vec<Conflict> {
+    let target_prefix = format!(
+        "CARGO_TARGET_{}_",
+        target.to_ascii_uppercase().replace(['-', '.'], "_")
+    );
+
+    vars.iter()
+        .filter(|(_, value)| !value.is_empty())
+        .filter_map(|(name, _)| {
+            let effect = match name.as_str() {
+                "RUSTFLAGS" | "CARGO_ENCODED_RUSTFLAGS" | "CARGO_BUILD_RUSTFLAGS" => {
+                    RUSTFLAGS_REPLACED
+                }
+                "CARGO_TARGET_DIR" | "CARGO_BUILD_TARGET_DIR" => TARGET_DIR,
+                "CARGO_BUILD_TARGET" => TARGET,
+                _ => match name.strip_prefix(&target_prefix)? {
+                    "RUSTFLAGS" => RUSTFLAGS_REPLACED,
+                    "LINKER" => LINKER,
+                    "RUNNER" => RUNNER,
+                    _ => return None,
+                },
+            };
+
+            Some(Conflict {
+                source: "the environment".to_string(),
+                setting: name.clone(),
+                effect,
+            })
+        })
+        .collect()
+}
+
+/// The Cargo configuration files applying to the project besides its own:
+/// those of its parent directories and of `$CARGO_HOME`.
+fn config_files(project_dir: &Path) -> Vec<PathBuf> {
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")));
+
+    let project_dir = project_dir
+        .canonicalize()
+        .unwrap_or_else(|_| project_dir.to_path_buf());
+    let mut dirs = project_dir
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join(".cargo"))
+        .collect::<Vec<_>>();
+    // `$CARGO_HOME` is usually `~/.cargo`, one of the parent directories
+    if let Some(cargo_home) = cargo_home.filter(|home| !dirs.contains(home)) {
+        dirs.push(cargo_home);
+    }
+
+    dirs.iter()
+        // Cargo prefers `config.toml`, and still reads the legacy `config`
+        .filter_map(|dir| {
+            ["config.toml", "config"]
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+        })
+        .collect()
+}
+
+/// Warn about the settings from outside of the project directory which
+/// override its Cargo configuration.
+pub fn check(project_dir: &Path, target: &str) {
+    let mut conflicts = Vec::new();
+    for path in config_files(project_dir) {
+        if let Ok(contents) = fs::read_to_string(&path) {
+            conflicts.extend(file_conflicts(
+                &contents,
+                &path.display().to_string(),
+                target,
+            ));
+        }
+    }
+    conflicts.extend(env_conflicts(&env::vars().collect::<Vec<_>>(), target));
+
+    if conflicts.is_empty() {
+        return;
+    }
+
+    println!("\nChecking the Cargo configuration");
+    for conflict in &conflicts {
+        println!("{}  {conflict}", Glyph::Warning);
+    }
+    println!(
+        "These settings apply to every project, remove them or move them to the projects which need them."
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_config_conflicts() {
+        let target = "riscv32imac-unknown-none-elf";
+        let config = r#"
+[build]
+rustflags = ["-C", "target-cpu=native"]
+target = "x86_64-unknown-linux-gnu"
+
+[target.riscv32imac-unknown-none-elf]
+runner = "espflash flash"
+linker = "riscv32-esp-elf-gcc"
+
+[target.x86_64-unknown-linux-gnu]
+linker = "clang"
+rustflags = ["-C", "link-arg=-fuse-ld=mold"]
+
+[target.'cfg(target_os = "linux")']
+rustflags = ["-C", "target-cpu=native"]
+
+[target.'cfg(all(target_arch = "riscv32", not(windows)))']
+rustflags = ["-C", "force-frame-pointers"]
+
+[target.'cfg(feature = "x")']
+rustflags = []
+"#;
+        let settings = file_conflicts(config, "config.toml", target)
+            .into_iter()
+            .map(|conflict| conflict.setting)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            settings,
+            [
+                "build.rustflags",
+                "target.riscv32imac-unknown-none-elf.linker",
+                r#"target.cfg(all(target_arch = "riscv32", not(windows))).rustflags"#,
+                r#"target.cfg(feature = "x").rustflags"#,
+            ]
+        );
+        assert!(
+            file_conflicts("[build]\ntarget-dir = \"/tmp\"", "config.toml", target)[0]
+                .to_string()
+                .starts_with("`build.target-dir` in config.toml moves the build output")
+        );
+        assert!(file_conflicts("[net]\noffline = true", "config.toml", target).is_empty());
+        assert!(file_conflicts("not toml [", "config.toml", target).is_empty());
+
+        // ESP-IDF targets are unix
+        let config = "[target.'cfg(unix)']\nrustflags = []";
+        assert!(file_conflicts(config, "config.toml", target).is_empty());
+        assert_eq!(
+            file_conflicts(config, "config.toml", "riscv32imac-esp-espidf").len(),
+            1
+        );
+
+        let vars = [
+            ("RUSTFLAGS", "-C target-cpu=native"),
+            ("CARGO_TARGET_DIR", ""),
+            ("CARGO_BUILD_TARGET_DIR", "/tmp/target"),
+            (
+                "CARGO_TARGET_RISCV32IMAC_UNKNOWN_NONE_ELF_RUNNER",
+                "probe-rs run",
+            ),
+            ("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_LINKER", "clang"),
+            ("PATH", "/usr/bin"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        let settings = env_conflicts(&vars, target)
+            .into_iter()
+            .map(|conflict| conflict.setting)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            settings,
+            [
+                "RUSTFLAGS",
+                "CARGO_BUILD_TARGET_DIR",
+                "CARGO_TARGET_RISCV32IMAC_UNKNOWN_NONE_ELF_RUNNER",
+            ]
+        );
+    }
+}
@@ -0,0 +1,681 @@
+//! Checks of the tools needed to build and flash a generated project.
+//!
+//! The tools are probed concurrently, each with a timeout, so that a slow or
+//! wedged tool cannot hang the generator. Detected versions are cached for a
+//! few minutes in the user's cache directory. Besides their version, tools
+//! which list the chips they support are checked to support the selected one.
+//!
+//! The minimum versions are declared in `requirements.toml`, which is embedded
+//! at build time.
+
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    io::Read,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use esp_metadata::Chip;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::solver, glyphs::Glyph};
+
+/// How long a tool may take to report its version before it is killed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long detected versions are reused before probing the tools again.
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// The supported version of the `requirements.toml` format.
+const REQUIREMENTS_VERSION: u32 = 1;
+
+const REQUIREMENTS: &str = include_str!("../requirements.toml");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl Version {
+    const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse the first `major.minor.patch` version in the output of a
+    /// `--version` invocation, e.g. `probe-rs 0.25.0 (git commit: ...)`.
+    pub fn parse(output: &str) -> Option<Self> {
+        output.split_whitespace().find_map(|word| {
+            let mut parts = word.split(['.', '-']);
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+            let patch = parts.next()?.parse().ok()?;
+
+            Some(Self::new(major, minor, patch))
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A tool to check, and how to install or update it.
+struct Tool {
+    /// The key of the tool in `requirements.toml`.
+    requirement: &'static str,
+    name: &'static str,
+    command: &'static str,
+    args: &'static [&'static str],
+    /// Arguments making the tool list the chips it supports, if it does.
+    chips_args: Option<&'static [&'static str]>,
+    hint: &'static str,
+    /// The selected options needing the tool, if it is not needed by every
+    /// project.
+    needed_by: Vec<&'static str>,
+}
+
+impl Tool {
+    /// The name of the tool, and why it is needed if not by every project.
+    fn label(&self) -> String {
+        if self.needed_by.is_empty() {
+            return self.name.to_string();
+        }
+
+        let options = self
+            .needed_by
+            .iter()
+            .map(|option| format!("`{option}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{} for {options}", self.name)
+    }
+}
+
+/// A tool invocation, and how to extract the relevant part of its output.
+struct Query {
+    command: &'static str,
+    args: &'static [&'static str],
+    extract: fn(&str) -> Option<String>,
+}
+
+impl Query {
+    fn version(command: &'static str, args: &'static [&'static str]) -> Self {
+        Self {
+            command,
+            args,
+            extract: |output| Version::parse(output).map(|version| version.to_string()),
+        }
+    }
+
+    fn chips(command: &'static str, args: &'static [&'static str]) -> Self {
+        Self {
+            command,
+            args,
+            extract: |output| {
+                let chips = supported_chips(output);
+                (!chips.is_empty()).then(|| chips.join(" "))
+            },
+        }
+    }
+
+    /// The key of the query in the cache.
+    fn key(&self) -> String {
+        [self.command]
+            .iter()
+            .chain(self.args)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// The outcome of running a query.
+#[derive(Clone, Debug)]
+enum Probe {
+    Found(String),
+    NotFound,
+    TimedOut,
+}
+
+/// The Espressif chip names mentioned in the output of a tool, e.g. in the
+/// possible values of its `--chip` argument.
+fn supported_chips(output: &str) -> Vec<String> {
+    let mut chips = output
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|word| word.to_ascii_lowercase())
+        .filter(|word| word.starts_with("esp32"))
+        .collect::<Vec<_>>();
+    chips.sort();
+    chips.dedup();
+
+    chips
+}
+
+/// Whether `command` is an executable in one of the directories of `PATH`,
+/// so that running it does not fail with an unhelpful "not found" error.
+pub fn in_path(command: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+    let file_name = if cfg!(windows) {
+        format!("{command}.exe")
+    } else {
+        command.to_string()
+    };
+
+    env::split_paths(&path).any(|dir| dir.join(&file_name).is_file())
+}
+
+/// How a tool fared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// The tool is too old or does not support the chip.
+    Failed,
+    /// The tool is missing or did not respond.
+    Error,
+}
+
+/// The result of checking a tool.
+#[derive(Clone, Debug)]
+pub struct CheckResult {
+    pub status: Status,
+    pub message: String,
+}
+
+impl CheckResult {
+    fn ok(message: String) -> Self {
+        Self {
+            status: Status::Ok,
+            message,
+        }
+    }
+
+    fn failed(message: String) -> Self {
+        Self {
+            status: Status::Failed,
+            message,
+        }
+    }
+
+    fn error(message: String) -> Self {
+        Self {
+            status: Status::Error,
+            message,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == Status::Ok
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let glyph = match self.status {
+            Status::Ok => Glyph::Ok,
+            Status::Failed => Glyph::Failed,
+            Status::Error => Glyph::Error,
+        };
+        write!(f, "{glyph} {}", self.message)
+    }
+}
+
+/// Check the tools needed by a project for the given chip, and print the
+/// results.
+pub fn check(chip: Chip, options: &[String], use_cache: bool) {
+    let results = run(chip, options, use_cache);
+
+    println!("\nChecking installed versions");
+    for result in &results {
+        println!("{result}");
+    }
+}
+
+/// Check the tools needed by a project for the given chip.
+pub fn run(chip: Chip, options: &[String], use_cache: bool) -> Vec<CheckResult> {
+    let requirements = Requirements::embedded();
+
+    let mut tools = vec![
+        if chip.is_xtensa() {
+            Tool {
+                requirement: "rust",
+                name: "Rust (esp)",
+                command: "rustc",
+                args: &["+esp", "--version"],
+                chips_args: None,
+                hint: "Update by running `espup update`",
+                needed_by: Vec::new(),
+            }
+        } else {
+            Tool {
+                requirement: "rust",
+                name: "Rust",
+                command: "rustc",
+                args: &["--version"],
+                chips_args: None,
+                hint: "Update by running `rustup update`",
+                needed_by: Vec::new(),
+            }
+        },
+        Tool {
+            requirement: "cargo",
+            name: "cargo",
+            command: "cargo",
+            args: &["--version"],
+            chips_args: None,
+            hint: "Install Rust with rustup, see https://rustup.rs",
+            needed_by: Vec::new(),
+        },
+        Tool {
+            requirement: "espflash",
+            name: "espflash",
+            command: "espflash",
+            args: &["--version"],
+            chips_args: Some(&["save-image", "--help"]),
+            hint: "Install or update by running `cargo install espflash --locked`",
+            needed_by: Vec::new(),
+        },
+    ];
+    // The `esp` toolchain already is a nightly one
+    let nightly = solver::requiring_nightly(options);
+    if !chip.is_xtensa() && !nightly.is_empty() {
+        tools.push(Tool {
+            requirement: "rust-nightly",
+            name: "Rust nightly",
+            command: "rustc",
+            args: &["+nightly", "--version"],
+            chips_args: None,
+            hint: "Install by running `rustup toolchain install nightly --component rust-src`",
+            needed_by: nightly,
+        });
+    }
+    if options.iter().any(|option| option == "probe-rs") {
+        tools.push(Tool {
+            requirement: "probe-rs",
+            name: "probe-rs",
+            command: "probe-rs",
+            args: &["--version"],
+            chips_args: Some(&["chip", "list"]),
+            hint: "Install or update by running `cargo install probe-rs-tools --locked`",
+            needed_by: Vec::new(),
+        });
+    }
+
+    tools.push(Tool {
+        requirement: "git",
+        name: "git",
+        command: "git",
+        args: &["--version"],
+        chips_args: None,
+        hint: "Install it from https://git-scm.com to version the project",
+        needed_by: Vec::new(),
+    });
+
+    // Run all queries at once, the version of each tool first:
+    let mut queries = Vec::new();
+    for tool in &tools {
+        queries.push(Query::version(tool.command, tool.args));
+        if let Some(args) = tool.chips_args {
+            queries.push(Query::chips(tool.command, args));
+        }
+    }
+    let mut probes = run_queries(&queries, use_cache).into_iter();
+
+    let mut results = Vec::new();
+    for tool in &tools {
+        let version = probes.next().unwrap();
+        let chips = tool.chips_args.and_then(|_| probes.next());
+
+        let min_version = requirements.min_version(tool.requirement, chip, options);
+        let version = match version {
+            Probe::Found(version) => Version::parse(&version),
+            Probe::NotFound => None,
+            Probe::TimedOut => {
+                results.push(CheckResult::error(format!(
+                    "{} did not report its version within {}s",
+                    tool.label(),
+                    PROBE_TIMEOUT.as_secs()
+                )));
+                continue;
+            }
+        };
+        let Some(version) = version else {
+            results.push(CheckResult::error(format!(
+                "{} not found. {}",
+                tool.label(),
+                tool.hint
+            )));
+            continue;
+        };
+
+        let result = if let Some(min_version) = min_version.filter(|min| version < *min) {
+            CheckResult::failed(format!(
+                "{} ({version}), {min_version} or newer is required. {}",
+                tool.label(),
+                tool.hint
+            ))
+        } else if let Some(Probe::Found(chips)) = &chips {
+            if chips.split(' ').any(|c| c == chip.to_string()) {
+                CheckResult::ok(format!("{} ({version})", tool.label()))
+            } else {
+                CheckResult::failed(format!(
+                    "{} ({version}) does not support the {chip}. {}",
+                    tool.label(),
+                    tool.hint
+                ))
+            }
+        } else {
+            // Whether the chip is supported could not be determined, which
+            // must not be reported as a failure
+            CheckResult::ok(format!("{} ({version})", tool.label()))
+        };
+        results.push(result);
+    }
+
+    results
+}
+
+/// The minimum tool versions, as declared in `requirements.toml`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Requirements {
+    version: u32,
+    tools: BTreeMap<String, ToolRequirement>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ToolRequirement {
+    min_version: String,
+    #[serde(default)]
+    when: Vec<ConditionalRequirement>,
+}
+
+/// A stricter minimum, applying when the chip and option (if given) are
+/// selected.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ConditionalRequirement {
+    chip: Option<Chip>,
+    option: Option<String>,
+    min_version: String,
+}
+
+impl Requirements {
+    /// Parse and validate the contents of a `requirements.toml` file.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let requirements: Self =
+            toml_edit::de::from_str(contents).map_err(|error| error.to_string())?;
+
+        if requirements.version != REQUIREMENTS_VERSION {
+            return Err(format!(
+                "Unsupported requirements version {}, expected {REQUIREMENTS_VERSION}",
+                requirements.version
+            ));
+        }
+
+        let versions = requirements.tools.iter().flat_map(|(tool, requirement)| {
+            std::iter::once(&requirement.min_version)
+                .chain(requirement.when.iter().map(|when| &when.min_version))
+                .map(move |version| (tool, version))
+        });
+        for (tool, version) in versions {
+            if Version::parse(version).is_none() {
+                return Err(format!("Invalid minimum version '{version}' for {tool}"));
+            }
+        }
+
+        Ok(requirements)
+    }
+
+    /// The requirements embedded at build time.
+    pub fn embedded() -> Self {
+        Self::parse(REQUIREMENTS).expect("the embedded requirements.toml is invalid")
+    }
+
+    /// The highest minimum version of a tool applying to the chip and selected
+    /// options, if the tool has any.
+    fn min_version(&self, tool: &str, chip: Chip, options: &[String]) -> Option<Version> {
+        let requirement = self.tools.get(tool)?;
+
+        requirement
+            .when
+            .iter()
+            .filter(|when| when.chip.is_none_or(|c| c == chip))
+            .filter(|when| {
+                when.option
+                    .as_ref()
+                    .is_none_or(|option| options.contains(option))
+            })
+            .map(|when| &when.min_version)
+            .chain([&requirement.min_version])
+            .filter_map(|version| Version::parse(version))
+            .max()
+    }
+}
+
+/// Run all queries concurrently, reusing cached results which are recent
+/// enough unless `use_cache` is false.
+fn run_queries(queries: &[Query], use_cache: bool) -> Vec<Probe> {
+    let mut cache = ProbeCache::load();
+
+    // Cached results are `Ok`, queries which need running run concurrently:
+    let handles = thread::scope(|scope| {
+        let handles = queries
+            .iter()
+            .map(|query| match cache.get(query).filter(|_| use_cache) {
+                Some(probe) => Ok(probe),
+                None => Err(scope.spawn(|| probe(query))),
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.map_err(|handle| handle.join().unwrap_or(Probe::NotFound)))
+            .collect::<Vec<_>>()
+    });
+
+    let mut probes = Vec::new();
+    for (query, result) in queries.iter().zip(handles) {
+        match result {
+            Ok(cached) => probes.push(cached),
+            Err(probed) => {
+                cache.insert(query.key(), probed.clone());
+                probes.push(probed);
+            }
+        }
+    }
+    cache.save();
+
+    probes
+}
+
+/// Run a query, killing the tool if it does not exit within
+/// [`PROBE_TIMEOUT`].
+fn probe(query: &Query) -> Probe {
+    let Ok(mut child) = Command::new(query.command)
+        .args(query.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return Probe::NotFound;
+    };
+
+    // Read concurrently, long outputs (e.g. chip lists) would otherwise fill
+    // the pipe and block the tool
+    let mut stdout = child.stdout.take();
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(stdout) = &mut stdout {
+            let _ = stdout.read_to_string(&mut output);
+        }
+        output
+    });
+
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(Some(_)) | Err(_) => return Probe::NotFound,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Probe::TimedOut;
+            }
+        }
+    }
+
+    let output = reader.join().unwrap_or_default();
+    (query.extract)(&output).map_or(Probe::NotFound, Probe::Found)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// The relevant part of the output, `None` if the tool was not found.
+    output: Option<String>,
+    /// Seconds since the Unix epoch.
+    checked_at: u64,
+}
+
+/// Query results, keyed by the command line of the query.
+#[derive(Default, Serialize, Deserialize)]
+struct ProbeCache {
+    queries: BTreeMap<String, CacheEntry>,
+}
+
+impl ProbeCache {
+    fn path() -> Option<PathBuf> {
+        Some(
+            dirs::cache_dir()?
+                .join("esp-generate")
+                .join("tool-probes.json"),
+        )
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs())
+    }
+
+    /// Load the cache, starting from scratch if it is missing or unreadable.
+    fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Store the cache. Failing to do so only costs probing again next time.
+    fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = fs::write(path, json);
+        }
+    }
+
+    /// The cached result of a query. Outputs the query would not have
+    /// extracted, e.g. after editing the cache by hand, are a miss.
+    fn get(&self, query: &Query) -> Option<Probe> {
+        let entry = self.queries.get(&query.key())?;
+        if Self::now().saturating_sub(entry.checked_at) > CACHE_TTL.as_secs() {
+            return None;
+        }
+
+        match &entry.output {
+            Some(output) => (query.extract)(output).map(Probe::Found),
+            None => Some(Probe::NotFound),
+        }
+    }
+
+    /// Record a probe. Timeouts are not cached, the tool may respond next time.
+    fn insert(&mut self, key: String, probe: Probe) {
+        let output = match probe {
+            Probe::Found(output) => Some(output),
+            Probe::NotFound => None,
+            Probe::TimedOut => {
+                self.queries.remove(&key);
+                return;
+            }
+        };
+
+        self.queries.insert(
+            key,
+            CacheEntry {
+                output,
+                checked_at: Self::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_requirements() {
+        assert!(Requirements::parse(include_str!("../requirements.toml")).is_ok());
+        assert!(Requirements::parse("version = 2\n[tools]\n").is_err());
+        assert!(
+            Requirements::parse("version = 1\n[tools.rust]\nmin-version = \"latest\"\n").is_err()
+        );
+    }
+
+    #[test]
+    fn test_in_path() {
+        assert!(in_path("cargo"));
+        assert!(!in_path("esp-generate-missing-tool"));
+    }
+
+    #[test]
+    fn test_chips_query() {
+        let query = Query::chips("espflash", &["save-image", "--help"]);
+        assert_eq!(
+            (query.extract)("--chip <CHIP>  [possible values: esp32, esp32c3, ESP32S3]"),
+            Some("esp32 esp32c3 esp32s3".to_string())
+        );
+        // Unknown rather than supporting no chip, e.g. when the help changed
+        assert_eq!((query.extract)("Usage: espflash save-image"), None);
+    }
+
+    #[test]
+    fn test_probe_cache() {
+        let query = Query::version("espflash", &["--version"]);
+        let cache = |output: &str| ProbeCache {
+            queries: BTreeMap::from([(
+                query.key(),
+                CacheEntry {
+                    output: Some(output.to_string()),
+                    checked_at: ProbeCache::now(),
+                },
+            )]),
+        };
+
+        assert!(
+            matches!(cache("3.3.0").get(&query), Some(Probe::Found(version)) if version == "3.3.0")
+        );
+        // The cache is writable by the user, an invalid version is probed again
+        assert!(cache("latest").get(&query).is_none());
+    }
+}
@@ -0,0 +1,136 @@
+//! Parsing the chip given with `--chip`.
+//!
+//! Besides the names `esp-generate` uses, e.g. `esp32c3`, chips are accepted
+//! as they are commonly written: in any case, with or without dashes, without
+//! the `esp32` prefix (`c3`), as the name of a module or DevKit built around
+//! them (`ESP32-C3-MINI-1`) or by their ESP8684/ESP8685 names.
+
+use std::ffi::OsStr;
+
+use clap::{
+    builder::{PossibleValue, TypedValueParser},
+    error::ErrorKind,
+    Arg, Command, ValueEnum,
+};
+use esp_metadata::Chip;
+
+/// The series of modules, and development boards, which follow the chip in
+/// their names.
+const MODULE_SERIES: &[&str] = &["mini", "wroom", "wrover", "solo", "pico", "devkit"];
+
+/// Chips which are also sold under another name.
+const OTHER_NAMES: &[(&str, Chip)] = &[("esp8684", Chip::Esp32c2), ("esp8685", Chip::Esp32c3)];
+
+/// The chip a name refers to.
+pub fn parse(name: &str) -> Result<Chip, String> {
+    let normalized = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>();
+
+    if let Some((_, chip)) = OTHER_NAMES
+        .iter()
+        .find(|(other, _)| normalized.starts_with(other))
+    {
+        return Ok(*chip);
+    }
+
+    let (prefixed, rest) = match normalized.strip_prefix("esp32") {
+        Some(rest) => (true, rest),
+        None => (false, normalized.as_str()),
+    };
+    let is_module = |rest: &str| MODULE_SERIES.iter().any(|series| rest.starts_with(series));
+
+    // The plain ESP32 can only be told apart from the other chips by its
+    // prefix, e.g. `ESP32-WROOM-32`
+    let found = Chip::value_variants().iter().find(|chip| {
+        let suffix = chip.as_ref().strip_prefix("esp32").unwrap_or_default();
+        match rest.strip_prefix(suffix) {
+            Some(_) if suffix.is_empty() && !prefixed => false,
+            Some(module) => module.is_empty() || is_module(module),
+            None => false,
+        }
+    });
+
+    found.copied().ok_or_else(|| {
+        let chips = Chip::value_variants()
+            .iter()
+            .map(|chip| chip.to_string())
+            .collect::<Vec<_>>();
+        format!(
+            "Unknown chip '{name}', the supported chips are: {}",
+            chips.join(", ")
+        )
+    })
+}
+
+/// The value parser of `--chip`, which still lists the chips in the help.
+#[derive(Clone)]
+pub struct ChipParser;
+
+impl TypedValueParser for ChipParser {
+    type Value = Chip;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        _arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Chip, clap::Error> {
+        let Some(value) = value.to_str() else {
+            return Err(clap::Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd));
+        };
+
+        parse(value).map_err(|error| {
+            clap::Error::raw(ErrorKind::InvalidValue, format!("{error}\n")).with_cmd(cmd)
+        })
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            Chip::value_variants()
+                .iter()
+                .filter_map(ValueEnum::to_possible_value),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::Args;
+
+    #[test]
+    fn test_chip_names() {
+        for (name, chip) in [
+            ("esp32c3", Chip::Esp32c3),
+            ("ESP32C3", Chip::Esp32c3),
+            ("esp32-c3", Chip::Esp32c3),
+            ("c3", Chip::Esp32c3),
+            ("ESP32-C3-MINI-1", Chip::Esp32c3),
+            ("esp8685", Chip::Esp32c3),
+            ("ESP8684-WROOM-02C", Chip::Esp32c2),
+            ("ESP32", Chip::Esp32),
+            ("ESP32-WROOM-32E", Chip::Esp32),
+            ("esp32-pico-d4", Chip::Esp32),
+            ("S3", Chip::Esp32s3),
+            ("ESP32-S3-WROOM-1", Chip::Esp32s3),
+            ("esp32_h2", Chip::Esp32h2),
+            ("ESP32-C6-DevKitC-1", Chip::Esp32c6),
+        ] {
+            assert_eq!(parse(name), Ok(chip), "{name}");
+        }
+
+        for name in ["", "wroom", "esp32c4", "c3x", "s3x"] {
+            let error = parse(name).unwrap_err();
+            assert!(error.contains("esp32, esp32c2, esp32c3"), "{name}: {error}");
+        }
+
+        let args = Args::try_parse_from(["esp-generate", "--chip", "C3", "project"]).unwrap();
+        assert_eq!(args.chip, Some(Chip::Esp32c3));
+        assert!(Args::try_parse_from(["esp-generate", "--chip", "c4", "project"]).is_err());
+    }
+}
@@ -0,0 +1,1025 @@
+//! The generation options, their metadata and the constraints between them.
+//!
+//! The option tree is printed as JSON by the `options` subcommand, for editors
+//! and other tools presenting the options. Each item is an object with a
+//! `type` of `category`, `option` or `value` and the fields of the matching
+//! struct below; the format is versioned by [`OPTIONS_FORMAT_VERSION`].
+
+use clap::ValueEnum;
+use esp_metadata::Chip;
+use serde::{Serialize, Serializer};
+
+pub use capability::Capability;
+
+pub mod board;
+pub mod capability;
+pub mod solver;
+
+/// The version of the JSON form of the options, increased on changes which
+/// are not backwards compatible.
+pub const OPTIONS_FORMAT_VERSION: u32 = 1;
+
+/// An option which is either selected or not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct GeneratorOption {
+    pub name: &'static str,
+    /// The description shown to users.
+    pub display_name: &'static str,
+    /// The options selected along with this one.
+    pub enables: &'static [&'static str],
+    /// The options which cannot be selected along with this one.
+    pub disables: &'static [&'static str],
+    /// The chip capabilities the option needs, it is available for every chip
+    /// having all of them.
+    pub capabilities: &'static [Capability],
+    /// The oldest supported revision of a chip, as `major * 100 + minor`.
+    /// Generated projects warn at boot when running on an older revision.
+    #[serde(serialize_with = "serialize_revisions")]
+    pub min_chip_revision: &'static [(Chip, u16)],
+    /// Whether the bare-metal template of the option uses APIs of `esp-hal`
+    /// which are only available with its `unstable` feature, i.e. the option
+    /// requires `unstable-hal` unless `esp-idf-std` is selected.
+    pub requires_unstable_hal: bool,
+    /// Whether the option needs a nightly toolchain on RISC-V chips, whose
+    /// projects otherwise build with stable Rust. The `esp` toolchain of
+    /// Xtensa chips is a nightly one.
+    pub requires_nightly: bool,
+    /// Whether the option is still experimental, e.g. a new template which may
+    /// change or break. Experimental options are hidden, and rejected, unless
+    /// `--experimental` is passed.
+    pub experimental: bool,
+}
+
+/// Serialize the minimum revisions as a map from chip to revision.
+fn serialize_revisions<S: Serializer>(
+    revisions: &&'static [(Chip, u16)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(revisions.iter().map(|(chip, revision)| (chip, revision)))
+}
+
+impl GeneratorOption {
+    pub fn options(&self) -> Vec<String> {
+        vec![self.name.to_string()]
+    }
+
+    /// Whether the option is available for the given chip.
+    pub fn supports(&self, chip: Chip) -> bool {
+        self.capabilities
+            .iter()
+            .all(|capability| capability.supported_by(chip))
+    }
+}
+
+/// An option taking a numeric value, which is available to the templates as a
+/// variable of the same name.
+///
+/// A value is selected as `name=value`, options left unset use their default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct GeneratorValueOption {
+    pub name: &'static str,
+    /// The description shown to users.
+    pub display_name: &'static str,
+    pub unit: &'static str,
+    pub default: u32,
+    /// The smallest valid value.
+    pub min: u32,
+    /// The largest valid value.
+    pub max: u32,
+    /// The options selected along with this one.
+    pub enables: &'static [&'static str],
+    /// The chip capabilities the option needs, it is available for every chip
+    /// having all of them.
+    pub capabilities: &'static [Capability],
+}
+
+impl GeneratorValueOption {
+    pub fn options(&self) -> Vec<String> {
+        vec![self.name.to_string()]
+    }
+
+    /// Whether the option is available for the given chip.
+    pub fn supports(&self, chip: Chip) -> bool {
+        self.capabilities
+            .iter()
+            .all(|capability| capability.supported_by(chip))
+    }
+
+    /// The value selected for this option, or its default.
+    pub fn value(&self, selected: &[String]) -> u32 {
+        selected
+            .iter()
+            .filter_map(|option| split_value(option))
+            .find(|(name, _)| *name == self.name)
+            .and_then(|(_, value)| value.parse().ok())
+            .unwrap_or(self.default)
+    }
+
+    /// Parse and range-check a value for this option.
+    pub fn parse(&self, value: &str) -> Result<u32, String> {
+        match value.parse::<u32>() {
+            Ok(value) if (self.min..=self.max).contains(&value) => Ok(value),
+            _ => Err(format!(
+                "Option '{}' must be a number between {} and {} ({})",
+                self.name, self.min, self.max, self.unit
+            )),
+        }
+    }
+}
+
+/// Split a `name=value` selection into its name and value.
+pub fn split_value(option: &str) -> Option<(&str, &str)> {
+    option.split_once('=')
+}
+
+/// A group of options, which is not selectable itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct GeneratorOptionCategory {
+    pub name: &'static str,
+    /// The title shown to users.
+    pub display_name: &'static str,
+    pub options: &'static [GeneratorOptionItem],
+}
+
+impl GeneratorOptionCategory {
+    pub fn options(&self) -> Vec<String> {
+        let mut res = Vec::new();
+        for option in self.options {
+            res.extend(option.options());
+        }
+        res
+    }
+}
+
+/// An item of the option tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum GeneratorOptionItem {
+    Category(GeneratorOptionCategory),
+    Option(GeneratorOption),
+    Value(GeneratorValueOption),
+}
+
+impl GeneratorOptionItem {
+    pub fn title(&self) -> String {
+        match self {
+            GeneratorOptionItem::Category(category) => category.display_name.to_string(),
+            GeneratorOptionItem::Option(option) => option.display_name.to_string(),
+            GeneratorOptionItem::Value(option) => option.display_name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            GeneratorOptionItem::Category(category) => category.name.to_string(),
+            GeneratorOptionItem::Option(option) => option.name.to_string(),
+            GeneratorOptionItem::Value(option) => option.name.to_string(),
+        }
+    }
+
+    pub fn options(&self) -> Vec<String> {
+        match self {
+            GeneratorOptionItem::Category(category) => category.options(),
+            GeneratorOptionItem::Option(option) => option.options(),
+            GeneratorOptionItem::Value(option) => option.options(),
+        }
+    }
+
+    pub fn is_category(&self) -> bool {
+        matches!(self, GeneratorOptionItem::Category(_))
+    }
+
+    pub fn is_value(&self) -> bool {
+        matches!(self, GeneratorOptionItem::Value(_))
+    }
+
+    /// Whether the option is available for the given chip. Categories are
+    /// available for every chip.
+    pub fn supports(&self, chip: Chip) -> bool {
+        match self {
+            GeneratorOptionItem::Category(_) => true,
+            GeneratorOptionItem::Option(option) => option.supports(chip),
+            GeneratorOptionItem::Value(option) => option.supports(chip),
+        }
+    }
+
+    pub fn enables(&self) -> &[&str] {
+        match self {
+            GeneratorOptionItem::Category(_) => &[],
+            GeneratorOptionItem::Option(option) => option.enables,
+            GeneratorOptionItem::Value(option) => option.enables,
+        }
+    }
+
+    pub fn requires_unstable_hal(&self) -> bool {
+        match self {
+            GeneratorOptionItem::Option(option) => option.requires_unstable_hal,
+            _ => false,
+        }
+    }
+
+    pub fn requires_nightly(&self) -> bool {
+        match self {
+            GeneratorOptionItem::Option(option) => option.requires_nightly,
+            _ => false,
+        }
+    }
+
+    pub fn is_experimental(&self) -> bool {
+        match self {
+            GeneratorOptionItem::Option(option) => option.experimental,
+            _ => false,
+        }
+    }
+}
+
+/// The option tree and the capabilities of each chip, in the JSON form
+/// printed by the `options` subcommand.
+pub fn to_json() -> serde_json::Value {
+    let capabilities = Chip::value_variants()
+        .iter()
+        .map(|chip| {
+            let names = Capability::ALL
+                .iter()
+                .filter(|capability| capability.supported_by(*chip))
+                .map(|capability| capability.name())
+                .collect::<Vec<_>>();
+            (chip.to_string(), serde_json::json!(names))
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    serde_json::json!({
+        "version": OPTIONS_FORMAT_VERSION,
+        "options": OPTIONS,
+        "capabilities": capabilities,
+    })
+}
+
+pub static OPTIONS: &[GeneratorOptionItem] = &[
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "flavor",
+        display_name: "Flavor (bare-metal `no_std` unless selected)",
+        options: &[GeneratorOptionItem::Option(GeneratorOption {
+            name: "esp-idf-std",
+            display_name: "Generates a `std` application on ESP-IDF, using `esp-idf-svc`, instead of a bare-metal one.",
+            enables: &[],
+            // Everything building on `esp-hal`, or on the bare-metal build setup.
+            // `alloc` and `wifi` have an ESP-IDF equivalent.
+            disables: &[
+                "heap-size",
+                "heap-dram2",
+                "heap-psram",
+                "ble",
+                "embassy",
+                "probe-rs",
+                "audio-out",
+                "hw-crypto",
+                "devkit",
+                "ident",
+                "flip-link",
+                "release-small",
+                "examples",
+                "heartbeat",
+                "git-lfs",
+                "dev-container",
+                "ci",
+                "release-tooling",
+                "firmware-manifest",
+                "manufacturing",
+                "provisioning",
+                "web-installer",
+                "docker-build",
+                "c-interop",
+                "c-logging",
+                "unstable-hal",
+                "panic-policy",
+                "reset-reason",
+                "interrupts",
+                "sync",
+                "map-file",
+                "app-config",
+                "hal-facade",
+                "wifi-sta",
+                "net-tools",
+                "metrics",
+                "remote-logging",
+                "ram-placement",
+            ],
+            capabilities: &[],
+            min_chip_revision: &[],
+            requires_unstable_hal: false,
+            requires_nightly: true,
+            experimental: true,
+        })],
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "unstable-hal",
+        display_name: "Enables the `unstable` feature of `esp-hal`, for the drivers it does not consider stable yet, e.g. timers, DMA and delays. Required by the options marked `unstable-hal`.",
+        enables: &[],
+        disables: &[],
+        capabilities: &[],
+        min_chip_revision: &[],
+        requires_unstable_hal: false,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "alloc",
+        display_name: "Enables allocations via the `esp-alloc` crate, or the ESP-IDF heap with `esp-idf-std`.",
+        enables: &[],
+        disables: &[],
+        capabilities: &[],
+        min_chip_revision: &[],
+        requires_unstable_hal: false,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "wifi",
+        display_name: "Enables Wi-Fi via the `esp-wifi` crate, or `esp-idf-svc` with `esp-idf-std`. Requires `alloc`.",
+        enables: &["alloc"],
+        disables: &[],
+        capabilities: &[Capability::Wifi],
+        min_chip_revision: &[],
+        requires_unstable_hal: true,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "wifi-sta",
+        display_name: "Keeps a Wi-Fi station connected in an `embassy` task, reconnecting with an exponential backoff, and publishes the connection state in a `Watch`. Requires `wifi` and `embassy`.",
+        enables: &["wifi", "embassy"],
+        disables: &[],
+        capabilities: &[Capability::Wifi],
+        min_chip_revision: &[],
+        requires_unstable_hal: true,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "net-tools",
+        display_name: "Adds ping and DNS lookup helpers on an `embassy-net` stack, and a task logging their results periodically. Requires `wifi-sta`.",
+        enables: &["wifi-sta"],
+        disables: &[],
+        capabilities: &[Capability::Wifi],
+        min_chip_revision: &[],
+        requires_unstable_hal: true,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "metrics",
+        display_name: "Adds a registry of counters and gauges, served at `/metrics` over HTTP in the Prometheus text format. Requires `wifi-sta`.",
+        enables: &["wifi-sta"],
+        disables: &[],
+        capabilities: &[Capability::Wifi],
+        min_chip_revision: &[],
+        requires_unstable_hal: true,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "remote-logging",
+        display_name: "Forwards the log to a syslog server over UDP, besides printing it. Requires `wifi-sta`, not available with `probe-rs`.",
+        enables: &["wifi-sta"],
+        disables: &["probe-rs"],
+        capabilities: &[Capability::Wifi],
+        min_chip_revision: &[],
+        requires_unstable_hal: true,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "ble",
+        display_name: "Enables BLE via the `esp-wifi` crate. Requires `alloc`.",
+        enables: &["alloc"],
+        disables: &[],
+        capabilities: &[Capability::Bt],
+        min_chip_revision: &[],
+        requires_unstable_hal: true,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "beacon",
+        display_name: "BLE beacon (requires `ble`)",
+        options: &[
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "beacon",
+                display_name: "Advertises as an iBeacon, without a GATT server.",
+                enables: &["ble"],
+                disables: &[],
+                capabilities: &[Capability::Bt],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "beacon-eddystone",
+                display_name: "Advertises an Eddystone-URL instead of an iBeacon.",
+                enables: &["beacon"],
+                disables: &[],
+                capabilities: &[Capability::Bt],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Value(GeneratorValueOption {
+                name: "beacon-tx-power",
+                display_name: "Received signal strength at 1 m, advertised for distance estimation",
+                unit: "-dBm",
+                default: 59,
+                min: 30,
+                max: 100,
+                enables: &["beacon"],
+                capabilities: &[Capability::Bt],
+            }),
+        ],
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "embassy",
+        display_name: "Adds `embassy` framework support.",
+        enables: &[],
+        disables: &[],
+        capabilities: &[],
+        min_chip_revision: &[],
+        requires_unstable_hal: true,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "channels",
+        display_name: "Adds `embassy-sync` channels between a producer and a consumer task. Requires `embassy`.",
+        enables: &["embassy"],
+        disables: &[],
+        capabilities: &[],
+        min_chip_revision: &[],
+        requires_unstable_hal: false,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "battery",
+        display_name: "Battery monitoring (requires `channels`)",
+        options: &[
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "battery",
+                display_name: "Measures the battery voltage through a voltage divider and signals a low battery.",
+                enables: &["channels"],
+                disables: &[],
+                capabilities: &[Capability::AdcCalibration],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Value(GeneratorValueOption {
+                name: "battery-divider",
+                display_name: "Ratio of the voltage divider, battery voltage / ADC pin voltage",
+                unit: "hundredths",
+                default: 200,
+                min: 100,
+                max: 2000,
+                enables: &["battery"],
+                capabilities: &[Capability::AdcCalibration],
+            }),
+            GeneratorOptionItem::Value(GeneratorValueOption {
+                name: "battery-low-mv",
+                display_name: "Battery voltage below which a low battery is signalled",
+                unit: "mV",
+                default: 3300,
+                min: 1000,
+                max: 20000,
+                enables: &["battery"],
+                capabilities: &[Capability::AdcCalibration],
+            }),
+        ],
+    }),
+    GeneratorOptionItem::Option(GeneratorOption {
+        name: "probe-rs",
+        display_name: "Enables `defmt` and flashes using `probe-rs` instead of `espflash`.",
+        enables: &[],
+        disables: &[],
+        capabilities: &[],
+        min_chip_revision: &[],
+        requires_unstable_hal: false,
+        requires_nightly: false,
+        experimental: false,
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "heap",
+        display_name: "Heap placement (requires `alloc`)",
+        options: &[
+            GeneratorOptionItem::Value(GeneratorValueOption {
+                name: "heap-size",
+                display_name: "Size of the heap region in internal DRAM",
+                unit: "KiB",
+                default: 72,
+                min: 8,
+                max: 256,
+                enables: &["alloc"],
+                capabilities: &[],
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "heap-dram2",
+                display_name: "Adds a 64 KiB heap region in `.dram2_uninit`, RAM otherwise only used by the bootloader. Always added for Wi-Fi + BLE.",
+                enables: &["alloc"],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "heap-psram",
+                display_name: "Adds the external PSRAM as a heap region.",
+                enables: &["alloc"],
+                disables: &[],
+                capabilities: &[Capability::Psram],
+                // Older ESP32 revisions need a compiler workaround for the PSRAM
+                // cache issue, which is not available for Rust
+                min_chip_revision: &[(Chip::Esp32, 300)],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+        ],
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "tasks",
+        display_name: "Embassy tasks (requires `embassy`)",
+        options: &[
+            GeneratorOptionItem::Value(GeneratorValueOption {
+                name: "task-arena-size",
+                display_name: "Size of the arena all `embassy-executor` tasks are allocated from",
+                unit: "bytes",
+                default: 20480,
+                min: 1024,
+                max: 1024 * 1024,
+                enables: &["embassy"],
+                capabilities: &[],
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "stack-usage",
+                display_name: "Adds a `stack-usage` cargo alias and documents the task budget.",
+                enables: &["embassy"],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: true,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "trace",
+                display_name: "Traces when tasks are spawned, polled and the executor idles, over `defmt`. Requires `probe-rs`.",
+                enables: &["embassy", "probe-rs"],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+        ],
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "ui",
+        display_name: "Graphical user interface",
+        options: &[GeneratorOptionItem::Option(GeneratorOption {
+            name: "ui-slint",
+            display_name: "Renders a Slint UI to an SPI display. Requires `heap-psram`, not available with `embassy`.",
+            enables: &["heap-psram"],
+            disables: &["embassy"],
+            capabilities: &[Capability::Psram, Capability::Display],
+            min_chip_revision: &[],
+            requires_unstable_hal: true,
+            requires_nightly: false,
+            experimental: false,
+        })],
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "audio",
+        display_name: "Audio",
+        options: &[GeneratorOptionItem::Option(GeneratorOption {
+            name: "audio-out",
+            display_name: "Plays a tone through an I2S amplifier or DAC using DMA. Not available with `ui-slint`.",
+            enables: &[],
+            disables: &["ui-slint"],
+            capabilities: &[Capability::Audio],
+            min_chip_revision: &[],
+            requires_unstable_hal: true,
+            requires_nightly: false,
+            experimental: false,
+        })],
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "crypto",
+        display_name: "Cryptography",
+        options: &[GeneratorOptionItem::Option(GeneratorOption {
+            name: "hw-crypto",
+            display_name: "Benchmarks the SHA, AES and RSA accelerators against software implementations.",
+            enables: &[],
+            disables: &[],
+            capabilities: &[Capability::Sha],
+            min_chip_revision: &[],
+            requires_unstable_hal: true,
+            requires_nightly: false,
+            experimental: false,
+        })],
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "board",
+        display_name: "Board",
+        options: &[GeneratorOptionItem::Option(GeneratorOption {
+            name: "devkit",
+            display_name: "Targets the chip's Espressif DevKit, naming its pins in a `pins` module.",
+            enables: &[],
+            disables: &[],
+            capabilities: &[Capability::DevKit],
+            min_chip_revision: &[],
+            requires_unstable_hal: false,
+            requires_nightly: false,
+            experimental: false,
+        })],
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "optional",
+        display_name: "Options",
+        options: &[
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "wokwi",
+                display_name: "Adds support for Wokwi simulation using VS Code Wokwi extension.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[Capability::Wokwi],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "chip-docs",
+                display_name: "Adds a `docs/chip.md` describing the chip's hardware.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "ident",
+                display_name: "Adds an `ident` module deriving a unique device name from the MAC address.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "dev-container",
+                display_name: "Adds support for VS Code Dev Containers and GitHub Codespaces.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "ci",
+                display_name: "Adds GitHub Actions support with some basics checks.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "release-tooling",
+                display_name: "Adds git-cliff and cargo-release configuration, and a GitHub Actions workflow releasing the firmware image per tag.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "firmware-manifest",
+                display_name: "Adds a script building the firmware image and a `firmware-manifest.json` describing it for OTA servers.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "manufacturing",
+                display_name: "Adds scripts building a factory image and flashing it, with per-device data, to devices identified by their MAC address.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "provisioning",
+                display_name: "Adds a script writing per-device data, e.g. IDs and keys, from a CSV file for `manufacturing`, and code reading it at boot.",
+                enables: &["manufacturing"],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "web-installer",
+                display_name: "Adds an ESP Web Tools installer, published to GitHub Pages, flashing the firmware from the browser.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "docker-build",
+                display_name: "Adds a Docker image with pinned tools and a script building the firmware in it, for reproducible builds.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "release-small",
+                display_name: "Adds a `release-small` profile building `core` with `panic_immediate_abort`, for 1-2 MB flash. Needs nightly Rust on RISC-V.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: true,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "flip-link",
+                display_name: "Places the stack below the static data, so that a stack overflow faults instead of silently corrupting it.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[Capability::FlipLink],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "git-lfs",
+                display_name: "Stores the images and fonts of `ui-slint`, and the sounds of `audio-out`, with Git LFS.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "heartbeat",
+                display_name: "Toggles the board's LED every second, or logs where it is an RGB LED, showing that the firmware runs. Not available with `ui-slint`.",
+                enables: &[],
+                disables: &["ui-slint"],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "panic-policy",
+                display_name: "Reboots after a panic instead of halting, or halts or sleeps, as configured in `.cargo/config.toml`.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "reset-reason",
+                display_name: "Logs at boot why the chip last reset, e.g. a brownout, a watchdog or a panic, and provides it to the application.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "interrupts",
+                display_name: "Counts the presses of the BOOT button and the ticks of a timer in interrupt handlers, sharing the counts through `critical_section`. Not available with `embassy`.",
+                enables: &[],
+                disables: &["embassy"],
+                capabilities: &[Capability::DevKit],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "sync",
+                display_name: "Runs code on the second core and shares data with it through atomics, `critical_section` and `embassy-sync`, documenting what is safe across cores.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[Capability::MultiCore],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "app-config",
+                display_name: "Generates `src/cfg.rs` with application constants, e.g. intervals, thresholds and URLs, set in `.cargo/config.toml` and validated at build time.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "hal-facade",
+                display_name: "Imports the `esp-hal` types of the project through a `src/hal.rs` facade, so that migrating to a new `esp-hal` release changes one file.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "ram-placement",
+                display_name: "Adds examples of placing code in RAM with `#[ram]`, data in RTC memory and buffers for DMA, explaining when each is needed.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "map-file",
+                display_name: "Writes a linker map file with release builds and adds a script listing the largest symbols of each section.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "c-interop",
+                display_name: "Compiles C code from `csrc/` into the firmware, with a bindgen script generating its Rust declarations.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "c-logging",
+                display_name: "Adds the `ESP_LOGx` macros of ESP-IDF to `c-interop`, logging from C through the Rust logger.",
+                enables: &["c-interop"],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "examples",
+                display_name: "Adds examples to run with `cargo run --example`: blinking an LED, and scanning for Wi-Fi networks with `wifi`.",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: true,
+                requires_nightly: false,
+                experimental: false,
+            }),
+        ],
+    }),
+    GeneratorOptionItem::Category(GeneratorOptionCategory {
+        name: "editor",
+        display_name: "Optional editor config files for rust-analyzer",
+        options: &[
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "helix",
+                display_name: "Rust-Analyzer settings for Helix Editor",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+            GeneratorOptionItem::Option(GeneratorOption {
+                name: "vscode",
+                display_name: "Rust-Analyzer settings for Visual Studio Code",
+                enables: &[],
+                disables: &[],
+                capabilities: &[],
+                min_chip_revision: &[],
+                requires_unstable_hal: false,
+                requires_nightly: false,
+                experimental: false,
+            }),
+        ],
+    }),
+];
+
+/// Find the option (not category) with the given name, searching all
+/// categories.
+pub fn find_option_item(
+    name: &str,
+    items: &'static [GeneratorOptionItem],
+) -> Option<&'static GeneratorOptionItem> {
+    items.iter().find_map(|item| match item {
+        GeneratorOptionItem::Category(category) => find_option_item(name, category.options),
+        _ if item.name() == name => Some(item),
+        _ => None,
+    })
+}
+
+/// The option tree as presented to users. Experimental options are left out,
+/// along with the categories they leave empty, unless `experimental` is set,
+/// which moves them to a category of their own at the end.
+pub fn visible_options(experimental: bool) -> &'static [GeneratorOptionItem] {
+    fn split(
+        items: &'static [GeneratorOptionItem],
+        experimental: &mut Vec<GeneratorOptionItem>,
+    ) -> Vec<GeneratorOptionItem> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                GeneratorOptionItem::Category(category) => {
+                    let options = split(category.options, experimental);
+                    (!options.is_empty()).then(|| {
+                        GeneratorOptionItem::Category(GeneratorOptionCategory {
+                            options: options.leak(),
+                            ..*category
+                        })
+                    })
+                }
+                _ if item.is_experimental() => {
+                    experimental.push(*item);
+                    None
+                }
+                _ => Some(*item),
+            })
+            .collect()
+    }
+
+    let mut experimental_options = Vec::new();
+    let mut items = split(OPTIONS, &mut experimental_options);
+    if experimental && !experimental_options.is_empty() {
+        items.push(GeneratorOptionItem::Category(GeneratorOptionCategory {
+            name: "experimental",
+            display_name: "Experimental options, which may change or break",
+            options: experimental_options.leak(),
+        }));
+    }
+
+    // Built once per run, for the TUI or the web UI
+    items.leak()
+}
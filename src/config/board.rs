@@ -0,0 +1,231 @@
+//! The Espressif DevKit of each chip, whose pins the `devkit` option names in
+//! the generated `pins` module.
+
+use esp_metadata::Chip;
+
+/// A development board and the GPIO number of each pin function.
+pub struct Board {
+    pub chip: Chip,
+    pub name: &'static str,
+    /// The on-board LED. On most DevKits this is an addressable RGB LED.
+    pub led: u8,
+    /// Whether the LED is a plain one, which toggling its pin lights up.
+    pub plain_led: bool,
+    /// The BOOT button, usable by the application once booted.
+    pub button: u8,
+    pub sda: u8,
+    pub scl: u8,
+    /// The UART0 pins connected to the USB-to-UART bridge.
+    pub tx: u8,
+    pub rx: u8,
+    /// A free ADC1 pin, e.g. for measuring the battery voltage.
+    pub adc: u8,
+    /// The suggested wiring of an SPI display, if the chip is suited for
+    /// driving one.
+    pub display: Option<DisplayPins>,
+    /// The suggested wiring of an I2S amplifier or DAC, if the chip has I2S.
+    pub audio: Option<AudioPins>,
+}
+
+/// The GPIO numbers of an SPI display's pins.
+pub struct DisplayPins {
+    pub sck: u8,
+    pub mosi: u8,
+    pub cs: u8,
+    pub dc: u8,
+    pub reset: u8,
+}
+
+/// The GPIO numbers of an I2S amplifier's or DAC's pins.
+pub struct AudioPins {
+    pub bclk: u8,
+    pub ws: u8,
+    pub dout: u8,
+}
+
+impl Board {
+    /// The template variables holding the board's pins.
+    pub fn variables(&self) -> Vec<(String, String)> {
+        [
+            ("pin-led", self.led),
+            ("pin-button", self.button),
+            ("pin-sda", self.sda),
+            ("pin-scl", self.scl),
+            ("pin-tx", self.tx),
+            ("pin-rx", self.rx),
+            ("pin-adc", self.adc),
+        ]
+        .into_iter()
+        .chain(self.display.iter().flat_map(|display| {
+            [
+                ("pin-display-sck", display.sck),
+                ("pin-display-mosi", display.mosi),
+                ("pin-display-cs", display.cs),
+                ("pin-display-dc", display.dc),
+                ("pin-display-reset", display.reset),
+            ]
+        }))
+        .chain(self.audio.iter().flat_map(|audio| {
+            [
+                ("pin-audio-bclk", audio.bclk),
+                ("pin-audio-ws", audio.ws),
+                ("pin-audio-dout", audio.dout),
+            ]
+        }))
+        .map(|(name, gpio)| (name.to_string(), gpio.to_string()))
+        .chain([("board-name".to_string(), self.name.to_string())])
+        .collect()
+    }
+}
+
+// The I2C pins are a free choice on every chip, these avoid strapping, flash
+// and USB pins.
+const DEVKITS: &[Board] = &[
+    Board {
+        chip: Chip::Esp32,
+        name: "ESP32-DevKitC",
+        // The DevKitC has no user LED, GPIO2 is commonly used on other boards
+        led: 2,
+        plain_led: true,
+        button: 0,
+        sda: 21,
+        scl: 22,
+        tx: 1,
+        rx: 3,
+        adc: 34,
+        display: Some(DisplayPins {
+            sck: 18,
+            mosi: 23,
+            cs: 5,
+            dc: 16,
+            reset: 17,
+        }),
+        audio: Some(AudioPins {
+            bclk: 26,
+            ws: 25,
+            dout: 33,
+        }),
+    },
+    Board {
+        chip: Chip::Esp32c2,
+        name: "ESP8684-DevKitM-1",
+        led: 8,
+        plain_led: false,
+        button: 9,
+        sda: 4,
+        scl: 5,
+        tx: 20,
+        rx: 19,
+        adc: 3,
+        display: None,
+        audio: None,
+    },
+    Board {
+        chip: Chip::Esp32c3,
+        name: "ESP32-C3-DevKitM-1",
+        led: 8,
+        plain_led: false,
+        button: 9,
+        sda: 4,
+        scl: 5,
+        tx: 21,
+        rx: 20,
+        adc: 3,
+        display: None,
+        audio: Some(AudioPins {
+            bclk: 6,
+            ws: 7,
+            dout: 10,
+        }),
+    },
+    Board {
+        chip: Chip::Esp32c6,
+        name: "ESP32-C6-DevKitC-1",
+        led: 8,
+        plain_led: false,
+        button: 9,
+        sda: 6,
+        scl: 7,
+        tx: 16,
+        rx: 17,
+        adc: 3,
+        display: None,
+        audio: Some(AudioPins {
+            bclk: 20,
+            ws: 21,
+            dout: 22,
+        }),
+    },
+    Board {
+        chip: Chip::Esp32h2,
+        name: "ESP32-H2-DevKitM-1",
+        led: 8,
+        plain_led: false,
+        button: 9,
+        sda: 12,
+        scl: 22,
+        tx: 24,
+        rx: 23,
+        adc: 3,
+        display: None,
+        audio: Some(AudioPins {
+            bclk: 4,
+            ws: 5,
+            dout: 10,
+        }),
+    },
+    Board {
+        chip: Chip::Esp32s2,
+        name: "ESP32-S2-DevKitM-1",
+        led: 18,
+        plain_led: false,
+        button: 0,
+        sda: 8,
+        scl: 9,
+        tx: 43,
+        rx: 44,
+        adc: 4,
+        display: Some(DisplayPins {
+            sck: 36,
+            mosi: 35,
+            cs: 34,
+            dc: 33,
+            reset: 21,
+        }),
+        audio: Some(AudioPins {
+            bclk: 5,
+            ws: 6,
+            dout: 7,
+        }),
+    },
+    Board {
+        chip: Chip::Esp32s3,
+        name: "ESP32-S3-DevKitC-1",
+        // v1.0 of the board, the LED moved to GPIO38 in v1.1
+        led: 48,
+        plain_led: false,
+        button: 0,
+        sda: 8,
+        scl: 9,
+        tx: 43,
+        rx: 44,
+        adc: 4,
+        display: Some(DisplayPins {
+            sck: 12,
+            mosi: 11,
+            cs: 10,
+            dc: 13,
+            reset: 14,
+        }),
+        audio: Some(AudioPins {
+            bclk: 5,
+            ws: 6,
+            dout: 7,
+        }),
+    },
+];
+
+/// The Espressif DevKit of the chip, if any.
+pub fn devkit(chip: Chip) -> Option<&'static Board> {
+    DEVKITS.iter().find(|board| board.chip == chip)
+}
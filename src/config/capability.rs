@@ -0,0 +1,248 @@
+//! What each chip offers, as far as the options and templates are concerned.
+//!
+//! Capabilities are derived from `esp-metadata` wherever it has the
+//! information, so supporting a new chip only requires adding its data here
+//! (e.g. its Wokwi board) rather than touching options or templates. They
+//! also cover facts like the architecture and number of cores, so templates
+//! never need to enumerate chip names.
+
+use esp_metadata::{Arch, Chip, Config, Cores};
+use serde::{Serialize, Serializer};
+
+use super::board::devkit;
+
+/// A capability of a chip, which options can require and templates can check
+/// for as `option("has-<name>")`, or `option("riscv")` and `option("xtensa")`
+/// for the architecture. Serialized as its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capability {
+    /// A RISC-V CPU.
+    RiscV,
+    /// An Xtensa CPU.
+    Xtensa,
+    /// A second CPU core.
+    MultiCore,
+    /// A low-power RISC-V core, which keeps running while the main core is
+    /// in deep sleep.
+    LpCore,
+    /// The USB Serial/JTAG controller, used to flash and monitor the chip
+    /// over its USB port without a USB-UART bridge.
+    UsbSerialJtag,
+    /// A Wi-Fi radio.
+    Wifi,
+    /// A Bluetooth LE radio.
+    Bt,
+    /// The SYSTIMER peripheral, used as the embassy time driver when present.
+    Systimer,
+    /// Support for external PSRAM.
+    Psram,
+    /// A development board simulated by Wokwi.
+    Wokwi,
+    /// An Espressif DevKit with known pins.
+    DevKit,
+    /// A plain LED on the DevKit, rather than an addressable RGB LED.
+    Led,
+    /// Calibrated ADC readings in millivolts.
+    AdcCalibration,
+    /// Suggested pins for an SPI display.
+    Display,
+    /// Suggested pins for an I2S amplifier or DAC.
+    Audio,
+    /// The SHA accelerator.
+    Sha,
+    /// The AES accelerator.
+    Aes,
+    /// The RSA accelerator.
+    Rsa,
+    /// The general DMA controller, whose channels any peripheral can use.
+    /// Chips without it have a dedicated DMA channel per peripheral.
+    Gdma,
+    /// The `flip-link` feature of `esp-hal`, placing the stack below the
+    /// static data so that an overflow faults instead of corrupting it.
+    FlipLink,
+    /// Deep sleep woken up by a timer, as implemented by `esp-hal`.
+    DeepSleep,
+    /// RTC fast memory, which keeps its contents across all but power-on
+    /// resets.
+    RtcRam,
+    /// The `WAFER_VERSION_MAJOR` and `WAFER_VERSION_MINOR` eFuse fields
+    /// holding the chip revision. The ESP32 encodes it differently.
+    WaferVersion,
+    /// The minor chip revision split into the `WAFER_VERSION_MINOR_HI` and
+    /// `WAFER_VERSION_MINOR_LO` eFuse fields.
+    SplitWaferMinor,
+}
+
+impl Capability {
+    pub const ALL: &'static [Capability] = &[
+        Capability::RiscV,
+        Capability::Xtensa,
+        Capability::MultiCore,
+        Capability::LpCore,
+        Capability::UsbSerialJtag,
+        Capability::Wifi,
+        Capability::Bt,
+        Capability::Systimer,
+        Capability::Psram,
+        Capability::Wokwi,
+        Capability::DevKit,
+        Capability::Led,
+        Capability::AdcCalibration,
+        Capability::Display,
+        Capability::Audio,
+        Capability::Sha,
+        Capability::Aes,
+        Capability::Rsa,
+        Capability::Gdma,
+        Capability::FlipLink,
+        Capability::DeepSleep,
+        Capability::RtcRam,
+        Capability::WaferVersion,
+        Capability::SplitWaferMinor,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::RiscV => "riscv",
+            Capability::Xtensa => "xtensa",
+            Capability::MultiCore => "multi-core",
+            Capability::LpCore => "lp-core",
+            Capability::UsbSerialJtag => "usb-serial-jtag",
+            Capability::Wifi => "wifi",
+            Capability::Bt => "bt",
+            Capability::Systimer => "systimer",
+            Capability::Psram => "psram",
+            Capability::Wokwi => "wokwi",
+            Capability::DevKit => "devkit",
+            Capability::Led => "led",
+            Capability::AdcCalibration => "adc-calibration",
+            Capability::Display => "display",
+            Capability::Audio => "audio",
+            Capability::Sha => "sha",
+            Capability::Aes => "aes",
+            Capability::Rsa => "rsa",
+            Capability::Gdma => "gdma",
+            Capability::FlipLink => "flip-link",
+            Capability::DeepSleep => "deep-sleep",
+            Capability::RtcRam => "rtc-ram",
+            Capability::WaferVersion => "wafer-version",
+            Capability::SplitWaferMinor => "split-wafer-minor",
+        }
+    }
+
+    /// The name templates check the capability as.
+    pub fn template_option(self) -> String {
+        match self {
+            // Checked for in so many places that the prefix only adds noise
+            Capability::RiscV | Capability::Xtensa => self.name().to_string(),
+            _ => format!("has-{}", self.name()),
+        }
+    }
+
+    /// Whether the chip has this capability.
+    pub fn supported_by(self, chip: Chip) -> bool {
+        let config = Config::for_chip(&chip);
+        match self {
+            Capability::RiscV => config.arch() == Arch::RiscV,
+            Capability::Xtensa => config.arch() == Arch::Xtensa,
+            Capability::MultiCore => config.cores() == Cores::Multi,
+            Capability::LpCore => config.contains("lp_core"),
+            Capability::UsbSerialJtag => config.contains("usb_device"),
+            Capability::Wokwi => wokwi_board(chip).is_some(),
+            Capability::DevKit => devkit(chip).is_some(),
+            Capability::Led => devkit(chip).is_some_and(|board| board.plain_led),
+            Capability::AdcCalibration => ADC_CALIBRATION.contains(&chip),
+            Capability::Display => devkit(chip).is_some_and(|board| board.display.is_some()),
+            Capability::Audio => devkit(chip).is_some_and(|board| board.audio.is_some()),
+            Capability::FlipLink => FLIP_LINK.contains(&chip),
+            Capability::DeepSleep => DEEP_SLEEP.contains(&chip),
+            Capability::RtcRam => chip != Chip::Esp32c2,
+            Capability::WaferVersion => chip != Chip::Esp32,
+            Capability::SplitWaferMinor => SPLIT_WAFER_MINOR.contains(&chip),
+            // These are all symbols of the chip's `esp-metadata` device
+            _ => config.contains(self.name()),
+        }
+    }
+}
+
+impl Serialize for Capability {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// The Wokwi board of each chip which has one.
+const WOKWI_BOARDS: &[(Chip, &str)] = &[
+    (Chip::Esp32, "board-esp32-devkit-c-v4"),
+    (Chip::Esp32c3, "board-esp32-c3-devkitm-1"),
+    (Chip::Esp32c6, "board-esp32-c6-devkitc-1"),
+    (Chip::Esp32h2, "board-esp32-h2-devkitm-1"),
+    (Chip::Esp32s2, "board-esp32-s2-devkitm-1"),
+    (Chip::Esp32s3, "board-esp32-s3-devkitc-1"),
+];
+
+/// The Wokwi board simulating the chip, if any.
+pub fn wokwi_board(chip: Chip) -> Option<&'static str> {
+    WOKWI_BOARDS
+        .iter()
+        .find(|(c, _)| *c == chip)
+        .map(|(_, board)| *board)
+}
+
+/// The chips for which `esp-hal` implements the curve fitting ADC calibration.
+const ADC_CALIBRATION: &[Chip] = &[Chip::Esp32c3, Chip::Esp32c6, Chip::Esp32s3];
+
+/// The chips whose `esp-hal` linker scripts support `flip-link`.
+const FLIP_LINK: &[Chip] = &[Chip::Esp32c6, Chip::Esp32h2];
+
+/// The chips for which `esp-hal` implements deep sleep.
+const DEEP_SLEEP: &[Chip] = &[
+    Chip::Esp32,
+    Chip::Esp32c2,
+    Chip::Esp32c3,
+    Chip::Esp32c6,
+    Chip::Esp32s3,
+];
+
+/// The chips whose minor revision is split across two eFuse fields.
+const SPLIT_WAFER_MINOR: &[Chip] = &[Chip::Esp32c3, Chip::Esp32s2, Chip::Esp32s3];
+
+/// The internal SRAM of each chip, in KiB.
+const INTERNAL_RAM_KIB: &[(Chip, u32)] = &[
+    (Chip::Esp32, 520),
+    (Chip::Esp32c2, 272),
+    (Chip::Esp32c3, 400),
+    (Chip::Esp32c6, 512),
+    (Chip::Esp32h2, 320),
+    (Chip::Esp32s2, 320),
+    (Chip::Esp32s3, 512),
+];
+
+/// The size of the chip's internal SRAM in KiB, which `esp-metadata` does not
+/// provide.
+pub fn internal_ram_kib(chip: Chip) -> Option<u32> {
+    INTERNAL_RAM_KIB
+        .iter()
+        .find(|(c, _)| *c == chip)
+        .map(|(_, size)| *size)
+}
+
+/// The Rust target of `std` applications on ESP-IDF for the chip.
+pub fn esp_idf_target(chip: Chip) -> &'static str {
+    match chip {
+        Chip::Esp32 => "xtensa-esp32-espidf",
+        Chip::Esp32s2 => "xtensa-esp32s2-espidf",
+        Chip::Esp32s3 => "xtensa-esp32s3-espidf",
+        Chip::Esp32c2 | Chip::Esp32c3 => "riscv32imc-esp-espidf",
+        Chip::Esp32c6 | Chip::Esp32h2 => "riscv32imac-esp-espidf",
+    }
+}
+
+/// The capabilities of the chip as template options, e.g. `has-systimer`.
+pub fn template_options(chip: Chip) -> Vec<String> {
+    Capability::ALL
+        .iter()
+        .filter(|capability| capability.supported_by(chip))
+        .map(|capability| capability.template_option())
+        .collect()
+}
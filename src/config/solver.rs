@@ -0,0 +1,816 @@
+//! Reasoning about combinations of options: which options a selection
+//! implies, whether it is valid for a chip, and how to fix it when it is not.
+//!
+//! This is shared by headless validation, the TUI and, through the hidden
+//! `configurations` and `option-graph` subcommands, by xtask.
+
+use std::fmt;
+
+use esp_metadata::Chip;
+use serde::Serialize;
+
+use super::{find_option_item, split_value, GeneratorOptionItem, OPTIONS};
+
+/// The name of a selected option, without its value.
+fn option_name(option: &str) -> &str {
+    split_value(option).map_or(option, |(name, _)| name)
+}
+
+/// The option enabling the `unstable` feature of `esp-hal`.
+pub const UNSTABLE_HAL: &str = "unstable-hal";
+
+/// Whether the selection is a `std` application on ESP-IDF, which does not
+/// use `esp-hal`.
+fn is_esp_idf_std<S: AsRef<str>>(options: &[S]) -> bool {
+    options.iter().any(|o| o.as_ref() == "esp-idf-std")
+}
+
+/// The options an option requires: those it enables and, unless the
+/// project is a `std` one, `unstable-hal` if it uses unstable `esp-hal` APIs.
+fn requirements(item: &'static GeneratorOptionItem, esp_idf_std: bool) -> Vec<&'static str> {
+    let mut requirements = item.enables().to_vec();
+    if item.requires_unstable_hal() && !esp_idf_std {
+        requirements.push(UNSTABLE_HAL);
+    }
+
+    requirements
+}
+
+/// The selection along with the requirements of every selected option,
+/// transitively.
+pub fn implied(options: &[String]) -> Vec<String> {
+    implied_for(options, is_esp_idf_std(options))
+}
+
+fn implied_for(options: &[String], esp_idf_std: bool) -> Vec<String> {
+    let mut implied = options.to_vec();
+
+    let mut index = 0;
+    while index < implied.len() {
+        if let Some(item) = find_option_item(option_name(&implied[index]), OPTIONS) {
+            for requirement in requirements(item, esp_idf_std) {
+                if !implied.iter().any(|o| o == requirement) {
+                    implied.push(requirement.to_string());
+                }
+            }
+        }
+        index += 1;
+    }
+
+    implied
+}
+
+/// The selected options, including those implied by them, which need a
+/// nightly toolchain on RISC-V chips.
+pub fn requiring_nightly(options: &[String]) -> Vec<&'static str> {
+    implied(options)
+        .iter()
+        .filter_map(
+            |option| match find_option_item(option_name(option), OPTIONS)? {
+                GeneratorOptionItem::Option(option) if option.requires_nightly => Some(option.name),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// Whether the option, or one of its requirements, needs `unstable-hal` in a
+/// bare-metal project.
+pub fn needs_unstable_hal(option: &str) -> bool {
+    option != UNSTABLE_HAL
+        && implied_for(&[option.to_string()], false)
+            .iter()
+            .any(|o| o == UNSTABLE_HAL)
+}
+
+/// The options disabled by any of the selected options.
+pub fn disabled(options: &[String]) -> Vec<&'static str> {
+    options
+        .iter()
+        .filter_map(|option| find_option_item(option_name(option), OPTIONS))
+        .flat_map(|item| match item {
+            GeneratorOptionItem::Option(option) => option.disables,
+            _ => &[],
+        })
+        .copied()
+        .collect()
+}
+
+/// Whether two options cannot be selected together, because one of them, or
+/// one of their requirements, disables the other or one of its requirements.
+pub fn are_exclusive(a: &str, b: &str) -> bool {
+    // What an option requires depends on the flavor
+    let esp_idf_std = is_esp_idf_std(&[a, b]);
+    let a = implied_for(&[a.to_string()], esp_idf_std);
+    let b = implied_for(&[b.to_string()], esp_idf_std);
+
+    let conflicts = |x: &[String], y: &[String]| {
+        disabled(x)
+            .iter()
+            .any(|disabled| y.iter().any(|o| option_name(o) == *disabled))
+    };
+
+    conflicts(&a, &b) || conflicts(&b, &a)
+}
+
+/// Why a selection of options is not valid for a chip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// No option has this name. The closest names are suggested instead.
+    Unknown {
+        option: String,
+        suggestions: Vec<String>,
+    },
+    /// A value option was selected without a value.
+    MissingValue { option: String },
+    /// A value was given to an option which does not take one.
+    UnexpectedValue { option: String },
+    /// The value is not a number within the option's range.
+    InvalidValue {
+        option: String,
+        min: u32,
+        max: u32,
+        unit: &'static str,
+    },
+    /// The option is not available for the chip.
+    Unsupported { option: String, chip: Chip },
+    /// An option required by the option is not selected.
+    MissingRequirement { option: String, requirement: String },
+    /// The option is disabled by another selected option.
+    Conflict { option: String, with: String },
+    /// The option is experimental, and experimental options are not enabled.
+    Experimental { option: String },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Unknown {
+                option,
+                suggestions,
+            } => {
+                write!(f, "Unknown option '{option}'")?;
+                match suggestions.as_slice() {
+                    [] => Ok(()),
+                    [suggestion] => write!(f, ", did you mean '{suggestion}'?"),
+                    suggestions => {
+                        write!(f, ", did you mean one of '{}'?", suggestions.join("', '"))
+                    }
+                }
+            }
+            Violation::MissingValue { option } => write!(
+                f,
+                "Option '{option}' requires a value, e.g. '{option}=<value>'"
+            ),
+            Violation::UnexpectedValue { option } => {
+                write!(f, "Option '{option}' does not take a value")
+            }
+            Violation::InvalidValue {
+                option,
+                min,
+                max,
+                unit,
+            } => write!(
+                f,
+                "Option '{option}' must be a number between {min} and {max} ({unit})"
+            ),
+            Violation::Unsupported { option, chip } => {
+                write!(f, "Option '{option}' is not supported for chip {chip}")
+            }
+            Violation::MissingRequirement {
+                option,
+                requirement,
+            } => write!(f, "Option '{option}' requires '{requirement}'"),
+            Violation::Conflict { option, with } => {
+                write!(f, "Option '{option}' is not available with '{with}'")
+            }
+            Violation::Experimental { option } => write!(
+                f,
+                "Option '{option}' is experimental, pass `--experimental` to use it"
+            ),
+        }
+    }
+}
+
+/// Everything which makes the selection invalid for the given chip, in the
+/// order of the selected options. The selection is valid if there is nothing.
+pub fn violations(chip: Chip, options: &[String]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let esp_idf_std = is_esp_idf_std(options);
+
+    for option in options {
+        let (name, value) = match split_value(option) {
+            Some((name, value)) => (name, Some(value)),
+            None => (option.as_str(), None),
+        };
+        let Some(item) = find_option_item(name, OPTIONS) else {
+            violations.push(Violation::Unknown {
+                option: name.to_string(),
+                suggestions: similar_options(name),
+            });
+            continue;
+        };
+        let option = name.to_string();
+
+        match (item, value) {
+            (GeneratorOptionItem::Value(value_option), Some(value)) => {
+                if value_option.parse(value).is_err() {
+                    violations.push(Violation::InvalidValue {
+                        option: option.clone(),
+                        min: value_option.min,
+                        max: value_option.max,
+                        unit: value_option.unit,
+                    });
+                }
+            }
+            (GeneratorOptionItem::Value(_), None) => violations.push(Violation::MissingValue {
+                option: option.clone(),
+            }),
+            (_, Some(_)) => violations.push(Violation::UnexpectedValue {
+                option: option.clone(),
+            }),
+            (_, None) => {}
+        }
+
+        if !item.supports(chip) {
+            violations.push(Violation::Unsupported {
+                option: option.clone(),
+                chip,
+            });
+        }
+
+        for requirement in requirements(item, esp_idf_std) {
+            if !options.iter().any(|o| o == requirement) {
+                violations.push(Violation::MissingRequirement {
+                    option: option.clone(),
+                    requirement: requirement.to_string(),
+                });
+            }
+        }
+
+        if let Some(other) = options
+            .iter()
+            .find(|other| disabled(std::slice::from_ref(*other)).contains(&name))
+        {
+            violations.push(Violation::Conflict {
+                option,
+                with: option_name(other).to_string(),
+            });
+        }
+    }
+
+    violations
+}
+
+/// The most similar options to a name which is not one, the closest first, to
+/// catch typos like `wifl`. Only names within a few edits are suggested.
+pub fn similar_options(name: &str) -> Vec<String> {
+    const MAX_SUGGESTIONS: usize = 3;
+
+    // Allow an edit for every three characters, so short names do not match
+    // everything
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut similar = OPTIONS
+        .iter()
+        .flat_map(|item| item.options())
+        .map(|option| (edit_distance(name, &option), option))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    similar.sort();
+
+    similar
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, option)| option)
+        .collect()
+}
+
+/// The Levenshtein distance of two strings: the number of characters to
+/// insert, remove or replace to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a != *b);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// The options which can be selected for the given chip, in the order of the
+/// option tree.
+pub fn available_options(chip: Chip) -> Vec<String> {
+    OPTIONS
+        .iter()
+        .flat_map(|item| item.options())
+        .filter(|option| find_option_item(option, OPTIONS).is_some_and(|item| item.supports(chip)))
+        .collect()
+}
+
+/// The selected experimental options, which are rejected unless experimental
+/// options are enabled.
+pub fn experimental(options: &[String]) -> Vec<Violation> {
+    options
+        .iter()
+        .filter(|option| {
+            find_option_item(option_name(option), OPTIONS)
+                .is_some_and(|item| item.is_experimental())
+        })
+        .map(|option| Violation::Experimental {
+            option: option_name(option).to_string(),
+        })
+        .collect()
+}
+
+/// Whether the selection is valid for the given chip: every option is known,
+/// available for the chip and given a value in range if, and only if, it takes
+/// one. All requirements must be selected and no option may be disabled by
+/// another.
+pub fn is_valid(chip: Chip, options: &[String]) -> bool {
+    violations(chip, options).is_empty()
+}
+
+/// The most options [`valid_configurations`] combines, i.e. 2^16
+/// configurations to check.
+pub const MAX_COMBINED_OPTIONS: usize = 16;
+
+/// Every valid configuration for the given chip consisting only of the given
+/// options. Value options are left out, so their defaults apply.
+///
+/// The number of candidates grows exponentially, so only pass the options of
+/// interest. More than [`MAX_COMBINED_OPTIONS`] candidates are rejected.
+pub fn valid_configurations(chip: Chip, options: &[&str]) -> Result<Vec<Vec<String>>, String> {
+    let candidates = options
+        .iter()
+        .filter(|option| {
+            find_option_item(option, OPTIONS)
+                .is_some_and(|item| !item.is_value() && item.supports(chip))
+        })
+        .collect::<Vec<_>>();
+    if candidates.len() > MAX_COMBINED_OPTIONS {
+        return Err(format!(
+            "Too many options to combine, {} are available for the {chip} but at most {MAX_COMBINED_OPTIONS} can be combined",
+            candidates.len()
+        ));
+    }
+
+    Ok((0..1u32 << candidates.len())
+        .map(|mask| {
+            candidates
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, option)| option.to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|configuration| is_valid(chip, configuration))
+        .collect())
+}
+
+/// An option and its constraints, as a node of the option graph.
+#[derive(Debug, Serialize)]
+pub struct OptionNode {
+    pub name: &'static str,
+    /// The innermost category containing the option, if any.
+    pub category: Option<&'static str>,
+    pub enables: &'static [&'static str],
+    pub disables: &'static [&'static str],
+    pub capabilities: Vec<&'static str>,
+    /// Whether the option requires `unstable-hal` in bare-metal projects.
+    pub requires_unstable_hal: bool,
+}
+
+/// Every option along with the options it requires and conflicts with.
+pub fn graph() -> Vec<OptionNode> {
+    fn collect(
+        items: &'static [GeneratorOptionItem],
+        category: Option<&'static str>,
+        nodes: &mut Vec<OptionNode>,
+    ) {
+        for item in items {
+            let (name, disables, capabilities) = match item {
+                GeneratorOptionItem::Category(inner) => {
+                    collect(inner.options, Some(inner.name), nodes);
+                    continue;
+                }
+                GeneratorOptionItem::Option(option) => {
+                    (option.name, option.disables, option.capabilities)
+                }
+                GeneratorOptionItem::Value(option) => (option.name, &[][..], option.capabilities),
+            };
+
+            nodes.push(OptionNode {
+                name,
+                category,
+                enables: item.enables(),
+                disables,
+                capabilities: capabilities.iter().map(|c| c.name()).collect(),
+                requires_unstable_hal: item.requires_unstable_hal(),
+            });
+        }
+    }
+
+    let mut nodes = Vec::new();
+    collect(OPTIONS, None, &mut nodes);
+
+    nodes
+}
+
+/// The changes which make a selection of options valid.
+#[derive(Debug, Default, PartialEq)]
+pub struct OptionsFix {
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+impl OptionsFix {
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty()
+    }
+
+    /// Drop an option from the selection, undoing its addition if it was added
+    /// by this fix.
+    pub fn drop(&mut self, option: &str) {
+        if let Some(index) = self.add.iter().position(|o| o == option) {
+            self.add.remove(index);
+        } else {
+            self.remove.push(option.to_string());
+        }
+    }
+
+    pub fn apply(&self, options: &[String]) -> Vec<String> {
+        options
+            .iter()
+            .filter(|option| !self.remove.contains(option))
+            .chain(&self.add)
+            .cloned()
+            .collect()
+    }
+}
+
+impl fmt::Display for OptionsFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quote = |options: &[String]| {
+            options
+                .iter()
+                .map(|option| format!("'{option}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match (self.add.is_empty(), self.remove.is_empty()) {
+            (false, false) => write!(
+                f,
+                "add {}, remove {}",
+                quote(&self.add),
+                quote(&self.remove)
+            ),
+            (false, true) => write!(f, "add {}", quote(&self.add)),
+            (true, false) => write!(f, "remove {}", quote(&self.remove)),
+            (true, true) => write!(f, "nothing to change"),
+        }
+    }
+}
+
+/// Compute the smallest change making the selected options valid for the given
+/// chip: options which cannot be used on the chip, or with the given value, are
+/// removed and missing requirements are added. Out of range values are clamped,
+/// unknown options are replaced by the closest option.
+///
+/// An option is only removed when one of its requirements is not available for
+/// the chip.
+pub fn suggest_fix(chip: Chip, options: &[String]) -> OptionsFix {
+    let mut fix = OptionsFix::default();
+    let mut selected = Vec::new();
+
+    for option in options {
+        let (name, value) = match split_value(option) {
+            Some((name, value)) => (name, Some(value)),
+            None => (option.as_str(), None),
+        };
+
+        // Unknown options are rejected, they are replaced by the closest
+        // option, e.g. a typo by the option meant, or dropped if there is none
+        let Some(item) = find_option_item(name, OPTIONS) else {
+            fix.drop(option);
+            let closest = similar_options(name).into_iter().next().filter(|closest| {
+                find_option_item(closest, OPTIONS)
+                    .is_some_and(|item| !item.is_value() && item.supports(chip))
+                    && !options.contains(closest)
+                    && !selected.contains(closest)
+            });
+            if let Some(closest) = closest {
+                fix.add.push(closest.clone());
+                selected.push(closest);
+            }
+            continue;
+        };
+
+        if !item.supports(chip) {
+            fix.drop(option);
+            continue;
+        }
+
+        match (item, value) {
+            (GeneratorOptionItem::Value(value_option), Some(value)) => {
+                if value_option.parse(value).is_ok() {
+                    selected.push(option.clone());
+                } else {
+                    fix.drop(option);
+                    if let Ok(value) = value.parse::<u32>() {
+                        let clamped =
+                            format!("{name}={}", value.clamp(value_option.min, value_option.max));
+                        fix.add.push(clamped.clone());
+                        selected.push(clamped);
+                    }
+                }
+            }
+            // Without a value the default is used, which is what leaving the
+            // option out does too
+            (GeneratorOptionItem::Value(_), None) => fix.drop(option),
+            (_, Some(_)) => {
+                fix.drop(option);
+                fix.add.push(name.to_string());
+                selected.push(name.to_string());
+            }
+            (_, None) => selected.push(option.clone()),
+        }
+    }
+
+    // Of two mutually exclusive options, keep the one selected first
+    let mut index = 0;
+    while index < selected.len() {
+        let name = option_name(&selected[index]);
+        if selected[..index]
+            .iter()
+            .any(|other| are_exclusive(option_name(other), name))
+        {
+            let option = selected.remove(index);
+            fix.drop(&option);
+        } else {
+            index += 1;
+        }
+    }
+
+    let esp_idf_std = is_esp_idf_std(&selected);
+    resolve_requirements(
+        &mut selected,
+        &mut fix,
+        |name| {
+            find_option_item(name, OPTIONS)
+                .map(|item| requirements(item, esp_idf_std))
+                .unwrap_or_default()
+        },
+        |name| find_option_item(name, OPTIONS).is_some_and(|item| item.supports(chip)),
+    );
+
+    fix
+}
+
+/// Add the requirements of every selected option, until nothing is missing.
+/// An option whose requirement is not supported, or was dropped already, is
+/// dropped too, so that its own dependents are dropped in turn rather than
+/// adding it back.
+fn resolve_requirements<'a>(
+    selected: &mut Vec<String>,
+    fix: &mut OptionsFix,
+    requirements: impl Fn(&str) -> Vec<&'a str>,
+    supported: impl Fn(&str) -> bool,
+) {
+    let mut dropped: Vec<String> = Vec::new();
+    'resolve: loop {
+        for option in selected.iter() {
+            for requirement in requirements(option_name(option)) {
+                if selected.iter().any(|o| o == requirement) {
+                    continue;
+                }
+
+                let option = option.clone();
+                let was_dropped = dropped.iter().any(|d| option_name(d) == requirement);
+                if supported(requirement) && !was_dropped {
+                    fix.add.push(requirement.to_string());
+                    selected.push(requirement.to_string());
+                } else {
+                    fix.drop(&option);
+                    selected.retain(|o| *o != option);
+                    dropped.push(option);
+                }
+
+                continue 'resolve;
+            }
+        }
+
+        break;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solver() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        let implied = implied(&options(&["heap-psram"]));
+        assert_eq!(implied, options(&["heap-psram", "alloc", "unstable-hal"]));
+
+        assert!(is_valid(
+            Chip::Esp32,
+            &options(&["alloc", "wifi", "ble", "unstable-hal"])
+        ));
+        assert!(!is_valid(Chip::Esp32, &options(&["wifi"])));
+        assert!(!is_valid(Chip::Esp32h2, &options(&["alloc", "wifi"])));
+        assert!(!is_valid(Chip::Esp32, &options(&["alloc", "heap-size=1"])));
+        assert!(!is_valid(Chip::Esp32, &options(&["unknown"])));
+
+        assert!(!are_exclusive("wifi", "ble"));
+
+        let options_of_chip = available_options(Chip::Esp32c3);
+        let options_of_chip = options_of_chip
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        assert!(valid_configurations(Chip::Esp32c3, &options_of_chip).is_err());
+
+        let configurations = valid_configurations(
+            Chip::Esp32c3,
+            &["alloc", "wifi", "heap-psram", "unstable-hal"],
+        )
+        .unwrap();
+        assert_eq!(
+            configurations,
+            vec![
+                options(&[]),
+                options(&["alloc"]),
+                options(&["unstable-hal"]),
+                options(&["alloc", "unstable-hal"]),
+                options(&["alloc", "wifi", "unstable-hal"])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suggest_fix() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        let fix = suggest_fix(Chip::Esp32c3, &options(&["wifi", "heap-size=4096"]));
+        assert_eq!(
+            fix.add,
+            options(&["heap-size=256", "alloc", "unstable-hal"])
+        );
+        assert_eq!(fix.remove, options(&["heap-size=4096"]));
+
+        let fixed = fix.apply(&options(&["wifi", "heap-size=4096"]));
+        assert!(violations(Chip::Esp32c3, &fixed).is_empty());
+
+        // `heap-psram` is not available on the ESP32-C3
+        let fix = suggest_fix(Chip::Esp32c3, &options(&["alloc", "heap-psram"]));
+        assert!(fix.add.is_empty());
+        assert_eq!(fix.remove, options(&["heap-psram"]));
+
+        assert!(suggest_fix(Chip::Esp32, &options(&["alloc", "wifi", "unstable-hal"])).is_empty());
+
+        // Unknown options are replaced by the closest one, unless it is
+        // selected already
+        let fix = suggest_fix(Chip::Esp32c3, &options(&["wifl", "wifi", "alloc"]));
+        assert_eq!(fix.add, options(&["unstable-hal"]));
+        assert_eq!(fix.remove, options(&["wifl"]));
+        let fix = suggest_fix(Chip::Esp32c3, &options(&["alloc", "embasy"]));
+        assert_eq!(fix.add, options(&["embassy", "unstable-hal"]));
+        assert_eq!(fix.remove, options(&["embasy"]));
+        for options in [
+            options(&["wifl", "wifi", "alloc"]),
+            options(&["completely-different"]),
+        ] {
+            let fixed = suggest_fix(Chip::Esp32c3, &options).apply(&options);
+            assert!(violations(Chip::Esp32c3, &fixed).is_empty(), "{fixed:?}");
+        }
+
+        // `y` requires `x`, which requires the unsupported `z`: both are
+        // dropped, rather than adding `x` back forever
+        let mut selected = options(&["alloc", "y"]);
+        let mut fix = OptionsFix::default();
+        resolve_requirements(
+            &mut selected,
+            &mut fix,
+            |name| match name {
+                "y" => vec!["x"],
+                "x" => vec!["z"],
+                _ => vec![],
+            },
+            |name| name != "z",
+        );
+        assert_eq!(selected, options(&["alloc"]));
+        assert!(fix.add.is_empty());
+        assert_eq!(fix.remove, options(&["y"]));
+    }
+
+    #[test]
+    fn test_violations() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        assert!(violations(Chip::Esp32c3, &options(&["alloc", "wifi", "unstable-hal"])).is_empty());
+
+        let violations = violations(
+            Chip::Esp32c2,
+            &options(&[
+                "wifl",
+                "wifi",
+                "heap-psram",
+                "heap-size=4096",
+                "embassy",
+                "ui-slint",
+                "unstable-hal",
+            ]),
+        );
+        assert_eq!(
+            violations,
+            [
+                Violation::Unknown {
+                    option: "wifl".to_string(),
+                    suggestions: vec!["wifi".to_string()]
+                },
+                Violation::MissingRequirement {
+                    option: "wifi".to_string(),
+                    requirement: "alloc".to_string()
+                },
+                Violation::Unsupported {
+                    option: "heap-psram".to_string(),
+                    chip: Chip::Esp32c2
+                },
+                Violation::MissingRequirement {
+                    option: "heap-psram".to_string(),
+                    requirement: "alloc".to_string()
+                },
+                Violation::InvalidValue {
+                    option: "heap-size".to_string(),
+                    min: 8,
+                    max: 256,
+                    unit: "KiB"
+                },
+                Violation::MissingRequirement {
+                    option: "heap-size".to_string(),
+                    requirement: "alloc".to_string()
+                },
+                Violation::Conflict {
+                    option: "embassy".to_string(),
+                    with: "ui-slint".to_string()
+                },
+                Violation::Unsupported {
+                    option: "ui-slint".to_string(),
+                    chip: Chip::Esp32c2
+                },
+            ]
+        );
+        assert_eq!(violations[1].to_string(), "Option 'wifi' requires 'alloc'");
+        assert_eq!(
+            violations[6].to_string(),
+            "Option 'embassy' is not available with 'ui-slint'"
+        );
+    }
+
+    #[test]
+    fn test_similar_options() {
+        assert_eq!(similar_options("wifl"), ["wifi"]);
+        assert_eq!(similar_options("embasy"), ["embassy"]);
+        assert!(similar_options("x").is_empty());
+        assert!(similar_options("completely-different").is_empty());
+
+        let violation = Violation::Unknown {
+            option: "wifl".to_string(),
+            suggestions: similar_options("wifl"),
+        };
+        assert_eq!(
+            violation.to_string(),
+            "Unknown option 'wifl', did you mean 'wifi'?"
+        );
+
+        let available = available_options(Chip::Esp32c2);
+        assert!(available.iter().any(|option| option == "wifi"));
+        assert!(!available.iter().any(|option| option == "heap-psram"));
+    }
+
+    #[test]
+    fn test_option_graph() {
+        let graph = graph();
+
+        // Every constraint refers to a known option
+        for node in &graph {
+            for other in node.enables.iter().chain(node.disables) {
+                assert!(graph.iter().any(|n| n.name == *other), "{other}");
+            }
+        }
+
+        let node = |name: &str| graph.iter().find(|n| n.name == name).unwrap();
+        assert_eq!(node("alloc").category, None);
+        assert_eq!(node("heap-size").category, Some("heap"));
+        assert_eq!(node("ui-slint").disables, ["embassy"]);
+        assert_eq!(node("wifi").capabilities, ["wifi"]);
+    }
+}
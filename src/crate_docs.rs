@@ -0,0 +1,106 @@
+//! The documentation matching the `esp-hal` and `esp-wifi` versions a
+//! generated project depends on.
+//!
+//! Their APIs change between minor versions, so the documentation of another
+//! version, e.g. the latest one found by a search engine, often does not match
+//! the generated code. After generating a project, the versions in its
+//! `Cargo.toml` are printed along with the documentation and migration guide
+//! of exactly these versions.
+
+use esp_metadata::Chip;
+
+use crate::cargo::CargoToml;
+
+/// The crates whose versions are printed, and the name of their library.
+const CRATES: &[(&str, &str)] = &[("esp-hal", "esp_hal"), ("esp-wifi", "esp_wifi")];
+
+/// A crate the project depends on and where its documentation is.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CrateDocs {
+    pub name: &'static str,
+    pub version: String,
+    /// The documentation of the version, built for the chip.
+    pub docs: String,
+    /// The guide for updating from the previous minor version, if there is
+    /// one.
+    pub migration_guide: Option<String>,
+}
+
+/// The documentation of the crates the manifest depends on, skipping those
+/// it does not depend on, e.g. with `esp-idf-std`.
+pub fn collect(manifest: &CargoToml, chip: Chip) -> Vec<CrateDocs> {
+    let dependencies = manifest.dependencies();
+
+    CRATES
+        .iter()
+        .filter_map(|(name, library)| {
+            let (_, requirement) = dependencies.iter().find(|(dep, _)| dep == name)?;
+            // The templates pin exact versions, but may use `=` or `^`
+            let version = requirement.trim_start_matches(['=', '^', '~', ' ']);
+
+            Some(CrateDocs {
+                name,
+                version: version.to_string(),
+                docs: format!(
+                    "https://docs.espressif.com/projects/rust/{name}/{version}/{chip}/{library}/index.html"
+                ),
+                migration_guide: previous_minor(version).map(|previous| {
+                    format!(
+                        "https://github.com/esp-rs/esp-hal/blob/{name}-v{version}/{name}/MIGRATING-{previous}.md"
+                    )
+                }),
+            })
+        })
+        .collect()
+}
+
+/// The minor version before a `0.x` version, whose migration guide describes
+/// the update to it.
+fn previous_minor(version: &str) -> Option<String> {
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse::<u32>().ok()?;
+    let minor = parts.next()?.parse::<u32>().ok()?;
+
+    (major == 0 && minor > 0).then(|| format!("0.{}", minor - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crate_docs() {
+        let manifest = CargoToml::parse(
+            r#"
+[dependencies]
+esp-hal = { version = "0.23.1", features = ["esp32c3"] }
+esp-wifi = "=0.12.0"
+esp-println = "0.13.0"
+"#,
+        )
+        .unwrap();
+
+        let docs = collect(&manifest, Chip::Esp32c3);
+        assert_eq!(
+            docs,
+            [
+                CrateDocs {
+                    name: "esp-hal",
+                    version: "0.23.1".to_string(),
+                    docs: "https://docs.espressif.com/projects/rust/esp-hal/0.23.1/esp32c3/esp_hal/index.html".to_string(),
+                    migration_guide: Some("https://github.com/esp-rs/esp-hal/blob/esp-hal-v0.23.1/esp-hal/MIGRATING-0.22.md".to_string()),
+                },
+                CrateDocs {
+                    name: "esp-wifi",
+                    version: "0.12.0".to_string(),
+                    docs: "https://docs.espressif.com/projects/rust/esp-wifi/0.12.0/esp32c3/esp_wifi/index.html".to_string(),
+                    migration_guide: Some("https://github.com/esp-rs/esp-hal/blob/esp-wifi-v0.12.0/esp-wifi/MIGRATING-0.11.md".to_string()),
+                },
+            ]
+        );
+
+        // `std` projects do not use esp-hal
+        let manifest = CargoToml::parse("[dependencies]\nesp-idf-svc = \"0.51\"\n").unwrap();
+        assert!(collect(&manifest, Chip::Esp32c3).is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+//! Rendering the templates of an esp-generate checkout, for template
+//! contributors.
+//!
+//! Rather than rebuilding esp-generate to embed the edited templates, `dev`
+//! reads them from disk and renders them for a fixed chip and options into a
+//! project. With `--watch`, it renders them again whenever a template
+//! changes, so the project can be kept open in an editor or rebuilt with
+//! `cargo build` while working on the templates.
+
+use std::{
+    error::Error,
+    fs, panic,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use esp_metadata::Chip;
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::{
+    cargo::CargoToml, integrity, process_options, render_template, template_variables,
+    templates::Templates, timings::Timings, write_project,
+};
+
+/// How long to wait for further changes after one, as editors often save a
+/// file in several steps.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// What to render, and where.
+pub struct Dev {
+    pub templates_dir: PathBuf,
+    pub chip: Chip,
+    pub options: Vec<String>,
+    pub name: String,
+    pub output_path: PathBuf,
+}
+
+/// Render the templates once, then again on every change if `watch` is set.
+pub fn run(dev: Dev, watch: bool) -> Result<(), Box<dyn Error>> {
+    if let Err(violations) = process_options(dev.chip, &dev.options) {
+        let violations = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        return Err(violations.join("\n").into());
+    }
+
+    let project_dir = dev.output_path.join(&dev.name);
+    if project_dir.exists() && !is_generated(&project_dir) {
+        return Err(format!(
+            "{} exists and is not a generated project, refusing to render into it",
+            project_dir.display()
+        )
+        .into());
+    }
+
+    let mut rendered = Vec::new();
+    if !watch {
+        return render(&dev, &project_dir, &mut rendered);
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(&dev.templates_dir, RecursiveMode::Recursive)?;
+
+    if let Err(error) = render(&dev, &project_dir, &mut rendered) {
+        log::error!("{error}");
+    }
+    log::info!(
+        "Watching {} for changes, press Ctrl-C to stop",
+        dev.templates_dir.display()
+    );
+
+    for event in &receiver {
+        let changed = match event {
+            Ok(event) => matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ),
+            Err(error) => {
+                log::warn!("Failed to watch the templates: {error}");
+                false
+            }
+        };
+        if !changed {
+            continue;
+        }
+
+        thread::sleep(DEBOUNCE);
+        while receiver.try_recv().is_ok() {}
+
+        if let Err(error) = render(&dev, &project_dir, &mut rendered) {
+            log::error!("{error}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `dir` holds a project generated by esp-generate, which can be
+/// rendered over.
+fn is_generated(dir: &Path) -> bool {
+    CargoToml::load(dir).is_ok_and(|manifest| manifest.generator_metadata().is_some())
+}
+
+/// Render the templates into `project_dir`. Files of the previous render
+/// (`rendered`) which are no longer rendered are removed, any other file is
+/// kept, e.g. `target/`.
+fn render(dev: &Dev, project_dir: &Path, rendered: &mut Vec<String>) -> Result<(), Box<dyn Error>> {
+    let start = Instant::now();
+    let templates = Templates::from_dir(&dev.templates_dir).map_err(|error| {
+        format!(
+            "Failed to read the templates from {}: {error}",
+            dev.templates_dir.display()
+        )
+    })?;
+
+    for problem in integrity::problems(&templates) {
+        log::warn!("{problem}");
+    }
+
+    let variables = template_variables(&dev.name, dev.chip);
+    // Invalid `IF` conditions panic, which should not end watching. The panic
+    // message is printed by the default hook.
+    let files =
+        panic::catch_unwind(|| render_template(&templates, dev.chip, &dev.options, &variables))
+            .map_err(|_| "Failed to render the templates")?;
+
+    let paths = files
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    for stale in rendered.iter().filter(|path| !paths.contains(path)) {
+        let _ = fs::remove_file(project_dir.join(stale));
+    }
+
+    fs::create_dir_all(project_dir)?;
+    write_project(project_dir, files, &[], &mut Timings::default())?;
+    log::info!(
+        "Rendered {} files into {} in {} ms",
+        paths.len(),
+        project_dir.display(),
+        start.elapsed().as_millis()
+    );
+    *rendered = paths;
+
+    Ok(())
+}
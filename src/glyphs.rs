@@ -0,0 +1,174 @@
+//! The symbols of the check results and the TUI, with ASCII fallbacks.
+//!
+//! Emoji and box-drawing characters are garbled by terminals which cannot
+//! display them, e.g. the legacy Windows console or a CI log read without a
+//! UTF-8 locale. Whether to use the fallbacks is decided once at startup from
+//! the terminal, or forced with `--ascii`.
+
+use std::{
+    env, fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Use the ASCII fallbacks if `ascii` is set or the terminal does not seem
+/// to support Unicode.
+pub fn init(ascii: bool) {
+    let ascii = ascii || !supports_unicode(|name| env::var(name).ok(), cfg!(windows));
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Whether the terminal described by the environment variables (`var`)
+/// displays Unicode symbols.
+pub fn supports_unicode(var: impl Fn(&str) -> Option<String>, windows: bool) -> bool {
+    if windows {
+        // The legacy console does not, Windows Terminal and the terminals of
+        // editors and of Git Bash do
+        return var("WT_SESSION").is_some()
+            || var("TERM_PROGRAM").is_some()
+            || var("TERM").is_some_and(|term| term.starts_with("xterm"));
+    }
+
+    if var("TERM").is_some_and(|term| term == "linux" || term == "dumb") {
+        return false;
+    }
+    // The first one set decides the character set, none means ASCII
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| var(name).filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+/// A symbol, displayed as its Unicode or ASCII version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Glyph {
+    /// A tool which is installed and recent enough.
+    Ok,
+    /// A tool which is too old or does not support the chip.
+    Failed,
+    /// A tool which is missing or did not respond.
+    Error,
+    Warning,
+    NewVersion,
+    /// A selected option in the TUI.
+    Selected,
+    /// An option which is not selected, as wide as [`Glyph::Selected`].
+    Unselected,
+    /// A category in the TUI, which opens a submenu.
+    Category,
+    /// The separator of the categories in the TUI's title.
+    Breadcrumb,
+    /// A feature of the chip in the chip comparison table.
+    Supported,
+    /// The cursor of text input.
+    Cursor,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Glyph {
+    pub fn unicode(self) -> &'static str {
+        match self {
+            Glyph::Ok => "🆗",
+            Glyph::Failed => "🛑",
+            Glyph::Error => "❌",
+            Glyph::Warning => "⚠️",
+            Glyph::NewVersion => "🚀",
+            Glyph::Selected => "✅",
+            Glyph::Unselected => "  ",
+            Glyph::Category => "▶️",
+            Glyph::Breadcrumb => "▸",
+            Glyph::Supported => "✔",
+            Glyph::Cursor => "▏",
+            Glyph::Up => "↑",
+            Glyph::Down => "↓",
+            Glyph::Left => "←",
+            Glyph::Right => "→",
+        }
+    }
+
+    pub fn ascii(self) -> &'static str {
+        match self {
+            Glyph::Ok => "[ok]",
+            Glyph::Failed => "[failed]",
+            Glyph::Error => "[error]",
+            Glyph::Warning => "[warning]",
+            Glyph::NewVersion => "[new]",
+            Glyph::Selected => "[x]",
+            Glyph::Unselected => "   ",
+            Glyph::Category => "[>]",
+            Glyph::Breadcrumb => ">",
+            Glyph::Supported => "x",
+            Glyph::Cursor => "|",
+            Glyph::Up => "Up",
+            Glyph::Down => "Down",
+            Glyph::Left => "Left",
+            Glyph::Right => "Right",
+        }
+    }
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if ASCII.load(Ordering::Relaxed) {
+            f.pad(self.ascii())
+        } else {
+            f.pad(self.unicode())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyphs() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        assert!(supports_unicode(env(&[("LANG", "en_US.UTF-8")]), false));
+        assert!(supports_unicode(
+            env(&[("LC_ALL", "C.utf8"), ("LANG", "C")]),
+            false
+        ));
+        assert!(!supports_unicode(
+            env(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]),
+            false
+        ));
+        assert!(!supports_unicode(
+            env(&[("LANG", "C.UTF-8"), ("TERM", "linux")]),
+            false
+        ));
+        assert!(!supports_unicode(env(&[]), false));
+
+        assert!(supports_unicode(env(&[("WT_SESSION", "1")]), true));
+        assert!(!supports_unicode(env(&[("LANG", "en_US.UTF-8")]), true));
+
+        for glyph in [
+            Glyph::Ok,
+            Glyph::Failed,
+            Glyph::Error,
+            Glyph::Up,
+            Glyph::Cursor,
+        ] {
+            assert!(glyph.ascii().is_ascii());
+            assert!(!glyph.unicode().is_ascii());
+        }
+        assert_eq!(
+            Glyph::Selected.ascii().len(),
+            Glyph::Unselected.ascii().len()
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Organizes the `use` statements of rendered Rust files.
+//!
+//! Options contribute imports to the same file from separate `IF` blocks, so
+//! the rendered file may import a path twice (a compile error) or in an
+//! arbitrary order. This collects the imports at the top of the file,
+//! deduplicates them and groups them as `std`/`core`/`alloc`, other crates and
+//! the current crate, merging imports from the same module into one statement,
+//! as `cargo fmt` does with the configuration used for generated projects.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The group of an import, in the order the groups are emitted.
+fn group(path: &str) -> u8 {
+    match root(path) {
+        "std" | "core" | "alloc" => 0,
+        "crate" | "self" | "super" => 2,
+        _ => 1,
+    }
+}
+
+/// The first segment of an import path, e.g. the crate it imports from.
+fn root(path: &str) -> &str {
+    path.split_once("::").map_or(path, |(root, _)| root)
+}
+
+/// Split the items of a use tree's braces, ignoring commas in nested braces.
+fn split_items(items: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in items.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                res.push(&items[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    res.push(&items[start..]);
+
+    res.into_iter()
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Flatten a use tree into the full path of each imported item.
+fn flatten(prefix: &str, tree: &str, paths: &mut Vec<String>) {
+    let join = |path: &str| {
+        if prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{prefix}::{path}")
+        }
+    };
+
+    let tree = tree.trim();
+    match (tree.find('{'), tree.rfind('}')) {
+        (Some(open), Some(close)) => {
+            let base = join(tree[..open].trim().trim_end_matches("::"));
+            for item in split_items(&tree[open + 1..close]) {
+                if item == "self" {
+                    paths.push(base.clone());
+                } else {
+                    flatten(&base, item, paths);
+                }
+            }
+        }
+        _ if !tree.is_empty() => paths.push(join(tree)),
+        _ => {}
+    }
+}
+
+/// Render the imports, one statement per module.
+fn render(paths: Vec<String>) -> Vec<String> {
+    let mut modules = BTreeMap::<&str, BTreeSet<&str>>::new();
+    let mut statements = BTreeSet::new();
+    for path in &paths {
+        match path.rsplit_once("::") {
+            Some((module, item)) => {
+                modules.entry(module).or_default().insert(item);
+            }
+            None => {
+                statements.insert((group(path), format!("use {path};")));
+            }
+        }
+    }
+    for (module, items) in modules {
+        let items = items.into_iter().collect::<Vec<_>>();
+        statements.insert((
+            group(module),
+            match items.as_slice() {
+                [item] => format!("use {module}::{item};"),
+                _ => format!("use {module}::{{{}}};", items.join(", ")),
+            },
+        ));
+    }
+
+    let mut lines = Vec::new();
+    let mut last_group = None;
+    for (group, statement) in statements {
+        if last_group.is_some_and(|last| last != group) {
+            lines.push(String::new());
+        }
+        last_group = Some(group);
+        lines.push(statement);
+    }
+
+    lines
+}
+
+/// Remove leading and trailing blank lines, and collapse runs of blank lines.
+fn tidy<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    let mut res: Vec<&str> = Vec::new();
+    for line in lines {
+        if !line.trim().is_empty() || res.last().is_some_and(|last| !last.trim().is_empty()) {
+            res.push(line);
+        }
+    }
+    while res.last().is_some_and(|last| last.trim().is_empty()) {
+        res.pop();
+    }
+
+    res
+}
+
+/// Deduplicate and group the imports at the top of a Rust file, i.e. those
+/// preceding its first item. Imports inside functions or modules are left
+/// untouched.
+pub fn organize(contents: &str) -> String {
+    let lines = contents.lines().collect::<Vec<_>>();
+
+    // Lines before the first import, the imports and other lines up to the
+    // first item (e.g. `extern crate`), in their original order:
+    let mut header = Vec::new();
+    let mut paths = Vec::new();
+    let mut others = Vec::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let trimmed = lines[i].trim();
+        if let Some(tree) = trimmed.strip_prefix("use ") {
+            let mut statement = tree.to_string();
+            while !statement.ends_with(';') && i + 1 < lines.len() {
+                i += 1;
+                statement.push(' ');
+                statement.push_str(lines[i].trim());
+            }
+            flatten("", statement.trim_end_matches(';'), &mut paths);
+        } else if trimmed.is_empty()
+            // Doc comments belong to the first item
+            || (trimmed.starts_with("//") && !trimmed.starts_with("///"))
+            || trimmed.starts_with("#![")
+            || trimmed.starts_with("extern crate ")
+        {
+            if paths.is_empty() {
+                header.push(lines[i]);
+            } else {
+                others.push(lines[i]);
+            }
+        } else {
+            break;
+        }
+        i += 1;
+    }
+
+    if paths.is_empty() {
+        return contents.to_string();
+    }
+
+    let mut res = Vec::new();
+    for section in [
+        tidy(&header).into_iter().map(str::to_string).collect(),
+        render(paths),
+        tidy(&others).into_iter().map(str::to_string).collect(),
+    ] {
+        if !section.is_empty() {
+            res.extend(section);
+            res.push(String::new());
+        }
+    }
+    if i == lines.len() {
+        res.pop();
+    }
+    res.extend(lines[i..].iter().map(|line| line.to_string()));
+
+    let mut res = res.join("\n");
+    if contents.ends_with('\n') {
+        res.push('\n');
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_organize_imports() {
+        let contents = r#"#![no_std]
+
+use esp_backtrace as _;
+use esp_hal::{clock::CpuClock, delay::Delay};
+use crate::pins;
+use esp_hal::delay::Delay;
+use core::fmt::Write;
+ use defmt::{
+    info,
+    warn,
+};
+use esp_hal::{gpio::{Level, Output}, main};
+
+extern crate alloc;
+
+fn main() {
+    use esp_hal::efuse::Efuse;
+}
+"#;
+
+        assert_eq!(
+            organize(contents),
+            r#"#![no_std]
+
+use core::fmt::Write;
+
+use defmt::{info, warn};
+use esp_backtrace as _;
+use esp_hal::clock::CpuClock;
+use esp_hal::delay::Delay;
+use esp_hal::gpio::{Level, Output};
+use esp_hal::main;
+
+use crate::pins;
+
+extern crate alloc;
+
+fn main() {
+    use esp_hal::efuse::Efuse;
+}
+"#
+        );
+
+        let contents = "fn main() {}\n";
+        assert_eq!(organize(contents), contents);
+
+        // Doc comments stay attached to the first item
+        let contents = "use core::fmt::Write;\n\n/// The answer.\nconst ANSWER: u8 = 42;\n";
+        assert_eq!(organize(contents), contents);
+    }
+}
@@ -0,0 +1,194 @@
+//! Rendering a single file of the templates, for template contributors.
+//!
+//! Rather than generating a whole project to look at one file, `inspect`
+//! prints the file as it would be rendered for a chip and options. With
+//! `--annotate`, every line is followed by the template line it comes from,
+//! which may be in a snippet, and the conditions of the `IF` branches it is
+//! in, to find out why a line is, or is not, in the output.
+
+use std::error::Error;
+
+use esp_metadata::Chip;
+
+use crate::{
+    process_lines, process_options, render_inputs, render_template, snippets::Snippets,
+    templates::Templates, ESP_IDF_STD_TEMPLATES,
+};
+
+/// The widest column the annotations are aligned to.
+const MAX_WIDTH: usize = 80;
+
+/// What to render.
+pub struct Inspect {
+    pub chip: Chip,
+    pub options: Vec<String>,
+    /// The path of the file in the generated project.
+    pub file: String,
+    pub variables: Vec<(String, String)>,
+    pub annotate: bool,
+}
+
+/// Print the file.
+pub fn run(templates: &Templates, inspect: &Inspect) -> Result<(), Box<dyn Error>> {
+    if let Err(violations) = process_options(inspect.chip, &inspect.options) {
+        let violations = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        return Err(violations.join("\n").into());
+    }
+
+    print!("{}", render(templates, inspect)?);
+
+    Ok(())
+}
+
+/// The rendered file, annotated if requested.
+pub fn render(templates: &Templates, inspect: &Inspect) -> Result<String, String> {
+    let files = render_template(
+        templates,
+        inspect.chip,
+        &inspect.options,
+        &inspect.variables,
+    );
+    let Some((_, contents)) = files.iter().find(|(path, _)| *path == inspect.file) else {
+        let paths = files
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        return Err(format!(
+            "{} is not rendered for these options, the rendered files are: {}",
+            inspect.file,
+            paths.join(", ")
+        ));
+    };
+
+    if inspect.annotate {
+        Ok(annotate(templates, inspect))
+    } else {
+        Ok(contents.clone())
+    }
+}
+
+/// The file with the origin of every line. The `use` lines of Rust files are
+/// shown as they are in the templates, before they are organized.
+fn annotate(templates: &Templates, inspect: &Inspect) -> String {
+    let (selected, variables) = render_inputs(
+        templates,
+        inspect.chip,
+        &inspect.options,
+        &inspect.variables,
+    );
+    let esp_idf_std = selected.iter().any(|option| option == "esp-idf-std");
+
+    // The files of the `esp-idf-std` flavor replace those at the same path
+    let override_path = format!("{ESP_IDF_STD_TEMPLATES}{}", inspect.file);
+    let find = |path: &str| {
+        templates
+            .files()
+            .iter()
+            .find(|(file_path, _)| file_path == path)
+    };
+    let (path, contents) = esp_idf_std
+        .then(|| find(&override_path))
+        .flatten()
+        .or_else(|| find(&inspect.file))
+        .expect("the file was rendered from a template");
+
+    let snippets = Snippets::collect(templates, &selected);
+    let expanded = snippets.expand_lines(path, contents);
+    let text = expanded
+        .iter()
+        .map(|(_, line)| format!("{line}\n"))
+        .collect::<String>();
+    let lines = process_lines(&text, &selected, &variables).unwrap_or_default();
+
+    let width = lines
+        .iter()
+        .map(|line| line.text.chars().count())
+        .filter(|&width| width <= MAX_WIDTH)
+        .max()
+        .unwrap_or(0);
+
+    let mut res = String::new();
+    for line in lines {
+        let (origin, _) = &expanded[line.index];
+        let mut annotation = format!("// {origin}");
+        if !line.branches.is_empty() {
+            let nested = line.branches.len() > 1;
+            let branches = line
+                .branches
+                .iter()
+                .map(|cond| {
+                    if nested && cond.contains(' ') && !cond.starts_with("!(") {
+                        format!("({cond})")
+                    } else {
+                        cond.clone()
+                    }
+                })
+                .collect::<Vec<_>>();
+            annotation.push_str(" if ");
+            annotation.push_str(&branches.join(" && "));
+        }
+
+        res.push_str(&format!("{:<width$}  {annotation}\n", line.text));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{negate, template_variables};
+
+    #[test]
+    fn test_inspect() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        let templates = Templates::embedded();
+        let mut inspect = Inspect {
+            chip: Chip::Esp32c3,
+            options: options(&["app-config", "embassy", "unstable-hal"]),
+            file: "src/bin/async_main.rs".to_string(),
+            variables: template_variables("project", Chip::Esp32c3),
+            annotate: false,
+        };
+
+        let files = render_template(
+            &templates,
+            inspect.chip,
+            &inspect.options,
+            &inspect.variables,
+        );
+        let rendered = render(&templates, &inspect).unwrap();
+        assert!(files.contains(&(inspect.file.clone(), rendered.clone())));
+
+        inspect.annotate = true;
+        let annotated = render(&templates, &inspect).unwrap();
+        let line = |text: &str| {
+            annotated
+                .lines()
+                .find(|line| line.contains(text))
+                .unwrap()
+                .to_string()
+        };
+        assert!(line("#![no_std]").ends_with("// src/bin/async_main.rs:2"));
+        // Lines of snippets, and the conditions of nested and `ELSE` branches
+        assert!(line("cfg::THRESHOLD").contains("// snippets/app-config.rs:"));
+        assert!(line("init_logger_from_env")
+            .ends_with(r#"if !option("remote-logging") && !option("probe-rs")"#));
+        assert!(line("cfg::INTERVAL_MS.into()")
+            .ends_with(r#"if !option("channels") && option("app-config")"#));
+
+        inspect.file = "src/missing.rs".to_string();
+        let error = render(&templates, &inspect).unwrap_err();
+        assert!(error.contains("src/bin/async_main.rs"));
+
+        assert_eq!(negate(r#"option("a")"#), r#"!option("a")"#);
+        assert_eq!(negate(r#"!option("a")"#), r#"option("a")"#);
+        assert_eq!(
+            negate(r#"option("a") || option("b")"#),
+            r#"!(option("a") || option("b"))"#
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Checking that the templates are consistent with the options.
+//!
+//! A template testing an option which does not exist, e.g. after renaming
+//! it, or an unbalanced `IF` silently renders a wrong project rather than
+//! failing. The embedded templates are checked at startup, so that a mistake
+//! made when embedding them is reported as such instead.
+
+use clap::ValueEnum;
+use esp_metadata::Chip;
+
+use crate::{
+    config::{capability::Capability, OPTIONS},
+    snippets::SNIPPETS_DIR,
+    templates::Templates,
+};
+
+/// The options `render_template` selects besides those of the user.
+const DERIVED_OPTIONS: &[&str] = &[
+    "chip-revision-check",
+    "template-revision",
+    "variable-overrides",
+];
+
+/// Every name templates can test with `option("...")`.
+fn known_options() -> Vec<String> {
+    let mut known = OPTIONS
+        .iter()
+        .flat_map(|option| option.options())
+        .collect::<Vec<_>>();
+    known.extend(DERIVED_OPTIONS.iter().map(|option| option.to_string()));
+    known.extend(Chip::value_variants().iter().map(ToString::to_string));
+    known.extend(
+        Capability::ALL
+            .iter()
+            .map(|capability| capability.template_option()),
+    );
+
+    known
+}
+
+/// The directive of a `#<name> ...` or `//<name> ...` line, and its argument.
+fn directive(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim();
+    let rest = trimmed
+        .strip_prefix("//")
+        .or_else(|| trimmed.strip_prefix('#'))?;
+    let (name, argument) = rest.split_once(' ').unwrap_or((rest, ""));
+
+    matches!(
+        name,
+        "IF" | "ELSE" | "ENDIF" | "INCLUDEFILE" | "SNIPPET" | "ANCHOR"
+    )
+    .then(|| (name, argument.trim()))
+}
+
+/// The options tested with `option("...")` in a condition.
+fn tested_options(condition: &str) -> impl Iterator<Item = &str> {
+    condition
+        .split("option(\"")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('"').map(|(option, _)| option))
+}
+
+/// The problems of the templates, as `<file>:<line>: <problem>`.
+pub fn problems(templates: &Templates) -> Vec<String> {
+    let known = known_options();
+    let is_known = |option: &str| known.iter().any(|known| known == option);
+    let engine = rhai::Engine::new();
+
+    let anchors = templates
+        .files()
+        .iter()
+        .flat_map(|(_, contents)| contents.lines())
+        .filter_map(|line| match directive(line) {
+            Some(("ANCHOR", anchor)) => Some(anchor),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut problems = Vec::new();
+    for (path, contents) in templates.files() {
+        if let Some(file_name) = path.strip_prefix(SNIPPETS_DIR) {
+            let option = file_name.split_once('.').map_or(file_name, |(o, _)| o);
+            if !is_known(option) {
+                problems.push(format!("{path}: snippets of unknown option '{option}'"));
+            }
+        }
+
+        let mut depth = 0usize;
+        for (number, line) in contents.lines().enumerate() {
+            let mut problem = |problem: String| {
+                problems.push(format!("{path}:{}: {problem}", number + 1));
+            };
+
+            match directive(line) {
+                Some(("IF", condition)) => {
+                    depth += 1;
+                    if let Err(error) = engine.compile_expression(condition) {
+                        problem(format!("invalid condition `{condition}`: {error}"));
+                    }
+                    for option in tested_options(condition).filter(|o| !is_known(o)) {
+                        problem(format!("unknown option '{option}'"));
+                    }
+                }
+                Some(("ELSE", _)) if depth == 0 => problem("ELSE without IF".to_string()),
+                Some(("ENDIF", _)) if depth == 0 => problem("ENDIF without IF".to_string()),
+                Some(("ENDIF", _)) => depth -= 1,
+                Some(("INCLUDEFILE", condition)) if number == 0 => {
+                    let options = if condition.contains("option(") {
+                        tested_options(condition).collect()
+                    } else {
+                        vec![condition.strip_prefix('!').unwrap_or(condition)]
+                    };
+                    for option in options.into_iter().filter(|o| !is_known(o)) {
+                        problem(format!("unknown option '{option}'"));
+                    }
+                }
+                Some(("INCLUDEFILE", _)) => problem("INCLUDEFILE after the first line".to_string()),
+                Some(("SNIPPET", anchor)) if !anchors.contains(&anchor) => {
+                    problem(format!("no template has the anchor '{anchor}'"));
+                }
+                _ => {}
+            }
+        }
+        if depth > 0 {
+            problems.push(format!("{path}: {depth} IF without ENDIF"));
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_integrity() {
+        assert_eq!(problems(&Templates::embedded()), Vec::<String>::new());
+
+        let file = |path: &str, contents: &str| (path.to_string(), contents.to_string());
+        let templates = Templates::from_files(vec![
+            file("README.md", "#INCLUDEFILE wifl
+# Title
+"),
+            file(
+                "src/main.rs",
+                "//IF option(\"alloc\") && option(\"embasy\")\n//ELSE\n//ENDIF\n//ENDIF\n//IF option(\"alloc\"\n",
+            ),
+            file("snippets/heartbeat.rs", "//SNIPPET modules\n//SNIPPET nowhere\n"),
+            file("snippets/blinky.rs", ""),
+            file("src/lib.rs", "//ANCHOR modules\n"),
+        ]);
+        let problems = problems(&templates);
+        assert_eq!(problems.len(), 7, "{problems:#?}");
+        assert!(problems.contains(&"README.md:1: unknown option 'wifl'".to_string()));
+        assert!(problems.contains(&"src/main.rs:1: unknown option 'embasy'".to_string()));
+        assert!(problems.contains(&"src/main.rs:4: ENDIF without IF".to_string()));
+        assert!(problems
+            .iter()
+            .any(|p| p.starts_with("src/main.rs:5: invalid condition")));
+        assert!(problems.contains(&"src/main.rs: 1 IF without ENDIF".to_string()));
+        assert!(problems
+            .contains(&"snippets/blinky.rs: snippets of unknown option 'blinky'".to_string()));
+        assert!(problems.contains(
+            &"snippets/heartbeat.rs:2: no template has the anchor 'nowhere'".to_string()
+        ));
+    }
+}
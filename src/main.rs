@@ -1,215 +1,66 @@
 use std::{
+    collections::BTreeMap,
     env,
     error::Error,
-    fs,
+    fs, io,
     path::{Path, PathBuf},
-    process::{self, Command},
-    time::Duration,
+    process::{self, Command, Output},
+    time::Instant,
 };
 
-use clap::Parser;
+use chip_name::ChipParser;
+use clap::{Parser, Subcommand};
+use config::{
+    board, capability, solver, solver::Violation, split_value, GeneratorOptionItem, OPTIONS,
+};
 use env_logger::{Builder, Env};
-use esp_metadata::Chip;
+use esp_metadata::{Arch, Chip, Cores};
+use serde::Deserialize;
+use snippets::Snippets;
 use taplo::formatter::Options;
-use update_informer::{registry, Check};
-
+use templates::{TemplateChannel, Templates};
+use timings::Timings;
+
+mod batch;
+mod cargo;
+mod cargo_config;
+mod check;
+mod chip_name;
+mod config;
+mod crate_docs;
+#[cfg(feature = "dev")]
+mod dev;
+mod glyphs;
+mod imports;
+mod inspect;
+mod integrity;
+mod migrations;
+mod outdated;
+mod provenance;
+#[cfg(feature = "serve")]
+mod serve;
+mod snippets;
 mod template_files;
+mod templates;
+mod timings;
 mod tui;
-
-#[derive(Clone, Copy)]
-pub struct GeneratorOption {
-    name: &'static str,
-    display_name: &'static str,
-    enables: &'static [&'static str],
-    disables: &'static [&'static str],
-    chips: &'static [Chip],
-}
-
-impl GeneratorOption {
-    fn options(&self) -> Vec<String> {
-        vec![self.name.to_string()]
-    }
-}
-
-#[derive(Clone, Copy)]
-pub struct GeneratorOptionCategory {
-    name: &'static str,
-    display_name: &'static str,
-    options: &'static [GeneratorOptionItem],
-}
-
-impl GeneratorOptionCategory {
-    fn options(&self) -> Vec<String> {
-        let mut res = Vec::new();
-        for option in self.options {
-            res.extend(option.options());
-        }
-        res
-    }
-}
-
-#[derive(Clone, Copy)]
-pub enum GeneratorOptionItem {
-    Category(GeneratorOptionCategory),
-    Option(GeneratorOption),
-}
-
-impl GeneratorOptionItem {
-    fn title(&self) -> String {
-        match self {
-            GeneratorOptionItem::Category(category) => category.display_name.to_string(),
-            GeneratorOptionItem::Option(option) => option.display_name.to_string(),
-        }
-    }
-
-    fn name(&self) -> String {
-        match self {
-            GeneratorOptionItem::Category(category) => category.name.to_string(),
-            GeneratorOptionItem::Option(option) => option.name.to_string(),
-        }
-    }
-
-    fn options(&self) -> Vec<String> {
-        match self {
-            GeneratorOptionItem::Category(category) => category.options(),
-            GeneratorOptionItem::Option(option) => option.options(),
-        }
-    }
-
-    fn is_category(&self) -> bool {
-        matches!(self, GeneratorOptionItem::Category(_))
-    }
-
-    fn chips(&self) -> &'static [Chip] {
-        match self {
-            GeneratorOptionItem::Category(_) => &[],
-            GeneratorOptionItem::Option(option) => option.chips,
-        }
-    }
-
-    fn enables(&self) -> &[&str] {
-        match self {
-            GeneratorOptionItem::Category(_) => &[],
-            GeneratorOptionItem::Option(option) => option.enables,
-        }
-    }
-}
-
-static OPTIONS: &[GeneratorOptionItem] = &[
-    GeneratorOptionItem::Option(GeneratorOption {
-        name: "alloc",
-        display_name: "Enables allocations via the `esp-alloc` crate.",
-        enables: &[],
-        disables: &[],
-        chips: &[],
-    }),
-    GeneratorOptionItem::Option(GeneratorOption {
-        name: "wifi",
-        display_name: "Enables Wi-Fi via the `esp-wifi` crate. Requires `alloc`.",
-        enables: &["alloc"],
-        disables: &[],
-        chips: &[
-            Chip::Esp32,
-            Chip::Esp32c2,
-            Chip::Esp32c3,
-            Chip::Esp32c6,
-            Chip::Esp32s2,
-            Chip::Esp32s3,
-        ],
-    }),
-    GeneratorOptionItem::Option(GeneratorOption {
-        name: "ble",
-        display_name: "Enables BLE via the `esp-wifi` crate. Requires `alloc`.",
-        enables: &["alloc"],
-        disables: &[],
-        chips: &[
-            Chip::Esp32,
-            Chip::Esp32c2,
-            Chip::Esp32c3,
-            Chip::Esp32c6,
-            Chip::Esp32h2,
-            Chip::Esp32s3,
-        ],
-    }),
-    GeneratorOptionItem::Option(GeneratorOption {
-        name: "embassy",
-        display_name: "Adds `embassy` framework support.",
-        enables: &[],
-        disables: &[],
-        chips: &[],
-    }),
-    GeneratorOptionItem::Option(GeneratorOption {
-        name: "probe-rs",
-        display_name: "Enables `defmt` and flashes using `probe-rs` instead of `espflash`.",
-        enables: &[],
-        disables: &[],
-        chips: &[],
-    }),
-    GeneratorOptionItem::Category(GeneratorOptionCategory {
-        name: "optional",
-        display_name: "Options",
-        options: &[
-            GeneratorOptionItem::Option(GeneratorOption {
-                name: "wokwi",
-                display_name: "Adds support for Wokwi simulation using VS Code Wokwi extension.",
-                enables: &[],
-                disables: &[],
-                chips: &[
-                    Chip::Esp32,
-                    Chip::Esp32c3,
-                    Chip::Esp32c6,
-                    Chip::Esp32h2,
-                    Chip::Esp32s2,
-                    Chip::Esp32s3,
-                ],
-            }),
-            GeneratorOptionItem::Option(GeneratorOption {
-                name: "dev-container",
-                display_name: "Adds support for VS Code Dev Containers and GitHub Codespaces.",
-                enables: &[],
-                disables: &[],
-                chips: &[],
-            }),
-            GeneratorOptionItem::Option(GeneratorOption {
-                name: "ci",
-                display_name: "Adds GitHub Actions support with some basics checks.",
-                enables: &[],
-                disables: &[],
-                chips: &[],
-            }),
-        ],
-    }),
-    GeneratorOptionItem::Category(GeneratorOptionCategory {
-        name: "editor",
-        display_name: "Optional editor config files for rust-analyzer",
-        options: &[
-            GeneratorOptionItem::Option(GeneratorOption {
-                name: "helix",
-                display_name: "Rust-Analyzer settings for Helix Editor",
-                enables: &[],
-                disables: &[],
-                chips: &[],
-            }),
-            GeneratorOptionItem::Option(GeneratorOption {
-                name: "vscode",
-                display_name: "Rust-Analyzer settings for Visual Studio Code",
-                enables: &[],
-                disables: &[],
-                chips: &[],
-            }),
-        ],
-    }),
-];
+mod update;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Name of the project to generate
-    name: String,
+    #[arg(required_unless_present = "from_json")]
+    name: Option<String>,
 
-    /// Chip to target
-    #[arg(short, long)]
-    chip: Chip,
+    /// Chip to target, if not given it is selected in the TUI. Aliases like
+    /// `c3` and module names like `ESP32-C3-MINI-1` are accepted too
+    #[arg(short, long, value_parser = ChipParser)]
+    chip: Option<Chip>,
 
     /// Run in headless mode (i.e. do not use the TUI)
     #[arg(long)]
@@ -229,40 +80,431 @@ struct Args {
     #[arg(short = 'O', long)]
     output_path: Option<PathBuf>,
 
+    /// Apply the suggested fix when the selected options are not valid,
+    /// instead of exiting with an error
+    #[arg(long)]
+    auto_fix: bool,
+
+    /// Read the generation request from a JSON file, or from stdin if `-` is
+    /// given. Implies `--headless`
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["name", "chip", "option", "output_path", "headless"]
+    )]
+    from_json: Option<String>,
+
+    /// Where to take the templates from
+    #[arg(long, value_enum, default_value_t)]
+    template_channel: TemplateChannel,
+
+    /// Use the templates of this commit of the esp-generate repository instead
+    /// of the latest ones, requires `--template-channel git-main`
+    #[arg(long, value_name = "COMMIT")]
+    template_rev: Option<String>,
+
+    /// Probe the installed tools again instead of using the versions detected
+    /// during the last few minutes, and fetch the `git-main` templates again
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Print how long each phase of the generation took
+    #[arg(long)]
+    timings: bool,
+
+    /// Write a plain text transcript of the TUI session to this file: every
+    /// key pressed, how it changed the selection, and the final selection
+    #[arg(long, value_name = "FILE", conflicts_with = "headless")]
+    log_session: Option<PathBuf>,
+
+    /// Do not format the generated Rust code with `cargo fmt`
+    #[arg(long)]
+    no_fmt: bool,
+
+    /// Do not format the generated `Cargo.toml` with Taplo
+    #[arg(long)]
+    no_taplo: bool,
+
+    /// Do not initialize a git repository in the generated project
+    #[arg(long)]
+    no_git: bool,
+
+    /// Do not check the installed tools and the Cargo configuration after
+    /// generating the project
+    #[arg(long)]
+    no_check: bool,
+
     /// Do not check for updates
-    #[arg(short, long, global = true, action)]
+    #[arg(
+        short,
+        long,
+        global = true,
+        action,
+        env = "ESP_GENERATE_SKIP_UPDATE_CHECK",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
     skip_update_check: bool,
-}
 
-/// Check crates.io for a new version of the application
-fn check_for_update(name: &str, version: &str) {
-    // By setting the interval to 0 seconds we invalidate the cache with each
-    // invocation and ensure we're getting up-to-date results
-    let informer =
-        update_informer::new(registry::Crates, name, version).interval(Duration::from_secs(0));
+    /// Offer experimental options, whose templates may still change or break,
+    /// in a separate section of the TUI, and accept them in headless mode
+    #[arg(long, global = true)]
+    experimental: bool,
+
+    /// Print ASCII instead of emoji and other symbols, which is the default
+    /// when the terminal does not seem to support Unicode
+    #[arg(long, global = true)]
+    ascii: bool,
+}
 
-    if let Some(version) = informer.check_version().ok().flatten() {
-        log::warn!("🚀 A new version of {name} is available: {version}");
+impl Args {
+    /// The post-processing steps skipped with `--no-<step>`.
+    fn skipped(&self) -> Vec<Step> {
+        [
+            (self.no_fmt, Step::Fmt),
+            (self.no_taplo, Step::Taplo),
+            (self.no_git, Step::Git),
+            (self.no_check, Step::Check),
+        ]
+        .into_iter()
+        .filter_map(|(skipped, step)| skipped.then_some(step))
+        .collect()
     }
 }
 
+/// A step run after writing the rendered files, which automation doing it
+/// itself, or not needing it, can skip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Step {
+    /// Formatting the Rust code with `cargo fmt`
+    Fmt,
+    /// Formatting `Cargo.toml` with Taplo
+    Taplo,
+    /// Initializing a git repository
+    Git,
+    /// Checking the installed tools and the Cargo configuration
+    Check,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Verify that a generated project has not diverged from what this
+    /// version of esp-generate renders for the same inputs
+    Verify {
+        /// Path of the project to verify
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Print the changes to apply by hand to a generated project when updating
+    /// it to the templates of this version of esp-generate
+    Migrations {
+        /// Path of the generated project
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Version of esp-generate the project was generated with, needed for
+        /// projects which do not record it
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// List the dependencies of a generated project which are older than those
+    /// of the templates of this version of esp-generate
+    Outdated {
+        /// Path of the generated project
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// List every valid configuration of the given options for a chip, one per
+    /// line
+    #[command(hide = true)]
+    Configurations {
+        /// Chip to target
+        #[arg(short, long, value_parser = ChipParser)]
+        chip: Chip,
+
+        /// Options to combine
+        #[arg(short, long)]
+        option: Vec<String>,
+
+        /// Rather than combining the options, print them as they are if they
+        /// are a valid configuration together, and nothing otherwise
+        #[arg(long)]
+        check: bool,
+    },
+    /// Print every option, its category and the options it requires and
+    /// conflicts with, as JSON
+    #[command(hide = true)]
+    OptionGraph,
+    /// Print the options of the templates, and the capabilities of each chip
+    /// which options require, as JSON
+    Options,
+    /// Install the latest version of esp-generate
+    SelfUpdate,
+    /// Print a single file as it is rendered for a chip and options, for
+    /// debugging the templates
+    Inspect {
+        /// Chip to target
+        #[arg(short, long, value_parser = ChipParser)]
+        chip: Chip,
+
+        /// Generation options
+        #[arg(short, long)]
+        option: Vec<String>,
+
+        /// Path of the file in the generated project, e.g. `src/bin/main.rs`
+        #[arg(short, long)]
+        file: String,
+
+        /// Name of the project
+        #[arg(long, default_value = "project")]
+        name: String,
+
+        /// Follow every line with the template line it comes from and the
+        /// conditions it is rendered under
+        #[arg(long)]
+        annotate: bool,
+
+        /// Directory of the templates to render instead of the embedded ones
+        #[cfg(feature = "dev")]
+        #[arg(long)]
+        templates: Option<PathBuf>,
+    },
+    /// Generate every project listed in a manifest, e.g. `projects.toml`, and
+    /// summarize which ones were generated
+    Batch {
+        /// Path of the manifest
+        manifest: PathBuf,
+
+        /// Directory of the templates to render instead of the embedded ones
+        #[cfg(feature = "dev")]
+        #[arg(long)]
+        templates: Option<PathBuf>,
+    },
+    /// Render the templates of an esp-generate checkout into a project, for
+    /// working on the templates without rebuilding esp-generate
+    #[cfg(feature = "dev")]
+    Dev {
+        /// Chip to target
+        #[arg(short, long, value_parser = ChipParser)]
+        chip: Chip,
+
+        /// Generation options
+        #[arg(short, long)]
+        option: Vec<String>,
+
+        /// Directory of the templates
+        #[arg(long, default_value = "template")]
+        templates: PathBuf,
+
+        /// Name of the project
+        #[arg(long, default_value = "dev")]
+        name: String,
+
+        /// Directory in which to render the project
+        #[arg(short = 'O', long, default_value = "target")]
+        output_path: PathBuf,
+
+        /// Render the templates again whenever they change
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Start a local web UI for generating projects from a browser
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: std::net::SocketAddr,
+    },
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     Builder::from_env(Env::default().default_filter_or(log::LevelFilter::Info.as_str()))
         .format_target(false)
         .init();
 
     let args = Args::parse();
+    glyphs::init(args.ascii);
+
+    // Catch templates embedded inconsistently with the options before they
+    // render a broken project
+    let problems = integrity::problems(&Templates::embedded());
+    if !problems.is_empty() {
+        log::error!(
+            "Internal error: the embedded templates do not match the options of this version of esp-generate, please report this at https://github.com/esp-rs/esp-generate/issues"
+        );
+        for problem in &problems {
+            log::error!("{problem}");
+        }
+        process::exit(-1);
+    }
 
     // Only check for updates once the command-line arguments have been processed,
     // to avoid printing any update notifications when the help message is
     // displayed.
-    if !args.skip_update_check {
-        check_for_update(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let self_update = matches!(args.command, Some(Commands::SelfUpdate));
+    let update_check = (!args.skip_update_check && !self_update)
+        .then(|| update::spawn(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+
+    let result = run(args);
+
+    if let Some(update_check) = update_check {
+        update_check.report();
+    }
+
+    result
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Some(Commands::Verify { path }) => return provenance::verify(&path),
+        Some(Commands::Migrations { path, from }) => {
+            return migrations::print_for_project(&path, from.as_deref())
+        }
+        Some(Commands::Outdated { path }) => return outdated::print(&path),
+        Some(Commands::Configurations {
+            chip,
+            option,
+            check,
+        }) => {
+            let configurations = if check {
+                solver::is_valid(chip, &option)
+                    .then_some(option)
+                    .into_iter()
+                    .collect()
+            } else {
+                let options = option.iter().map(String::as_str).collect::<Vec<_>>();
+                solver::valid_configurations(chip, &options)?
+            };
+            for configuration in configurations {
+                println!("{}", configuration.join(" "));
+            }
+            return Ok(());
+        }
+        Some(Commands::OptionGraph) => {
+            println!("{}", serde_json::to_string_pretty(&solver::graph())?);
+            return Ok(());
+        }
+        Some(Commands::Options) => {
+            println!("{}", serde_json::to_string_pretty(&config::to_json())?);
+            return Ok(());
+        }
+        Some(Commands::SelfUpdate) => {
+            return update::self_update(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+        }
+        Some(Commands::Inspect {
+            chip,
+            option,
+            file,
+            name,
+            annotate,
+            #[cfg(feature = "dev")]
+            templates,
+        }) => {
+            #[cfg(feature = "dev")]
+            let templates = match templates {
+                Some(dir) => Templates::from_dir(&dir)?,
+                None => Templates::embedded(),
+            };
+            #[cfg(not(feature = "dev"))]
+            let templates = Templates::embedded();
+
+            let inspect = inspect::Inspect {
+                chip,
+                options: option,
+                file,
+                variables: template_variables(&name, chip),
+                annotate,
+            };
+            return inspect::run(&templates, &inspect);
+        }
+        Some(Commands::Batch {
+            ref manifest,
+            #[cfg(feature = "dev")]
+            ref templates,
+        }) => {
+            #[cfg(feature = "dev")]
+            let templates = match templates {
+                Some(dir) => Templates::from_dir(dir)?,
+                None => Templates::embedded(),
+            };
+            #[cfg(not(feature = "dev"))]
+            let templates = Templates::embedded();
+
+            return batch::run(manifest, &templates, &args.skipped(), args.experimental);
+        }
+        #[cfg(feature = "dev")]
+        Some(Commands::Dev {
+            chip,
+            option,
+            templates,
+            name,
+            output_path,
+            watch,
+        }) => {
+            let dev = dev::Dev {
+                templates_dir: templates,
+                chip,
+                options: option,
+                name,
+                output_path,
+            };
+            return dev::run(dev, watch);
+        }
+        #[cfg(feature = "serve")]
+        Some(Commands::Serve { address }) => return serve::serve(address),
+        None => {}
     }
 
-    let path = &args
+    let (request, headless) = if let Some(source) = &args.from_json {
+        let request = match GenerationRequest::from_json(source) {
+            Ok(request) => request,
+            Err(error) => {
+                log::error!("Invalid generation request: {error}");
+                process::exit(-1);
+            }
+        };
+
+        (request, true)
+    } else {
+        let chip = match args.chip {
+            Some(chip) => chip,
+            None if args.headless => {
+                log::error!("A chip must be given with `--chip` in headless mode");
+                process::exit(-1);
+            }
+            None => {
+                let terminal = tui::init_terminal()?;
+                let chip = tui::chip_picker::ChipPicker::new(load_keymap()).run(terminal)?;
+                tui::restore_terminal()?;
+
+                match chip {
+                    Some(chip) => chip,
+                    None => process::exit(-1),
+                }
+            }
+        };
+
+        let request = GenerationRequest {
+            // Guaranteed by clap when neither a subcommand nor `--from-json`
+            // was given
+            name: args.name.clone().unwrap(),
+            chip,
+            options: args.option.clone(),
+            variables: BTreeMap::new(),
+            output_path: args.output_path.clone(),
+            skip: Vec::new(),
+        };
+
+        (request, args.headless)
+    };
+
+    let name = request.name;
+    let chip = request.chip;
+    let mut skipped = request.skip;
+    skipped.extend(args.skipped());
+
+    let path = &request
         .output_path
-        .clone()
         .unwrap_or_else(|| env::current_dir().unwrap());
 
     if !path.is_dir() {
@@ -270,22 +512,107 @@ fn main() -> Result<(), Box<dyn Error>> {
         process::exit(-1);
     }
 
-    if path.join(&args.name).exists() {
+    if path.join(&name).exists() {
         log::error!("Directory already exists");
         process::exit(-1);
     }
 
+    let mut timings = Timings::default();
+
     // Validate options
-    process_options(&args);
+    let start = Instant::now();
+    let mut options = request.options;
+    let experimental = solver::experimental(&options);
+    if !args.experimental && !experimental.is_empty() {
+        log_violations(chip, &experimental);
+        process::exit(-1);
+    }
+    if let Err(violations) = process_options(chip, &options) {
+        let fix = solver::suggest_fix(chip, &options);
+        if args.auto_fix && !fix.is_empty() {
+            for violation in &violations {
+                log::warn!("{violation}");
+            }
+            log::info!("Applying fix: {fix}");
+            options = fix.apply(&options);
+        } else {
+            log_violations(chip, &violations);
+            if !fix.is_empty() {
+                log::info!("Suggested fix: {fix}, re-run with `--auto-fix` to apply it");
+            }
+            process::exit(-1);
+        }
+
+        if let Err(violations) = process_options(chip, &options) {
+            log_violations(chip, &violations);
+            process::exit(-1);
+        }
+    }
+    timings.record("options", start.elapsed());
+
+    let templates = timings.time("templates", || match args.template_channel {
+        TemplateChannel::Stable if args.template_rev.is_some() => {
+            log::error!("`--template-rev` requires `--template-channel git-main`");
+            process::exit(-1);
+        }
+        TemplateChannel::Stable => Templates::embedded(),
+        TemplateChannel::GitMain => {
+            match Templates::fetch(args.template_rev.as_deref(), !args.no_cache) {
+                Ok(templates) => templates,
+                Err(error) => {
+                    log::error!("Failed to fetch the templates: {error}");
+                    process::exit(-1);
+                }
+            }
+        }
+    });
+    if let Some(revision) = templates.revision() {
+        log::warn!(
+            "Using the templates of commit {revision}, which may rely on options or variables this version of esp-generate does not support"
+        );
+    }
+
+    let selected = if !headless {
+        let keymap = load_keymap();
+
+        // Report missing tools before the user spends time selecting options.
+        // Their versions are cached, so the check after generation is quick.
+        if !skipped.contains(&Step::Check) {
+            let failures = timings
+                .time("checks", || check::run(chip, &options, !args.no_cache))
+                .into_iter()
+                .filter(|result| !result.is_ok())
+                .collect::<Vec<_>>();
+            if !failures.is_empty() {
+                let terminal = tui::init_terminal()?;
+                let proceed =
+                    tui::preflight::Preflight::new(keymap.clone(), failures).run(terminal)?;
+                tui::restore_terminal()?;
+
+                if !proceed {
+                    process::exit(-1);
+                }
+            }
+        }
 
-    let mut selected = if !args.headless {
-        let repository = tui::Repository::new(args.chip, OPTIONS, &args.option);
+        let session_log = match &args.log_session {
+            Some(path) => match tui::SessionLog::create(path, chip, &options) {
+                Ok(log) => Some(log),
+                Err(error) => {
+                    log::error!("Failed to create {}: {error}", path.display());
+                    process::exit(-1);
+                }
+            },
+            None => None,
+        };
+        let repository =
+            tui::Repository::new(chip, config::visible_options(args.experimental), &options);
 
         // TUI stuff ahead
         let terminal = tui::init_terminal()?;
 
         // create app and run it
-        let selected = tui::App::new(repository).run(terminal)?;
+        let selected = tui::App::new(repository, keymap, session_log).run(terminal)?;
 
         tui::restore_terminal()?;
         // done with the TUI
@@ -296,30 +623,195 @@ fn main() -> Result<(), Box<dyn Error>> {
             process::exit(-1);
         }
     } else {
-        args.option.clone()
+        options
     };
 
-    selected.push(args.chip.to_string());
+    let mut variables = template_variables(&name, chip);
+    override_variables(&mut variables, &request.variables)?;
 
-    selected.push(if args.chip.is_riscv() {
-        "riscv".to_string()
-    } else {
-        "xtensa".to_string()
+    let project_dir = path.join(&name);
+    fs::create_dir(&project_dir)?;
+
+    let files = timings.time("render", || {
+        render_template(&templates, chip, &selected, &variables)
     });
+    if cfg!(windows) {
+        let scripts = posix_only_scripts(&files);
+        if !scripts.is_empty() {
+            log::warn!(
+                "These scripts have no PowerShell equivalent and need a POSIX shell, e.g. Git Bash or WSL: {}",
+                scripts.join(", ")
+            );
+        }
+    }
+    write_project(&project_dir, files, &skipped, &mut timings)?;
+    log_crate_docs(&project_dir, chip);
+
+    if !skipped.contains(&Step::Git) {
+        init_git_repo(&project_dir, &mut timings);
+    }
+
+    if !skipped.contains(&Step::Check) {
+        timings.time("checks", || check::check(chip, &selected, !args.no_cache));
+
+        let target = if selected.iter().any(|option| option == "esp-idf-std") {
+            capability::esp_idf_target(chip)
+        } else {
+            chip.target()
+        };
+        timings.time("cargo-config", || cargo_config::check(&project_dir, target));
+    }
+
+    if args.timings {
+        println!("\nTimings");
+        print!("{}", timings.report());
+    }
+
+    Ok(())
+}
 
-    let wokwi_devkit = match args.chip {
-        Chip::Esp32 => "board-esp32-devkit-c-v4",
-        Chip::Esp32c2 => "",
-        Chip::Esp32c3 => "board-esp32-c3-devkitm-1",
-        Chip::Esp32c6 => "board-esp32-c6-devkitc-1",
-        Chip::Esp32h2 => "board-esp32-h2-devkitm-1",
-        Chip::Esp32s2 => "board-esp32-s2-devkitm-1",
-        Chip::Esp32s3 => "board-esp32-s3-devkitc-1",
+/// Log the versions of the HAL crates the project uses, with the documentation
+/// and migration guide matching them.
+fn log_crate_docs(project_dir: &Path, chip: Chip) {
+    let manifest = match cargo::CargoToml::load(project_dir) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            log::warn!("{error}");
+            return;
+        }
     };
 
+    for docs in crate_docs::collect(&manifest, chip) {
+        log::info!(
+            "Using {} {}, documented at {}",
+            docs.name,
+            docs.version,
+            docs.docs
+        );
+        if let Some(guide) = docs.migration_guide {
+            log::info!("  Migration guide: {guide}");
+        }
+    }
+}
+
+/// Load the TUI key bindings, exiting if the user config file is invalid.
+fn load_keymap() -> tui::Keymap {
+    match tui::Keymap::load() {
+        Ok(keymap) => keymap,
+        Err(error) => {
+            log::error!("Invalid key bindings: {error}");
+            process::exit(-1);
+        }
+    }
+}
+
+/// A complete generation request, as accepted by `--from-json`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenerationRequest {
+    /// Name of the project to generate
+    name: String,
+    /// Chip to target
+    chip: Chip,
+    /// Generation options
+    #[serde(default)]
+    options: Vec<String>,
+    /// Overrides for the template variables
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+    /// Directory in which to generate the project
+    #[serde(default)]
+    output_path: Option<PathBuf>,
+    /// Post-processing steps to skip
+    #[serde(default)]
+    skip: Vec<Step>,
+}
+
+impl GenerationRequest {
+    /// Read a request from the given JSON file, or from stdin if `source` is
+    /// `-`, and validate it against the known options and variables.
+    fn from_json(source: &str) -> Result<Self, Box<dyn Error>> {
+        let json = if source == "-" {
+            io::read_to_string(io::stdin())?
+        } else {
+            fs::read_to_string(source)?
+        };
+
+        let request: Self = serde_json::from_str(&json)?;
+        request.validate()?;
+
+        Ok(request)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let known_options = OPTIONS
+            .iter()
+            .flat_map(|option| option.options())
+            .collect::<Vec<_>>();
+        for option in &self.options {
+            let name = split_value(option).map_or(option.as_str(), |(name, _)| name);
+            if !known_options.iter().any(|known| known == name) {
+                return Err(Violation::Unknown {
+                    option: name.to_string(),
+                    suggestions: solver::similar_options(name),
+                }
+                .to_string());
+            }
+        }
+
+        let mut variables = template_variables(&self.name, self.chip);
+        override_variables(&mut variables, &self.variables)
+    }
+}
+
+/// The template variables which may be overridden. The others are derived from
+/// the project name and the chip, e.g. the crate name or the Rust target, and
+/// the project would not build with other values.
+const OVERRIDABLE_VARIABLES: &[&str] = &["wokwi-board"];
+
+/// The overridable variables whose value differs from their default.
+fn overridden_variables(chip: Chip, variables: &[(String, String)]) -> Vec<(String, String)> {
+    let defaults = template_variables("", chip);
+    variables
+        .iter()
+        .filter(|(key, value)| {
+            OVERRIDABLE_VARIABLES.contains(&key.as_str())
+                && defaults.iter().any(|(k, v)| k == key && v != value)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Replace the values of template variables with those of a request or batch
+/// manifest, rejecting unknown and derived variables.
+fn override_variables(
+    variables: &mut [(String, String)],
+    overrides: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    for (key, value) in overrides {
+        let Some(variable) = variables.iter_mut().find(|(k, _)| k == key) else {
+            return Err(format!("Unknown variable '{key}'"));
+        };
+        if !OVERRIDABLE_VARIABLES.contains(&key.as_str()) {
+            return Err(format!(
+                "Variable '{key}' is derived from the project name and chip, it cannot be overridden"
+            ));
+        }
+        variable.1 = value.clone();
+    }
+
+    Ok(())
+}
+
+/// The template variables, and their values, for the given project name and
+/// chip.
+fn template_variables(name: &str, chip: Chip) -> Vec<(String, String)> {
+    let wokwi_devkit = capability::wokwi_board(chip).unwrap_or_default();
+
     let mut variables = vec![
-        ("project-name".to_string(), args.name.clone()),
-        ("mcu".to_string(), args.chip.to_string()),
+        ("project-name".to_string(), name.to_string()),
+        ("crate-name".to_string(), name.replace('-', "_")),
+        ("mcu".to_string(), chip.to_string()),
         ("wokwi-board".to_string(), wokwi_devkit.to_string()),
         (
             "generate-version".to_string(),
@@ -327,55 +819,305 @@ fn main() -> Result<(), Box<dyn Error>> {
         ),
     ];
 
-    variables.push(("rust_target".to_string(), args.chip.target().to_string()));
+    variables.push(("rust_target".to_string(), chip.target().to_string()));
 
-    let project_dir = path.join(&args.name);
-    fs::create_dir(&project_dir)?;
+    variables
+}
+
+/// Shell scripts which run in the dev container, rather than on the host.
+const CONTAINER_SCRIPTS: &[&str] = &["scripts/build.sh", "scripts/flash.sh"];
+
+/// The rendered shell scripts to run on the host without a PowerShell
+/// equivalent next to them, which Windows users cannot run natively.
+fn posix_only_scripts(files: &[(String, String)]) -> Vec<&str> {
+    files
+        .iter()
+        .map(|(path, _)| path.as_str())
+        .filter(|path| !CONTAINER_SCRIPTS.contains(path))
+        .filter_map(|path| path.strip_suffix(".sh").map(|stem| (path, stem)))
+        .filter(|(_, stem)| {
+            let equivalent = format!("{stem}.ps1");
+            !files.iter().any(|(path, _)| *path == equivalent)
+        })
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// The directory of the templates of the `esp-idf-std` flavor, each replacing
+/// the bare-metal template at the same path if there is one.
+const ESP_IDF_STD_TEMPLATES: &str = "esp-idf-std/";
+
+/// Render all template files for the given chip, selected options and
+/// variables, returning the relative path and contents of each included file.
+///
+/// This does not touch the file system nor run any external tools, so it can
+/// be used by any front-end. The output only depends on the inputs: files are
+/// sorted by path and always use `\n` line endings.
+fn render_template(
+    templates: &Templates,
+    chip: Chip,
+    options: &[String],
+    variables: &[(String, String)],
+) -> Vec<(String, String)> {
+    let (selected, variables) = render_inputs(templates, chip, options, variables);
+    let esp_idf_std = selected.iter().any(|option| option == "esp-idf-std");
+
+    // The files of the `esp-idf-std` flavor replace those at the same path
+    let overrides = templates
+        .files()
+        .iter()
+        .filter_map(|(file_path, _)| file_path.strip_prefix(ESP_IDF_STD_TEMPLATES))
+        .collect::<Vec<_>>();
+
+    let snippets = Snippets::collect(templates, &selected);
+
+    let mut files = templates
+        .files()
+        .iter()
+        .filter(|(file_path, _)| !file_path.starts_with(snippets::SNIPPETS_DIR))
+        .filter_map(|(file_path, contents)| {
+            let file_path = match file_path.strip_prefix(ESP_IDF_STD_TEMPLATES) {
+                Some(file_path) if esp_idf_std => file_path,
+                Some(_) => return None,
+                None if esp_idf_std && overrides.contains(&file_path.as_str()) => return None,
+                None => file_path,
+            };
+
+            let contents = snippets.expand(contents);
+            let processed = process_file(&contents, &selected, &variables)?;
+            let processed = if file_path.ends_with(".rs") {
+                imports::organize(&processed)
+            } else {
+                processed
+            };
+
+            Some((file_path.to_string(), processed))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    files
+}
+
+/// The options templates are rendered with, i.e. the selected ones and those
+/// derived from the chip, and the variables they are rendered with.
+fn render_inputs(
+    templates: &Templates,
+    chip: Chip,
+    options: &[String],
+    variables: &[(String, String)],
+) -> (Vec<String>, Vec<(String, String)>) {
+    let mut selected = options.to_vec();
+
+    selected.sort();
+    selected.dedup();
+
+    // Record what the project was generated from in its manifest:
+    let mut variables = variables.to_vec();
+    variables.push((
+        "generate-hash".to_string(),
+        provenance::hash(templates, chip, &selected, &variables),
+    ));
+    variables.push((
+        "generate-options".to_string(),
+        selected
+            .iter()
+            .map(|option| format!("\"{option}\""))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ));
+    variables.extend(value_variables(OPTIONS, &selected));
+    variables.extend(chip_variables(chip));
+    if let Some(board) = board::devkit(chip) {
+        variables.extend(board.variables());
+    }
+
+    if let Some(revision) = min_chip_revision(OPTIONS, chip, &selected) {
+        variables.push(("min-chip-revision".to_string(), revision.to_string()));
+        selected.push("chip-revision-check".to_string());
+    }
+
+    if let Some(revision) = templates.revision() {
+        variables.push(("template-revision".to_string(), revision.to_string()));
+        selected.push("template-revision".to_string());
+    }
+
+    // `verify` renders with the same overrides
+    let overridden = overridden_variables(chip, &variables);
+    if !overridden.is_empty() {
+        let overridden = overridden
+            .iter()
+            .map(|(key, value)| format!("{key} = {}", toml_edit::Value::from(value.as_str())))
+            .collect::<Vec<_>>();
+        variables.push(("generate-variables".to_string(), overridden.join(", ")));
+        selected.push("variable-overrides".to_string());
+    }
+
+    let esp_idf_std = selected.iter().any(|option| option == "esp-idf-std");
+    if esp_idf_std {
+        for (key, value) in variables.iter_mut() {
+            if key == "rust_target" {
+                *value = capability::esp_idf_target(chip).to_string();
+            }
+        }
+    }
+
+    selected.push(chip.to_string());
+    selected.extend(capability::template_options(chip));
+
+    (selected, variables)
+}
+
+/// The variables describing the chip's hardware, as documented by
+/// `docs/chip.md`.
+fn chip_variables(chip: Chip) -> Vec<(String, String)> {
+    let config = esp_metadata::Config::for_chip(&chip);
+
+    let arch = match config.arch() {
+        Arch::RiscV => "RISC-V",
+        Arch::Xtensa => "Xtensa",
+    };
+    let cores = match config.cores() {
+        Cores::Single => "1",
+        Cores::Multi => "2",
+    };
+    let ram = capability::internal_ram_kib(chip)
+        .map_or("unknown".to_string(), |size| format!("{size} KiB"));
+    let peripherals = config
+        .peripherals()
+        .iter()
+        .map(|peripheral| format!("`{peripheral}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    vec![
+        ("chip-name".to_string(), chip.pretty_name().to_string()),
+        ("chip-arch".to_string(), arch.to_string()),
+        ("chip-cores".to_string(), cores.to_string()),
+        ("chip-ram".to_string(), ram),
+        ("chip-peripherals".to_string(), peripherals),
+    ]
+}
+
+/// The variables holding the selected (or default) value of each value option.
+fn value_variables(items: &[GeneratorOptionItem], selected: &[String]) -> Vec<(String, String)> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            GeneratorOptionItem::Category(category) => value_variables(category.options, selected),
+            GeneratorOptionItem::Value(option) => {
+                vec![(option.name.to_string(), option.value(selected).to_string())]
+            }
+            GeneratorOptionItem::Option(_) => vec![],
+        })
+        .collect()
+}
 
-    for &(file_path, contents) in template_files::TEMPLATE_FILES.iter() {
-        if let Some(processed) = process_file(contents, &selected, &variables) {
+/// The oldest chip revision supported by all selected options, if any of them
+/// restricts it for the given chip.
+fn min_chip_revision(
+    items: &[GeneratorOptionItem],
+    chip: Chip,
+    selected: &[String],
+) -> Option<u16> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            GeneratorOptionItem::Category(category) => {
+                min_chip_revision(category.options, chip, selected)
+            }
+            GeneratorOptionItem::Option(option) if selected.iter().any(|s| s == option.name) => {
+                option
+                    .min_chip_revision
+                    .iter()
+                    .find(|(c, _)| *c == chip)
+                    .map(|(_, revision)| *revision)
+            }
+            _ => None,
+        })
+        .max()
+}
+
+/// Write the rendered files into `project_dir` and format them, unless
+/// skipped, recording the duration of each step.
+fn write_project(
+    project_dir: &Path,
+    files: Vec<(String, String)>,
+    skipped: &[Step],
+    timings: &mut Timings,
+) -> Result<(), Box<dyn Error>> {
+    timings.time("write", || -> io::Result<()> {
+        for (file_path, contents) in files {
             let file_path = project_dir.join(file_path);
 
             fs::create_dir_all(file_path.parent().unwrap())?;
-            fs::write(file_path, processed)?;
+            fs::write(file_path, contents)?;
+        }
+
+        Ok(())
+    })?;
+
+    if !skipped.contains(&Step::Fmt) {
+        if !check::in_path("cargo") {
+            log::warn!(
+                "`cargo` was not found in PATH, the project is left unformatted. Install Rust, or pass `--no-fmt` to skip this step"
+            );
+        } else {
+            // Run cargo fmt:
+            let output = timings.time("rustfmt", || {
+                Command::new("cargo")
+                    .args([
+                        "fmt",
+                        "--",
+                        "--config",
+                        "group_imports=StdExternalCrate",
+                        "--config",
+                        "imports_granularity=Module",
+                        "--config",
+                        "newline_style=Unix",
+                    ])
+                    .current_dir(project_dir)
+                    .output()
+            });
+            warn_on_failure("cargo fmt", output);
         }
     }
 
-    // Run cargo fmt:
-    Command::new("cargo")
-        .args([
-            "fmt",
-            "--",
-            "--config",
-            "group_imports=StdExternalCrate",
-            "--config",
-            "imports_granularity=Module",
-        ])
-        .current_dir(&project_dir)
-        .output()?;
+    if !skipped.contains(&Step::Taplo) {
+        // Format Cargo.toml:
+        let start = Instant::now();
+        let input = fs::read_to_string(project_dir.join("Cargo.toml"))?;
+        let formated = format_cargo_toml(&input);
+        fs::write(project_dir.join("Cargo.toml"), formated)?;
+        timings.record("taplo", start.elapsed());
+    }
+
+    Ok(())
+}
+
+/// Warn when an external command run on the generated project could not be
+/// run or failed, which leaves the project usable but e.g. unformatted.
+fn warn_on_failure(command: &str, output: io::Result<Output>) {
+    match output {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => log::warn!(
+            "`{command}` failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(error) => log::warn!("Could not run `{command}`: {error}"),
+    }
+}
 
-    // Format Cargo.toml:
-    let input = fs::read_to_string(project_dir.join("Cargo.toml"))?;
+/// Format the contents of a generated `Cargo.toml` with Taplo.
+fn format_cargo_toml(input: &str) -> String {
     let format_options = Options {
         align_entries: true,
         reorder_keys: true,
         reorder_arrays: true,
         ..Default::default()
     };
-    let formated = taplo::formatter::format(&input, format_options);
-    fs::write(project_dir.join("Cargo.toml"), formated)?;
-
-    if should_initialize_git_repo(&project_dir) {
-        // Run git init:
-        Command::new("git")
-            .arg("init")
-            .current_dir(&project_dir)
-            .output()?;
-    } else {
-        log::warn!("Current directory is already in a git repository, skipping git initialization");
-    }
-
-    Ok(())
+    taplo::formatter::format(input, format_options)
 }
 
 fn process_file(
@@ -383,10 +1125,33 @@ fn process_file(
     options: &[String],             // Selected options
     variables: &[(String, String)], // Variables and their values in tuples
 ) -> Option<String> {
-    let mut res = String::new();
+    let lines = process_lines(contents, options, variables)?;
+
+    Some(lines.into_iter().map(|line| line.text + "\n").collect())
+}
+
+/// A line of a rendered template.
+struct RenderedLine {
+    text: String,
+    /// The index of the line in the template.
+    index: usize,
+    /// The conditions of the `IF` branches the line is in, outermost first,
+    /// negated for `ELSE` branches.
+    branches: Vec<String>,
+}
+
+/// The lines of a template rendered like [`process_file`] does, along with
+/// where they come from.
+fn process_lines(
+    contents: &str,
+    options: &[String],
+    variables: &[(String, String)],
+) -> Option<Vec<RenderedLine>> {
+    let mut res = Vec::new();
 
     let mut replace: Option<Vec<(String, String)>> = None;
     let mut include = vec![true];
+    let mut branches: Vec<String> = Vec::new();
     let mut first_line = true;
 
     // Create a new Rhai engine and scope
@@ -401,7 +1166,7 @@ fn process_file(
         options_clone.contains(&cond)
     });
 
-    for line in contents.lines() {
+    for (index, line) in contents.lines().enumerate() {
         let trimmed: &str = line.trim();
 
         // We check for the first line to see if we should include the file
@@ -412,7 +1177,11 @@ fn process_file(
                 .or_else(|| trimmed.strip_prefix("#INCLUDEFILE "));
 
             if let Some(cond) = cond {
-                let include_file = if let Some(stripped) = cond.strip_prefix("!") {
+                // Either an option name, optionally negated, or a condition
+                // like those of `IF`
+                let include_file = if cond.contains("option(") {
+                    engine.eval::<bool>(cond).unwrap()
+                } else if let Some(stripped) = cond.strip_prefix("!") {
                     !options.contains(&stripped.to_string())
                 } else {
                     options.contains(&cond.to_string())
@@ -467,12 +1236,17 @@ fn process_file(
             };
             let res = engine.eval::<bool>(cond).unwrap();
             include.push(res && *include.last().unwrap());
+            branches.push(cond.to_string());
         } else if trimmed.starts_with("#ELSE") || trimmed.starts_with("//ELSE") {
             let res = !*include.last().unwrap();
             include.pop();
             include.push(res);
+            if let Some(cond) = branches.pop() {
+                branches.push(negate(&cond));
+            }
         } else if trimmed.starts_with("#ENDIF") || trimmed.starts_with("//ENDIF") {
             include.pop();
+            branches.pop();
         // Trim #+ and //+
         } else if include.iter().all(|v| *v) {
             let mut line = line.to_string();
@@ -491,8 +1265,11 @@ fn process_file(
                 }
             }
 
-            res.push_str(&line);
-            res.push('\n');
+            res.push(RenderedLine {
+                text: line,
+                index,
+                branches: branches.clone(),
+            });
 
             replace = None;
         }
@@ -501,35 +1278,60 @@ fn process_file(
     Some(res)
 }
 
-fn process_options(args: &Args) {
-    for option in &args.option {
-        // Find the matching option in OPTIONS
-        if let Some(option_item) = OPTIONS.iter().find(|item| item.name() == *option) {
-            // Check if the chip is supported. If the chip list is empty,
-            // all chips are supported:
-            if !option_item.chips().iter().any(|chip| chip == &args.chip)
-                && !option_item.chips().is_empty()
-            {
-                log::error!(
-                    "Option '{}' is not supported for chip {}",
-                    option,
-                    args.chip
-                );
-                process::exit(-1);
-            }
-            if !option_item
-                .enables()
-                .iter()
-                .all(|requirement| args.option.contains(&requirement.to_string()))
-            {
-                log::error!(
-                    "Option '{}' requires {}",
-                    option_item.name(),
-                    option_item.enables().join(", ")
-                );
-                process::exit(-1);
-            }
-        }
+/// The condition of the `ELSE` branch of `IF <cond>`.
+fn negate(cond: &str) -> String {
+    if cond.contains(' ') {
+        format!("!({cond})")
+    } else if let Some(negated) = cond.strip_prefix('!') {
+        negated.to_string()
+    } else {
+        format!("!{cond}")
+    }
+}
+
+/// Log why the options are rejected. Unknown options are followed by the list of
+/// options available for the chip.
+fn log_violations(chip: Chip, violations: &[Violation]) {
+    for violation in violations {
+        log::error!("{violation}");
+    }
+    if violations
+        .iter()
+        .any(|violation| matches!(violation, Violation::Unknown { .. }))
+    {
+        log::info!(
+            "Options available for {chip}: {}",
+            solver::available_options(chip).join(", ")
+        );
+    }
+}
+
+fn process_options(chip: Chip, options: &[String]) -> Result<(), Vec<Violation>> {
+    let violations = solver::violations(chip, options);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Initialize a git repository in the project, unless it is already in one.
+fn init_git_repo(project_dir: &Path, timings: &mut Timings) {
+    if !check::in_path("git") {
+        log::warn!(
+            "`git` was not found in PATH, skipping git initialization. Install git, or pass `--no-git` to skip this step"
+        );
+    } else if should_initialize_git_repo(project_dir) {
+        // Run git init:
+        let output = timings.time("git", || {
+            Command::new("git")
+                .arg("init")
+                .current_dir(project_dir)
+                .output()
+        });
+        warn_on_failure("git init", output);
+    } else {
+        log::warn!("Current directory is already in a git repository, skipping git initialization");
     }
 }
 
@@ -553,6 +1355,16 @@ fn should_initialize_git_repo(mut path: &Path) -> bool {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::config::find_option_item;
+
+    /// The files of a project called `my-project`, by their path.
+    fn render(chip: Chip, options: &[&str]) -> BTreeMap<String, String> {
+        let options = options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        let variables = template_variables("my-project", chip);
+        render_template(&Templates::embedded(), chip, &options, &variables)
+            .into_iter()
+            .collect()
+    }
 
     #[test]
     fn test_nested_if_else1() {
@@ -696,4 +1508,1138 @@ mod test {
             res.trim()
         );
     }
+
+    #[test]
+    fn test_generation_request_validation() {
+        let request: GenerationRequest = serde_json::from_str(
+            r#"{ "name": "test", "chip": "esp32c3", "options": ["alloc", "wokwi"] }"#,
+        )
+        .unwrap();
+        assert!(request.validate().is_ok());
+
+        let request: GenerationRequest =
+            serde_json::from_str(r#"{ "name": "test", "chip": "esp32c3", "options": ["wifl"] }"#)
+                .unwrap();
+        assert!(request.validate().is_err());
+
+        let request: GenerationRequest = serde_json::from_str(
+            r#"{ "name": "test", "chip": "esp32c3", "variables": { "unknown": "value" } }"#,
+        )
+        .unwrap();
+        assert!(request.validate().is_err());
+
+        // Derived variables cannot be overridden, e.g. the crate name
+        let request: GenerationRequest = serde_json::from_str(
+            r#"{ "name": "test", "chip": "esp32c3", "variables": { "wokwi-board": "board-esp32-c3-rust-1" } }"#,
+        )
+        .unwrap();
+        assert!(request.validate().is_ok());
+        let request: GenerationRequest = serde_json::from_str(
+            r#"{ "name": "test", "chip": "esp32c3", "variables": { "crate-name": "other" } }"#,
+        )
+        .unwrap();
+        assert!(request.validate().is_err());
+
+        assert!(serde_json::from_str::<GenerationRequest>(
+            r#"{ "name": "test", "chip": "esp32c3", "unknown": true }"#
+        )
+        .is_err());
+
+        let request: GenerationRequest = serde_json::from_str(
+            r#"{ "name": "test", "chip": "esp32c3", "skip": ["fmt", "git"] }"#,
+        )
+        .unwrap();
+        assert_eq!(request.skip, [Step::Fmt, Step::Git]);
+        assert!(serde_json::from_str::<GenerationRequest>(
+            r#"{ "name": "test", "chip": "esp32c3", "skip": ["lint"] }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_value_options() {
+        let chip = Chip::Esp32c3;
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        assert!(process_options(chip, &options(&["alloc", "heap-size=96"])).is_ok());
+        assert!(process_options(chip, &options(&["alloc", "heap-size=4096"])).is_err());
+        assert!(process_options(chip, &options(&["alloc", "heap-size"])).is_err());
+        assert!(process_options(chip, &options(&["heap-size=96"])).is_err());
+        assert!(process_options(chip, &options(&["alloc=1"])).is_err());
+
+        let variables = value_variables(OPTIONS, &options(&["alloc", "heap-size=96"]));
+        assert!(variables.contains(&("heap-size".to_string(), "96".to_string())));
+
+        let variables = value_variables(OPTIONS, &options(&["alloc"]));
+        assert!(variables.contains(&("heap-size".to_string(), "72".to_string())));
+    }
+
+    #[test]
+    fn test_min_chip_revision() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        let selected = options(&["alloc", "heap-psram"]);
+        assert_eq!(
+            min_chip_revision(OPTIONS, Chip::Esp32, &selected),
+            Some(300)
+        );
+        assert_eq!(min_chip_revision(OPTIONS, Chip::Esp32s3, &selected), None);
+        assert_eq!(
+            min_chip_revision(OPTIONS, Chip::Esp32, &options(&["alloc"])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let supported =
+            |name: &str, chip: Chip| find_option_item(name, OPTIONS).unwrap().supports(chip);
+
+        assert!(supported("wifi", Chip::Esp32s2));
+        assert!(!supported("wifi", Chip::Esp32h2));
+        assert!(supported("ble", Chip::Esp32h2));
+        assert!(!supported("ble", Chip::Esp32s2));
+        assert!(!supported("heap-psram", Chip::Esp32c6));
+        assert!(!supported("wokwi", Chip::Esp32c2));
+        assert!(supported("battery-low-mv", Chip::Esp32c3));
+        assert!(!supported("battery", Chip::Esp32));
+        assert!(supported("ui-slint", Chip::Esp32s3));
+        assert!(!supported("ui-slint", Chip::Esp32c6));
+        assert!(supported("audio-out", Chip::Esp32c6));
+        assert!(!supported("audio-out", Chip::Esp32c2));
+        assert!(supported("hw-crypto", Chip::Esp32c2));
+
+        assert_eq!(
+            capability::template_options(Chip::Esp32),
+            [
+                "xtensa",
+                "has-multi-core",
+                "has-wifi",
+                "has-bt",
+                "has-psram",
+                "has-wokwi",
+                "has-devkit",
+                "has-led",
+                "has-display",
+                "has-audio",
+                "has-sha",
+                "has-aes",
+                "has-rsa",
+                "has-deep-sleep",
+                "has-rtc-ram"
+            ]
+        );
+        assert!(capability::template_options(Chip::Esp32c3).contains(&"has-systimer".to_string()));
+        assert_eq!(
+            capability::template_options(Chip::Esp32c6)[..4],
+            ["riscv", "has-lp-core", "has-usb-serial-jtag", "has-wifi"]
+        );
+
+        let files = render(Chip::Esp32c6, &["embassy"]);
+        let main = &files["src/bin/async_main.rs"];
+        assert!(main.contains("SystemTimer::new"));
+    }
+
+    #[test]
+    fn test_chip_docs() {
+        let files = render(Chip::Esp32c3, &["chip-docs"]);
+        let docs = &files["docs/chip.md"];
+
+        assert!(docs.starts_with("# ESP32-C3\n"));
+        assert!(docs.contains("- Rust target: `riscv32imc-unknown-none-elf`"));
+        assert!(docs.contains("- Internal SRAM: 400 KiB"));
+        assert!(docs.contains("`systimer`"));
+        assert!(!docs.contains("PSRAM: used"));
+    }
+
+    #[test]
+    fn test_ident() {
+        let files = render(Chip::Esp32c3, &["ident", "ble"]);
+        let ident = &files["src/ident.rs"];
+
+        assert!(ident.contains("const NAME_PREFIX: &str = \"my-project\";"));
+        assert!(ident.contains("pub fn ble_address()"));
+    }
+
+    #[test]
+    fn test_beacon() {
+        let beacon = |options: &[&str]| render(Chip::Esp32c3, options)["src/beacon.rs"].clone();
+
+        let ibeacon = beacon(&["alloc", "ble", "beacon", "beacon-tx-power=65"]);
+        assert!(ibeacon.contains("pub const MEASURED_POWER: i8 = -65;"));
+        // Beacons only advertise, they are not connectable
+        assert!(ibeacon.contains("advertising_type: AdvertisingType::AdvNonConnInd,"));
+        assert!(!ibeacon.contains("cmd_set_le_advertising_parameters()"));
+        assert!(ibeacon.contains("ManufacturerSpecificData"));
+
+        let eddystone = beacon(&["alloc", "ble", "beacon", "beacon-eddystone"]);
+        assert!(eddystone.contains("pub const MEASURED_POWER: i8 = -59;"));
+        assert!(!eddystone.contains("ManufacturerSpecificData"));
+    }
+
+    #[test]
+    fn test_release_tooling() {
+        let files = render(Chip::Esp32s3, &["release-tooling"]);
+        let workflow = &files[".github/workflows/release.yml"];
+
+        assert!(workflow.contains(
+            "run: espflash save-image --chip esp32s3 target/xtensa-esp32s3-none-elf/release/my-project my-project-${{ github.ref_name }}.bin"
+        ));
+        assert!(workflow.contains("uses: esp-rs/xtensa-toolchain@v1.5"));
+        assert!(files.contains_key("cliff.toml"));
+        assert!(files.contains_key("release.toml"));
+        assert!(!files.contains_key("scripts/firmware-manifest.sh"));
+    }
+
+    #[test]
+    fn test_firmware_manifest() {
+        let files = render(Chip::Esp32c6, &["release-tooling", "firmware-manifest"]);
+
+        let script = &files["scripts/firmware-manifest.sh"];
+        assert!(script.starts_with("#!/usr/bin/env bash\n"));
+        assert!(script.contains("CHIP=\"esp32c6\""));
+        assert!(script.contains("TARGET=\"riscv32imac-unknown-none-elf\""));
+        assert!(script.contains("NAME=\"my-project\""));
+        assert!(files[".github/workflows/release.yml"].contains("firmware-manifest.json"));
+    }
+
+    #[test]
+    fn test_web_installer() {
+        let files = render(Chip::Esp32s3, &["web-installer"]);
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&files["web/manifest.json"]).unwrap();
+        assert_eq!(manifest["name"], "my-project");
+        assert_eq!(manifest["builds"][0]["chipFamily"], "ESP32-S3");
+        assert!(files[".github/workflows/pages.yml"].contains(
+            "espflash save-image --merge --chip esp32s3 target/xtensa-esp32s3-none-elf/release/my-project web/firmware.bin"
+        ));
+    }
+
+    #[test]
+    fn test_docker_build() {
+        let files = render(Chip::Esp32s3, &["docker-build", "ci"]);
+
+        let dockerfile = &files["Dockerfile.build"];
+        assert!(dockerfile.contains("--targets esp32s3"));
+        assert!(!dockerfile.contains("RUSTUP_TOOLCHAIN"));
+        assert!(files["build-in-docker.sh"].contains("ENVIRONMENT=\". /export-esp.sh && \""));
+        assert!(files[".github/workflows/rust_ci.yml"].contains("run: bash build-in-docker.sh"));
+    }
+
+    #[test]
+    fn test_cargo_aliases() {
+        let config =
+            |options: &[&str]| render(Chip::Esp32c6, options)[".cargo/config.toml"].clone();
+
+        let espflash = config(&[]);
+        assert!(espflash.contains(
+            r#"flash = ["run", "--release", "--config", "target.riscv32imac-unknown-none-elf.runner='espflash flash'"]"#
+        ));
+        assert_eq!(espflash.matches("[alias]").count(), 1);
+
+        let probe_rs = config(&["probe-rs", "stack-usage", "embassy"]);
+        assert!(probe_rs.contains("runner='probe-rs attach --chip=esp32c6'"));
+        assert!(probe_rs.contains("stack-usage = "));
+        assert_eq!(probe_rs.matches("[alias]").count(), 1);
+
+        let trace = config(&["embassy", "probe-rs", "trace"]);
+        assert!(trace.contains(r#"env.DEFMT_LOG='info,my_project::trace=trace'"#));
+        assert!(!probe_rs.contains("trace = "));
+
+        let small = config(&["release-small"]);
+        assert!(small.contains("cargo +nightly build-small"));
+        assert!(small.contains(
+            r#""--profile", "release-small", "-Z", "build-std-features=panic_immediate_abort""#
+        ));
+        assert!(!espflash.contains("build-small"));
+    }
+
+    #[test]
+    fn test_esp_idf_std() {
+        let files = render(Chip::Esp32c3, &["esp-idf-std", "wokwi"]);
+        assert!(files["Cargo.toml"].contains("esp-idf-svc"));
+        assert!(!files["Cargo.toml"].contains("esp-hal"));
+        assert!(files["build.rs"].contains("embuild"));
+        assert!(files.contains_key("sdkconfig.defaults"));
+        assert!(files[".cargo/config.toml"].contains("[target.riscv32imc-esp-espidf]"));
+        assert!(files["wokwi.toml"].contains("target/riscv32imc-esp-espidf/debug/my-project"));
+        assert!(files["rust-toolchain.toml"].contains("nightly"));
+        assert!(files["src/bin/main.rs"].contains("link_patches"));
+        assert!(!files.contains_key("src/lib.rs"));
+        assert!(files.keys().all(|path| !path.starts_with("esp-idf-std/")));
+
+        let files = render(Chip::Esp32s3, &["esp-idf-std", "wokwi"]);
+        assert!(files[".cargo/config.toml"].contains("target = \"xtensa-esp32s3-espidf\""));
+
+        // The bare-metal templates are unaffected
+        let files = render(Chip::Esp32c3, &[]);
+        assert!(files.contains_key("src/lib.rs"));
+        assert!(!files.contains_key("sdkconfig.defaults"));
+
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        assert!(solver::is_valid(
+            Chip::Esp32c3,
+            &options(&["esp-idf-std", "chip-docs"])
+        ));
+        assert!(solver::is_valid(
+            Chip::Esp32c3,
+            &options(&["esp-idf-std", "wifi", "alloc"])
+        ));
+        assert!(!solver::is_valid(
+            Chip::Esp32c3,
+            &options(&["esp-idf-std", "alloc", "heap-size=64"])
+        ));
+        assert!(solver::are_exclusive("esp-idf-std", "ble"));
+        assert!(solver::are_exclusive("esp-idf-std", "heap-psram"));
+        assert!(!solver::are_exclusive("esp-idf-std", "wifi"));
+        assert_eq!(
+            process_options(Chip::Esp32c3, &options(&["esp-idf-std", "alloc", "ble"])),
+            Err(vec![Violation::Conflict {
+                option: "ble".to_string(),
+                with: "esp-idf-std".to_string(),
+            }])
+        );
+        assert!(
+            process_options(Chip::Esp32c3, &options(&["esp-idf-std", "alloc", "wifi"])).is_ok()
+        );
+
+        // Options shared with the bare-metal flavor generate their ESP-IDF
+        // equivalent
+        let files = render(Chip::Esp32c3, &["esp-idf-std", "wifi", "alloc"]);
+        assert!(files["src/bin/main.rs"].contains("BlockingWifi"));
+        assert!(!files["src/bin/main.rs"].contains("esp_wifi"));
+        assert!(files[".cargo/config.toml"].contains("WIFI_SSID"));
+        assert!(!files["Cargo.toml"].contains("esp-alloc"));
+    }
+
+    #[test]
+    fn test_flip_link() {
+        let supported = |chip: Chip| {
+            find_option_item("flip-link", OPTIONS)
+                .unwrap()
+                .supports(chip)
+        };
+        assert!(supported(Chip::Esp32c6));
+        assert!(!supported(Chip::Esp32c3));
+        assert!(!supported(Chip::Esp32s3));
+
+        let files = render(Chip::Esp32c6, &["flip-link", "probe-rs"]);
+
+        // esp-hal sizes the stack in linkall.x, which must come last
+        let link_args = &files["build.rs"]
+            .lines()
+            .filter(|line| line.contains("cargo:rustc-link-arg="))
+            .collect::<Vec<_>>();
+        assert_eq!(link_args.len(), 2);
+        assert!(link_args.last().unwrap().contains("-Tlinkall.x"));
+        assert!(files["Cargo.toml"].contains("\"flip-link\""));
+    }
+
+    #[test]
+    fn test_devkit_pins() {
+        let files = render(Chip::Esp32c6, &["devkit"]);
+
+        assert!(files["src/lib.rs"].contains("pub mod pins;"));
+        assert!(files["src/pins.rs"].contains("pub led: GpioPin<8>,"));
+        assert!(files["src/pins.rs"].contains("tx: $peripherals.GPIO16,"));
+        assert!(files["src/bin/main.rs"].contains("my_project::pins!(peripherals)"));
+    }
+
+    #[test]
+    fn test_audio_out() {
+        let main = |chip| render(chip, &["audio-out"])["src/bin/main.rs"].clone();
+
+        let esp32 = main(Chip::Esp32);
+        assert!(esp32.contains("let dma_channel = peripherals.DMA_I2S0;"));
+        assert!(esp32.contains(".with_bclk(peripherals.GPIO26)"));
+
+        let esp32c6 = main(Chip::Esp32c6);
+        assert!(esp32c6.contains("let dma_channel = peripherals.DMA_CH0;"));
+        assert!(esp32c6.contains(".with_dout(peripherals.GPIO22)"));
+        assert!(!esp32c6.contains("Hello world!"));
+    }
+
+    #[test]
+    fn test_hw_crypto() {
+        let crypto = |chip| render(chip, &["hw-crypto"])["src/crypto.rs"].clone();
+
+        // The ESP32-C2 only has the SHA accelerator
+        let esp32c2 = crypto(Chip::Esp32c2);
+        assert!(esp32c2.contains("pub fn sha256_hardware("));
+        assert!(!esp32c2.contains("pub fn aes128_hardware("));
+        assert!(!esp32c2.contains("pub fn rsa512_mod_exp_hardware("));
+
+        let esp32c3 = crypto(Chip::Esp32c3);
+        assert!(esp32c3.contains("pub fn aes128_hardware("));
+        assert!(esp32c3.contains("pub fn rsa512_mod_exp_hardware("));
+    }
+
+    #[test]
+    fn test_render_template_is_deterministic() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        let variables = template_variables("test", Chip::Esp32c3);
+
+        let files = render_template(
+            &Templates::embedded(),
+            Chip::Esp32c3,
+            &options(&["alloc", "wifi", "embassy"]),
+            &variables,
+        );
+        let reordered = render_template(
+            &Templates::embedded(),
+            Chip::Esp32c3,
+            &options(&["embassy", "wifi", "alloc", "wifi"]),
+            &variables,
+        );
+        assert_eq!(files, reordered);
+
+        assert!(files.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(files.iter().all(|(_, contents)| !contents.contains('\r')));
+
+        // Templates checked out with CRLF line endings render the same
+        let template = "#IF option(\"a\")\nline a\n#ENDIF\nline b\n";
+        assert_eq!(
+            process_file(&template.replace('\n', "\r\n"), &options(&["a"]), &[]),
+            process_file(template, &options(&["a"]), &[])
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let chip = Chip::Esp32c3;
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        let paths = |files: &BTreeMap<String, String>| {
+            files
+                .keys()
+                .filter(|path| path.starts_with("examples/"))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        assert!(paths(&render(chip, &[])).is_empty());
+        assert_eq!(paths(&render(chip, &["examples"])), ["examples/blinky.rs"]);
+
+        let files = render(chip, &["alloc", "wifi", "examples"]);
+        assert_eq!(
+            paths(&files),
+            ["examples/blinky.rs", "examples/wifi-scan.rs"]
+        );
+        let blinky = &files["examples/blinky.rs"];
+        assert!(blinky.contains("Output::new(peripherals.GPIO8, Level::Low)"));
+        assert!(!blinky.contains("//REPLACE") && !blinky.contains("//IF"));
+
+        assert!(process_options(chip, &options(&["esp-idf-std", "examples"])).is_err());
+    }
+
+    #[test]
+    fn test_heartbeat() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        // The ESP32 DevKit's LED is a plain one, the others are RGB LEDs
+        assert!(capability::template_options(Chip::Esp32).contains(&"has-led".to_string()));
+        assert!(!capability::template_options(Chip::Esp32c3).contains(&"has-led".to_string()));
+
+        let files = render(Chip::Esp32, &["heartbeat"]);
+        assert!(files["src/heartbeat.rs"].contains("self.led.toggle()"));
+        assert!(files["src/bin/main.rs"]
+            .contains("let led = Output::new(peripherals.GPIO2, Level::Low);"));
+
+        let files = render(Chip::Esp32, &["heartbeat", "embassy", "devkit"]);
+        let main = &files["src/bin/async_main.rs"];
+        assert!(main.contains("let led = Output::new(pins.led, Level::Low);"));
+        assert!(main.contains("spawner.spawn(heartbeat(Heartbeat::new(led)))"));
+
+        let files = render(Chip::Esp32c3, &["heartbeat"]);
+        assert!(files["src/heartbeat.rs"].contains("info!(\"Heartbeat #{}\", self.beats)"));
+        assert!(files["src/bin/main.rs"].contains("Heartbeat::new();"));
+
+        assert!(process_options(
+            Chip::Esp32s3,
+            &options(&["heartbeat", "heap-psram", "alloc", "ui-slint"])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_gitattributes() {
+        let file = |selected: &[&str], name: &str| render(Chip::Esp32s3, selected)[name].clone();
+
+        let attributes = file(&[], ".gitattributes");
+        assert!(attributes.contains("*.rs text eol=lf"));
+        assert!(attributes.contains("Cargo.lock linguist-generated=true"));
+        assert!(!attributes.contains("filter=lfs"));
+
+        let attributes = file(
+            &["alloc", "heap-psram", "ui-slint", "git-lfs"],
+            ".gitattributes",
+        );
+        assert!(attributes.contains("*.png filter=lfs diff=lfs merge=lfs -text"));
+        assert!(!attributes.contains("*.wav"));
+
+        assert!(!file(&["ci"], ".github/workflows/rust_ci.yml").contains("lfs: true"));
+        assert!(file(&["ci", "git-lfs"], ".github/workflows/rust_ci.yml").contains("lfs: true"));
+    }
+
+    #[test]
+    fn test_options_json() {
+        let psram = find_option_item("heap-psram", OPTIONS).unwrap();
+        assert_eq!(psram, find_option_item("heap-psram", OPTIONS).unwrap());
+        assert_eq!(
+            serde_json::to_value(psram).unwrap(),
+            serde_json::json!({
+                "type": "option",
+                "name": "heap-psram",
+                "display_name": psram.title(),
+                "enables": ["alloc"],
+                "disables": [],
+                "capabilities": ["psram"],
+                "min_chip_revision": { "esp32": 300 },
+                "requires_unstable_hal": true,
+                "requires_nightly": false,
+                "experimental": false,
+            })
+        );
+
+        let json = config::to_json();
+        assert_eq!(json["version"], config::OPTIONS_FORMAT_VERSION);
+        assert_eq!(json["options"].as_array().unwrap().len(), OPTIONS.len());
+        assert_eq!(json["options"][0]["type"], "category");
+        assert!(json["capabilities"]["esp32c6"]
+            .as_array()
+            .unwrap()
+            .contains(&"flip-link".into()));
+    }
+
+    #[test]
+    fn test_snippets() {
+        let templates = Templates::embedded();
+
+        let snippets = Snippets::collect(&templates, &["ident".to_string()]);
+        assert_eq!(
+            snippets.expand("fn main() {\n    //ANCHOR init\n    loop {}\n}\n"),
+            "fn main() {\n    info!(\"Device name: {}\", ident::device_name().as_str());\n\n    loop {}\n}\n"
+        );
+        let snippets = Snippets::collect(&templates, &[]);
+        assert_eq!(snippets.expand("a\n//ANCHOR init\nb"), "a\nb");
+
+        let files = render(Chip::Esp32c3, &["ident"]);
+        assert!(files["src/lib.rs"].contains("pub mod ident;"));
+        assert!(files["src/bin/main.rs"].contains("ident::device_name()"));
+        assert!(files.iter().all(|(path, contents)| {
+            !path.starts_with(snippets::SNIPPETS_DIR) && !contents.contains("ANCHOR")
+        }));
+    }
+
+    #[test]
+    fn test_manufacturing() {
+        let files = render(Chip::Esp32s3, &["manufacturing"]);
+
+        let factory_image = &files["scripts/manufacturing/factory-image.sh"];
+        assert!(factory_image.contains("CHIP=\"esp32s3\""));
+        assert!(factory_image.contains("TARGET=\"xtensa-esp32s3-none-elf\""));
+        assert!(factory_image.contains("NAME=\"my-project\""));
+
+        // Serial numbers match the device names of the `ident` module
+        let flash_device = &files["scripts/manufacturing/flash-device.sh"];
+        assert!(flash_device.contains("SERIAL_PREFIX=\"${SERIAL_PREFIX:-my-project}\""));
+        assert!(files[".gitignore"].contains("manufacturing/devices/"));
+        assert!(files["docs/manufacturing.md"].starts_with("# Manufacturing"));
+    }
+
+    #[test]
+    fn test_provisioning() {
+        let files = render(Chip::Esp32s3, &["manufacturing", "provisioning"]);
+
+        assert!(files["Cargo.toml"]
+            .lines()
+            .any(|line| line.starts_with("esp-storage") && line.contains(r#"["esp32s3"]"#)));
+        assert!(files["src/lib.rs"].contains("pub mod provisioning;"));
+        assert!(files["src/bin/main.rs"].contains("use my_project::provisioning::Provisioning;"));
+        assert!(files["src/bin/main.rs"].contains("match Provisioning::read() {"));
+        assert!(files["src/provisioning.rs"].contains("pub const OFFSET: u32 = 0x9000;"));
+        assert!(files["manufacturing/provisioning.csv"]
+            .starts_with("serial,device_id,api_key\nmy-project-"));
+        assert!(files["scripts/manufacturing/provision.py"].contains("manufacturing/devices"));
+
+        let files = render(Chip::Esp32s3, &["manufacturing"]);
+        assert!(!files.keys().any(|path| path.contains("provision")));
+        assert!(files
+            .values()
+            .all(|contents| !contents.contains("esp-storage")));
+    }
+
+    #[test]
+    fn test_c_interop() {
+        let files = render(Chip::Esp32c3, &["c-interop"]);
+        let build = &files["build.rs"];
+        assert!(build.contains(r#".compiler("riscv32-esp-elf-gcc")"#));
+        assert!(!build.contains("-mlongcalls"));
+        assert!(files["Cargo.toml"].contains("[build-dependencies]\ncc = "));
+        assert!(files["scripts/bindgen.sh"].contains("--target=riscv32-unknown-elf"));
+        assert!(files["src/lib.rs"].contains("pub mod bridge;"));
+        assert!(files["src/bin/main.rs"].contains("bridge::checksum("));
+        assert!(files["csrc/bridge.h"].contains("uint16_t bridge_checksum("));
+        assert!(files["src/bridge/bindings.rs"].contains("pub fn bridge_checksum("));
+
+        let files = render(Chip::Esp32s3, &["c-interop"]);
+        let build = &files["build.rs"];
+        assert!(build.contains(r#".compiler("xtensa-esp32s3-elf-gcc")"#));
+        assert!(build.contains(r#".flag("-mlongcalls")"#));
+        assert!(files["scripts/bindgen.sh"].contains("-mcpu=esp32s3"));
+    }
+
+    #[test]
+    fn test_c_logging() {
+        let files = render(Chip::Esp32c3, &["c-interop", "c-logging"]);
+        assert!(files["build.rs"].contains(r#".file("csrc/log.c")"#));
+        assert!(files["csrc/bridge.c"].contains("ESP_LOGI(TAG, "));
+        assert!(files["src/bridge.rs"].contains("mod logging;"));
+        let logging = &files["src/bridge/logging.rs"];
+        assert!(logging.contains("log::log!(level, "));
+        assert!(!logging.contains("defmt"));
+
+        let files = render(Chip::Esp32c3, &["c-interop", "c-logging", "probe-rs"]);
+        let logging = &files["src/bridge/logging.rs"];
+        assert!(logging.contains("use defmt::"));
+        assert!(!logging.contains("log::log!"));
+
+        let files = render(Chip::Esp32c3, &["c-interop"]);
+        assert!(!files.contains_key("csrc/log.c"));
+        assert!(!files["csrc/bridge.c"].contains("log.h"));
+        assert!(!files["build.rs"].contains("log.c"));
+    }
+
+    #[test]
+    fn test_posix_only_scripts() {
+        // Every option adding scripts to run on the host
+        let options = [
+            "firmware-manifest",
+            "docker-build",
+            "manufacturing",
+            "provisioning",
+            "c-interop",
+            "dev-container",
+        ]
+        .map(String::from);
+        for chip in [Chip::Esp32c3, Chip::Esp32s3] {
+            let files = render_template(
+                &Templates::embedded(),
+                chip,
+                &options,
+                &template_variables("my-project", chip),
+            );
+            assert!(files.iter().any(|(path, _)| path == "scripts/build.sh"));
+            assert!(files
+                .iter()
+                .any(|(path, _)| path == "scripts/manufacturing/flash-device.ps1"));
+            assert_eq!(posix_only_scripts(&files), Vec::<&str>::new());
+
+            let script = |name: &str| {
+                files
+                    .iter()
+                    .find(|(path, _)| path == name)
+                    .map(|(_, contents)| contents.as_str())
+                    .unwrap()
+            };
+            assert!(script("build-in-docker.ps1").contains(&format!("$Chip = \"{chip}\"")));
+            assert!(script("scripts/firmware-manifest.ps1")
+                .contains(&format!("$Target = \"{}\"", chip.target())));
+        }
+
+        let files = vec![
+            ("scripts/a.sh".to_string(), String::new()),
+            ("scripts/b.sh".to_string(), String::new()),
+            ("scripts/b.ps1".to_string(), String::new()),
+        ];
+        assert_eq!(posix_only_scripts(&files), vec!["scripts/a.sh"]);
+    }
+
+    #[test]
+    fn test_unstable_hal() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            solver::violations(Chip::Esp32c3, &options(&["embassy"])),
+            [Violation::MissingRequirement {
+                option: "embassy".to_string(),
+                requirement: "unstable-hal".to_string()
+            }]
+        );
+        // `std` projects do not use `esp-hal`
+        assert!(
+            process_options(Chip::Esp32c3, &options(&["esp-idf-std", "alloc", "wifi"])).is_ok()
+        );
+        assert!(
+            process_options(Chip::Esp32c3, &options(&["esp-idf-std", "unstable-hal"])).is_err()
+        );
+        assert_eq!(
+            solver::implied(&options(&["esp-idf-std", "wifi"])),
+            options(&["esp-idf-std", "wifi", "alloc"])
+        );
+
+        assert!(solver::needs_unstable_hal("channels"));
+        assert!(!solver::needs_unstable_hal("alloc"));
+        assert!(!solver::needs_unstable_hal("unstable-hal"));
+
+        let file = |selected: &[&str], path: &str| render(Chip::Esp32c3, selected)[path].clone();
+        assert!(!file(&[], "Cargo.toml").contains("\"unstable\""));
+        assert!(!file(&[], "src/bin/main.rs").contains("Delay"));
+        assert!(file(&["unstable-hal"], "Cargo.toml").contains("\"unstable\""));
+        assert!(file(&["unstable-hal"], "src/bin/main.rs").contains("delay.delay_millis(500)"));
+    }
+
+    #[test]
+    fn test_requiring_nightly() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        assert!(solver::requiring_nightly(&options(&["alloc", "wifi"])).is_empty());
+        assert_eq!(
+            solver::requiring_nightly(&options(&["release-small", "alloc", "esp-idf-std"])),
+            ["release-small", "esp-idf-std"]
+        );
+        // `stack-usage` implies `embassy`, which builds on stable
+        assert_eq!(
+            solver::requiring_nightly(&options(&["stack-usage"])),
+            ["stack-usage"]
+        );
+    }
+
+    #[test]
+    fn test_panic_policy() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        let selected = ["panic-policy", "unstable-hal"];
+        let files = render(Chip::Esp32c3, &selected);
+
+        let manifest = &files["Cargo.toml"];
+        assert!(manifest.contains("\"custom-halt\""));
+        assert!(manifest.contains(r#"features = ["build"]"#));
+        assert!(files["build.rs"].contains("esp_config::generate_config("));
+        assert!(files["src/bin/main.rs"].contains("info!(\"Panic policy: {}\", panic::POLICY);"));
+        assert!(files["src/panic.rs"].contains("#[cfg(panic_policy_deep_sleep)]"));
+
+        // `esp-hal` does not implement deep sleep for the ESP32-H2
+        let files = render(Chip::Esp32h2, &selected);
+        assert!(!files["src/panic.rs"].contains("deep_sleep"));
+        assert!(!files["build.rs"].contains("deep-sleep"));
+
+        assert!(!render(Chip::Esp32c3, &["unstable-hal"]).contains_key("src/panic.rs"));
+        assert!(
+            process_options(Chip::Esp32c3, &options(&["esp-idf-std", "panic-policy"])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_reset_reason() {
+        let files = |chip: Chip| render(chip, &["embassy", "reset-reason", "unstable-hal"]);
+
+        let files_c3 = files(Chip::Esp32c3);
+        assert!(
+            files_c3["src/bin/async_main.rs"].contains("let reset_reason = ResetReason::read();")
+        );
+        assert!(files_c3["src/lib.rs"].contains("pub mod reset_reason;"));
+        assert!(files_c3["src/reset_reason.rs"].contains("Self::Panic => \"panic\","));
+        assert!(files_c3["Cargo.toml"].contains("\"custom-pre-backtrace\""));
+
+        // Without RTC fast memory, panics cannot be told apart
+        let files_c2 = files(Chip::Esp32c2);
+        assert!(!files_c2["src/reset_reason.rs"].contains("Panic"));
+        assert!(!files_c2["Cargo.toml"].contains("custom-pre-backtrace"));
+    }
+
+    #[test]
+    fn test_interrupts() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        let main_and_interrupts = |chip, selected: &[&str]| {
+            let mut files = render(chip, selected);
+            (
+                files.remove("src/bin/main.rs").unwrap(),
+                files.remove("src/interrupts.rs"),
+            )
+        };
+
+        let (main, interrupts) =
+            main_and_interrupts(Chip::Esp32c3, &["interrupts", "unstable-hal"]);
+        assert!(interrupts.unwrap().contains("fn on_gpio()"));
+        assert!(main.contains("peripherals.GPIO9,"));
+        assert!(main.contains("SystemTimer::new(peripherals.SYSTIMER).alarm0"));
+        assert!(main.contains("interrupts::presses()"));
+
+        // The button is taken from the pins of the DevKit, TIMG0 is left to
+        // esp-wifi
+        let (main, _) = main_and_interrupts(
+            Chip::Esp32,
+            &["interrupts", "devkit", "wifi", "alloc", "unstable-hal"],
+        );
+        assert!(main.contains("pins.button,"));
+        assert!(main.contains("TimerGroup::new(peripherals.TIMG1).timer0"));
+        assert!(main.contains("TimerGroup::new(peripherals.TIMG0)"));
+
+        let (_, interrupts) = main_and_interrupts(Chip::Esp32c3, &["unstable-hal"]);
+        assert!(interrupts.is_none());
+        assert!(process_options(
+            Chip::Esp32c3,
+            &options(&["interrupts", "embassy", "unstable-hal"])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sync() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        assert!(process_options(Chip::Esp32s3, &options(&["sync", "unstable-hal"])).is_ok());
+        assert!(process_options(Chip::Esp32c6, &options(&["sync", "unstable-hal"])).is_err());
+
+        for (selected, main) in [
+            (&["sync", "unstable-hal"][..], "src/bin/main.rs"),
+            (
+                &["sync", "embassy", "unstable-hal"][..],
+                "src/bin/async_main.rs",
+            ),
+        ] {
+            let files = render(Chip::Esp32, selected);
+            assert!(files["src/lib.rs"].contains("pub mod sync;"));
+            assert!(files["src/sync.rs"].contains("CriticalSectionRawMutex"));
+            assert!(files[main].contains("sync::start_app_core(peripherals.CPU_CTRL);"));
+            assert!(files[main].contains("sync::record();"));
+            assert!(files["Cargo.toml"].contains("embassy-sync"));
+        }
+    }
+
+    #[test]
+    fn test_map_file() {
+        for (chip, argument) in [(Chip::Esp32c3, "-Map="), (Chip::Esp32s3, "-Wl,-Map=")] {
+            let files = render(chip, &["map-file"]);
+            assert!(files["build.rs"].contains(&format!("rustc-link-arg-bins={argument}")));
+            assert!(files["build.rs"].contains("\"my-project.map\""));
+            assert!(files["scripts/map-summary.py"]
+                .contains(&format!("target/{}/release/my-project.map", chip.target())));
+        }
+
+        assert!(!render(Chip::Esp32c3, &[]).contains_key("scripts/map-summary.py"));
+    }
+
+    #[test]
+    fn test_app_config() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        for (selected, main, interval) in [
+            (
+                &["app-config", "unstable-hal"][..],
+                "src/bin/main.rs",
+                "delay.delay_millis(cfg::INTERVAL_MS);",
+            ),
+            (
+                &["app-config", "embassy", "panic-policy", "unstable-hal"][..],
+                "src/bin/async_main.rs",
+                "Duration::from_millis(cfg::INTERVAL_MS.into())",
+            ),
+        ] {
+            let files = render(Chip::Esp32c6, selected);
+            assert!(files["src/lib.rs"].contains("pub mod cfg;"));
+            assert!(files["src/cfg.rs"].contains("\"APP_CONFIG_INTERVAL_MS\""));
+            assert!(files[main].contains(interval));
+            assert!(files[".cargo/config.toml"].contains("APP_CONFIG_ENDPOINT_URL"));
+
+            // esp-config rejects variables it is not given, so every setting
+            // is declared in a single call
+            let build = &files["build.rs"];
+            assert_eq!(build.matches("generate_config(").count(), 1);
+            assert!(build.contains("\"interval-ms\""));
+            assert_eq!(
+                build.contains("\"panic-policy\""),
+                selected.contains(&"panic-policy")
+            );
+        }
+
+        assert!(process_options(Chip::Esp32c6, &options(&["esp-idf-std", "app-config"])).is_err());
+    }
+
+    #[test]
+    fn test_hal_facade() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        for (chip, selected, main) in [
+            (Chip::Esp32c3, &["hal-facade"][..], "src/bin/main.rs"),
+            (
+                Chip::Esp32c6,
+                &["alloc", "hal-facade", "unstable-hal", "wifi"][..],
+                "src/bin/main.rs",
+            ),
+            (
+                Chip::Esp32,
+                &["alloc", "embassy", "hal-facade", "unstable-hal", "wifi"][..],
+                "src/bin/async_main.rs",
+            ),
+        ] {
+            let files = render(chip, selected);
+            assert!(files["src/lib.rs"].contains("pub mod hal;"));
+            assert!(files[main].contains("hal::init(config)"));
+            // Every path of esp-hal in `main` goes through the facade
+            assert!(files[main]
+                .lines()
+                .filter(|line| !line.trim_start().starts_with("//"))
+                .all(|line| !line.contains("esp_hal::")));
+            assert!(process_options(chip, &options(selected)).is_ok());
+
+            let hal = &files["src/hal.rs"];
+            for item in ["Rng", "TimerGroup", "Delay"] {
+                assert_eq!(
+                    hal.contains(item),
+                    selected.contains(&"wifi"),
+                    "{item} in {selected:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_wifi_sta() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        let implied = solver::implied(&options(&["wifi-sta"]));
+        for option in ["wifi", "embassy", "alloc"] {
+            assert!(implied.iter().any(|o| o == option), "{option}");
+        }
+
+        for selected in [
+            &["alloc", "embassy", "unstable-hal", "wifi", "wifi-sta"][..],
+            &[
+                "alloc",
+                "ble",
+                "beacon",
+                "embassy",
+                "unstable-hal",
+                "wifi",
+                "wifi-sta",
+            ][..],
+        ] {
+            assert!(process_options(Chip::Esp32c6, &options(selected)).is_ok());
+            let files = render(Chip::Esp32c6, selected);
+
+            assert!(files["src/lib.rs"].contains("pub mod wifi;"));
+            assert!(files["src/wifi.rs"].contains("pub static IS_CONNECTED"));
+            let main = &files["src/bin/async_main.rs"];
+            assert!(main.contains("spawner.spawn(wifi::connection(controller))"));
+            assert!(main.contains("IS_CONNECTED.receiver()"));
+            assert!(files["Cargo.toml"].contains("embassy-sync"));
+        }
+
+        let files = render(Chip::Esp32c6, &["alloc", "embassy", "unstable-hal", "wifi"]);
+        assert!(!files.contains_key("src/wifi.rs"));
+    }
+
+    #[test]
+    fn test_net_tools() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        let implied = solver::implied(&options(&["net-tools"]));
+        for option in ["wifi-sta", "wifi", "embassy"] {
+            assert!(implied.iter().any(|o| o == option), "{option}");
+        }
+
+        for (selected, net_tools) in [
+            (
+                &["alloc", "embassy", "unstable-hal", "wifi", "wifi-sta"][..],
+                false,
+            ),
+            (
+                &[
+                    "alloc",
+                    "embassy",
+                    "net-tools",
+                    "unstable-hal",
+                    "wifi",
+                    "wifi-sta",
+                ][..],
+                true,
+            ),
+        ] {
+            assert!(process_options(Chip::Esp32c6, &options(selected)).is_ok());
+            let files = render(Chip::Esp32c6, selected);
+
+            assert_eq!(files.contains_key("src/net.rs"), net_tools);
+            assert_eq!(files["src/lib.rs"].contains("pub mod net;"), net_tools);
+            let main = &files["src/bin/async_main.rs"];
+            assert_eq!(main.contains("net::diagnostics(stack)"), net_tools);
+            assert_eq!(main.contains("let (_device, controller)"), !net_tools);
+            assert_eq!(files["Cargo.toml"].contains("\"raw\""), net_tools);
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        for (selected, net_tools) in [
+            (
+                &[
+                    "alloc",
+                    "embassy",
+                    "metrics",
+                    "unstable-hal",
+                    "wifi",
+                    "wifi-sta",
+                ][..],
+                false,
+            ),
+            (
+                &[
+                    "alloc",
+                    "embassy",
+                    "metrics",
+                    "net-tools",
+                    "unstable-hal",
+                    "wifi",
+                    "wifi-sta",
+                ][..],
+                true,
+            ),
+        ] {
+            assert!(process_options(Chip::Esp32c6, &options(selected)).is_ok());
+            let files = render(Chip::Esp32c6, selected);
+
+            assert!(files.contains_key("src/metrics.rs"));
+            assert!(files["src/lib.rs"].contains("pub mod metrics;"));
+            assert!(files["src/wifi.rs"].contains("metrics::WIFI_DISCONNECTS.increment()"));
+            let main = &files["src/bin/async_main.rs"];
+            assert!(main.contains("spawner.spawn(metrics::serve(stack))"));
+            // One stack, shared with the diagnostics of `net-tools`
+            assert_eq!(main.matches("embassy_net::new(").count(), 1);
+            assert_eq!(main.contains("net::diagnostics(stack)"), net_tools);
+        }
+    }
+
+    #[test]
+    fn test_remote_logging() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        let selected = [
+            "alloc",
+            "embassy",
+            "remote-logging",
+            "unstable-hal",
+            "wifi",
+            "wifi-sta",
+        ];
+        assert!(process_options(Chip::Esp32c3, &options(&selected)).is_ok());
+        let files = render(Chip::Esp32c3, &selected);
+
+        assert!(files["src/lib.rs"].contains("pub mod remote_log;"));
+        let main = &files["src/bin/async_main.rs"];
+        // The remote logger replaces the one of esp-println, which prints too
+        assert!(main.contains("remote_log::init();"));
+        assert!(!main.contains("init_logger_from_env"));
+        assert!(main.contains("spawner.spawn(remote_log::forward(stack))"));
+        assert!(files["src/remote_log.rs"].contains("REMOTE_LOG_HOST"));
+
+        // defmt is decoded on the host, it cannot be forwarded as text
+        let mut with_probe_rs = options(&selected);
+        with_probe_rs.push("probe-rs".to_string());
+        assert!(process_options(Chip::Esp32c3, &with_probe_rs).is_err());
+    }
+
+    #[test]
+    fn test_ram_placement() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        for (chip, rtc_ram) in [(Chip::Esp32c3, true), (Chip::Esp32c2, false)] {
+            for (selected, main) in [
+                (&["ram-placement", "unstable-hal"][..], "src/bin/main.rs"),
+                (
+                    &["embassy", "ram-placement", "unstable-hal"][..],
+                    "src/bin/async_main.rs",
+                ),
+            ] {
+                assert!(process_options(chip, &options(selected)).is_ok());
+                let files = render(chip, selected);
+
+                assert!(files["src/lib.rs"].contains("pub mod placement;"));
+                let placement = &files["src/placement.rs"];
+                assert!(placement.contains("#[ram]"));
+                assert_eq!(placement.contains("#[ram(rtc_fast, persistent)]"), rtc_ram);
+                let main = &files[main];
+                assert!(main.contains("dma_buffer.fill(&placement::PATTERN)"));
+                assert_eq!(main.contains("placement::count_boot()"), rtc_ram);
+            }
+        }
+
+        assert!(process_options(Chip::Esp32c3, &options(&["ram-placement"])).is_err());
+    }
+
+    #[test]
+    fn test_experimental_options() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        assert!(find_option_item("esp-idf-std", OPTIONS)
+            .unwrap()
+            .is_experimental());
+        assert_eq!(
+            solver::experimental(&options(&["esp-idf-std", "alloc", "wifi"])),
+            [Violation::Experimental {
+                option: "esp-idf-std".to_string()
+            }]
+        );
+        assert!(solver::experimental(&options(&["alloc", "wifi"])).is_empty());
+
+        let names = |items: &[GeneratorOptionItem]| {
+            items
+                .iter()
+                .flat_map(|item| item.options())
+                .collect::<Vec<_>>()
+        };
+
+        // Hidden, along with the category left empty
+        let hidden = config::visible_options(false);
+        assert!(!names(hidden).contains(&"esp-idf-std".to_string()));
+        assert!(!hidden.iter().any(|item| item.name() == "flavor"));
+        assert!(names(hidden).contains(&"alloc".to_string()));
+
+        // In a section of their own
+        let shown = config::visible_options(true);
+        let Some(GeneratorOptionItem::Category(section)) = shown.last() else {
+            panic!("no experimental section");
+        };
+        assert_eq!(section.name, "experimental");
+        assert_eq!(section.options(), ["esp-idf-std"]);
+        assert_eq!(names(shown).len(), names(OPTIONS).len());
+    }
+
+    #[test]
+    fn test_troubleshooting() {
+        let docs = |chip: Chip, selected: &[&str]| {
+            render(chip, selected)["docs/troubleshooting.md"].clone()
+        };
+
+        let plain = docs(Chip::Esp32c3, &[]);
+        assert!(plain.starts_with("# Troubleshooting my-project\n"));
+        assert!(plain.contains("`ESP_LOG`"));
+        assert!(!plain.contains("## Xtensa toolchain"));
+        assert!(!plain.contains("## Wi-Fi"));
+        assert!(!plain.contains("## Embassy"));
+
+        let wifi = docs(
+            Chip::Esp32s3,
+            &[
+                "alloc",
+                "wifi",
+                "embassy",
+                "probe-rs",
+                "unstable-hal",
+                "heap-size=96",
+            ],
+        );
+        assert!(wifi.contains("The `esp32s3` is an Xtensa chip"));
+        assert!(wifi.contains("which is 96 KiB in this project"));
+        assert!(wifi.contains("with the `heap-dram2` option"));
+        assert!(wifi.contains("## Embassy"));
+        assert!(wifi.contains("`DEFMT_LOG`"));
+        assert!(!wifi.contains("`ESP_LOG`"));
+
+        let std = docs(Chip::Esp32c6, &["esp-idf-std", "wifi"]);
+        assert!(std.contains("## ESP-IDF"));
+        assert!(std.contains("`CONFIG_LOG_DEFAULT_LEVEL`"));
+        assert!(!std.contains("## Wi-Fi"));
+    }
 }
@@ -0,0 +1,197 @@
+//! Migration notes between template versions.
+//!
+//! Template changes which generated projects have to apply by hand are
+//! described in `migrations.toml`, which is embedded at build time. The notes
+//! for the versions between the one a project was generated with and this one
+//! are printed on request, and when verifying the project.
+
+use std::{error::Error, path::Path};
+
+use serde::Deserialize;
+
+use crate::{cargo::CargoToml, check::Version};
+
+/// The supported version of the `migrations.toml` format.
+const MIGRATIONS_VERSION: u32 = 1;
+
+const MIGRATIONS: &str = include_str!("../migrations.toml");
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Migrations {
+    version: u32,
+    #[serde(default)]
+    note: Vec<Note>,
+}
+
+/// A change to apply when updating past the `after` release.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Note {
+    after: String,
+    /// The options which must all be selected for the note to apply.
+    #[serde(default)]
+    options: Vec<String>,
+    pub title: String,
+    pub details: String,
+}
+
+impl Migrations {
+    /// Parse and validate the contents of a `migrations.toml` file.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let migrations: Self =
+            toml_edit::de::from_str(contents).map_err(|error| error.to_string())?;
+
+        if migrations.version != MIGRATIONS_VERSION {
+            return Err(format!(
+                "Unsupported migrations version {}, expected {MIGRATIONS_VERSION}",
+                migrations.version
+            ));
+        }
+
+        if let Some(note) = migrations
+            .note
+            .iter()
+            .find(|note| Version::parse(&note.after).is_none())
+        {
+            return Err(format!(
+                "Invalid version '{}' for '{}'",
+                note.after, note.title
+            ));
+        }
+
+        Ok(migrations)
+    }
+
+    pub fn embedded() -> Self {
+        Self::parse(MIGRATIONS).expect("the embedded migrations.toml is invalid")
+    }
+
+    /// The notes for updating a project from `from` to `to`. Without the
+    /// project's options, the notes for all options are returned.
+    pub fn between(&self, from: Version, to: Version, options: Option<&[String]>) -> Vec<&Note> {
+        self.note
+            .iter()
+            .filter(|note| {
+                // Validated when parsing
+                let after = Version::parse(&note.after).unwrap();
+                from <= after && after < to
+            })
+            .filter(|note| {
+                options.is_none_or(|options| note.options.iter().all(|o| options.contains(o)))
+            })
+            .collect()
+    }
+}
+
+/// Print the notes for updating a project generated by version `from` to
+/// this version.
+pub fn print(from: &str, options: Option<&[String]>) -> Result<(), Box<dyn Error>> {
+    let current = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+    let from = Version::parse(from).ok_or(format!("Invalid version '{from}'"))?;
+
+    let migrations = Migrations::embedded();
+    let notes = migrations.between(from, current, options);
+    if notes.is_empty() {
+        log::info!("No migration notes between esp-generate {from} and {current}");
+        return Ok(());
+    }
+
+    log::warn!(
+        "{} migration note(s) between esp-generate {from} and {current}:",
+        notes.len()
+    );
+    for note in notes {
+        println!("\n## {}\n\n{}", note.title, note.details.trim_end());
+    }
+
+    Ok(())
+}
+
+/// Print the notes relevant to a generated project. The version and options
+/// are read from the manifest, `from` is needed for projects which predate
+/// provenance tracking, for which the notes of all options are printed.
+pub fn print_for_project(project_dir: &Path, from: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let manifest = CargoToml::load(project_dir)?;
+    let metadata = manifest.generator_metadata();
+
+    let options = metadata
+        .and_then(|metadata| metadata.get("options"))
+        .and_then(|item| item.as_array())
+        .map(|options| {
+            options
+                .iter()
+                .filter_map(|option| option.as_str().map(String::from))
+                .collect::<Vec<_>>()
+        });
+    let from = match from {
+        Some(from) => from,
+        None => metadata
+            .and_then(|metadata| metadata.get("version"))
+            .and_then(|item| item.as_str())
+            .ok_or(format!(
+                "{} does not record the esp-generate version it was generated with, pass it with `--from`",
+                project_dir.display()
+            ))?,
+    };
+
+    print(from, options.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::Version;
+
+    #[test]
+    fn test_migration_notes() {
+        // The embedded notes must be valid
+        Migrations::embedded();
+
+        let migrations = Migrations::parse(
+            r#"
+version = 1
+
+[[note]]
+after   = "0.2.2"
+title   = "A"
+details = ""
+
+[[note]]
+after   = "0.3.0"
+options = ["wifi", "ble"]
+title   = "B"
+details = ""
+"#,
+        )
+        .unwrap();
+        let titles = |from, to, options: Option<&[String]>| {
+            migrations
+                .between(
+                    Version::parse(from).unwrap(),
+                    Version::parse(to).unwrap(),
+                    options,
+                )
+                .iter()
+                .map(|note| note.title.as_str())
+                .collect::<Vec<_>>()
+        };
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(titles("0.2.0", "0.4.0", None), ["A", "B"]);
+        assert_eq!(titles("0.2.2", "0.3.0", None), ["A"]);
+        assert_eq!(titles("0.2.3", "0.4.0", None), ["B"]);
+        assert!(titles("0.3.0", "0.3.0", None).is_empty());
+        assert_eq!(titles("0.2.0", "0.4.0", Some(&options(&["wifi"]))), ["A"]);
+        assert_eq!(
+            titles("0.2.0", "0.4.0", Some(&options(&["ble", "wifi", "alloc"]))),
+            ["A", "B"]
+        );
+
+        assert!(Migrations::parse("version = 2\n").is_err());
+        assert!(Migrations::parse(
+            "version = 1\n[[note]]\nafter = \"next\"\ntitle = \"\"\ndetails = \"\"\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Checking the dependencies of a generated project against those of the
+//! templates.
+//!
+//! The dependency versions of the embedded templates are known to work
+//! together, so a project using older versions of these crates can be
+//! updated to this set as a whole.
+
+use std::{error::Error, path::Path};
+
+use crate::{cargo::CargoToml, check::Version, templates::Templates};
+
+/// A dependency of the project older than the one of the templates.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Outdated {
+    pub name: String,
+    pub requirement: String,
+    pub latest: String,
+}
+
+/// The dependencies of a `Cargo.toml` template and their version
+/// requirements, across all options.
+pub fn template_dependencies(template: &str) -> Vec<(String, String)> {
+    let mut dependencies = Vec::new();
+    let mut in_dependencies = false;
+
+    for line in template.lines() {
+        // Lines only rendered for some options are commented out with `#+`
+        let line = line.trim();
+        let line = line.strip_prefix("#+").unwrap_or(line);
+        if line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            in_dependencies = matches!(line, "[dependencies]" | "[build-dependencies]");
+            continue;
+        }
+        if !in_dependencies {
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || "-_".contains(c))
+        {
+            continue;
+        }
+        let Some(version) = requirement_of(value) else {
+            continue;
+        };
+
+        if !dependencies.iter().any(|(n, _)| n == name) {
+            dependencies.push((name.to_string(), version));
+        }
+    }
+
+    dependencies
+}
+
+/// The version requirement in a `"0.1"` or `{ version = "0.1", ... }`
+/// dependency value, if it has one.
+fn requirement_of(value: &str) -> Option<String> {
+    let value = value.trim();
+    let value = if value.starts_with('{') {
+        let (_, rest) = value.split_once("version")?;
+        rest.trim_start().strip_prefix('=')?.trim_start()
+    } else {
+        value
+    };
+
+    let value = value.strip_prefix('"')?;
+    let (version, _) = value.split_once('"')?;
+    Some(version.to_string())
+}
+
+/// The version a requirement like `0.23.1`, `^0.4` or `=1.2.0` starts from.
+fn base_version(requirement: &str) -> Option<Version> {
+    let requirement = requirement.trim_start_matches(['^', '=', '~', ' ']);
+    let mut parts = requirement.split('.').collect::<Vec<_>>();
+    parts.resize(3, "0");
+
+    Version::parse(&parts.join("."))
+}
+
+/// The dependencies of the project which are older than those of the
+/// templates.
+pub fn outdated(manifest: &CargoToml, template: &str) -> Vec<Outdated> {
+    let dependencies = manifest.dependencies();
+
+    template_dependencies(template)
+        .into_iter()
+        .filter_map(|(name, latest)| {
+            let (_, requirement) = dependencies.iter().find(|(n, _)| *n == name)?;
+            let is_older = base_version(requirement)? < base_version(&latest)?;
+
+            is_older.then(|| Outdated {
+                name,
+                requirement: requirement.clone(),
+                latest,
+            })
+        })
+        .collect()
+}
+
+/// Print the dependencies of a generated project which are older than those
+/// of the templates, and how to update them.
+pub fn print(project_dir: &Path) -> Result<(), Box<dyn Error>> {
+    let manifest = CargoToml::load(project_dir)?;
+    let templates = Templates::embedded();
+    // The dependencies of both flavors, a project only has those of one
+    let template = templates
+        .files()
+        .iter()
+        .filter(|(path, _)| path == "Cargo.toml" || path == "esp-idf-std/Cargo.toml")
+        .map(|(_, contents)| contents.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let outdated = outdated(&manifest, &template);
+    if outdated.is_empty() {
+        log::info!(
+            "The dependencies are as recent as those of the esp-generate {} templates",
+            env!("CARGO_PKG_VERSION")
+        );
+        return Ok(());
+    }
+
+    log::warn!(
+        "{} dependencies are older than those of the esp-generate {} templates:",
+        outdated.len(),
+        env!("CARGO_PKG_VERSION")
+    );
+    let width = outdated.iter().map(|o| o.name.len()).max().unwrap_or(0);
+    for Outdated {
+        name,
+        requirement,
+        latest,
+    } in &outdated
+    {
+        println!("  {name:width$}  {requirement} -> {latest}");
+    }
+
+    println!(
+        "\nThese versions are known to work together, update them all at once in Cargo.toml and run `cargo update`."
+    );
+    let generated_with = manifest
+        .generator_metadata()
+        .and_then(|metadata| metadata.get("version"))
+        .and_then(|item| item.as_str());
+    if generated_with.is_some_and(|version| version != env!("CARGO_PKG_VERSION")) {
+        println!("Run `esp-generate migrations` for the changes to apply by hand.");
+    } else if generated_with.is_none() {
+        println!(
+            "Run `esp-generate migrations --from <version>` for the changes to apply by hand."
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outdated() {
+        let template = Templates::embedded()
+            .files()
+            .iter()
+            .find(|(path, _)| path == "Cargo.toml")
+            .map(|(_, contents)| contents.clone())
+            .unwrap();
+
+        let dependencies = template_dependencies(&template);
+        let version = |name: &str| {
+            dependencies
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, version)| version.as_str())
+        };
+        assert!(version("esp-hal").is_some());
+        assert!(version("embassy-executor").is_some());
+        assert!(version("slint-build").is_some());
+        // A git dependency
+        assert_eq!(version("bleps"), None);
+        // Not a dependency
+        assert_eq!(version("opt-level"), None);
+
+        let manifest = CargoToml::parse(
+            r#"
+[package]
+name = "my-project"
+
+[dependencies]
+esp-hal = { version = "0.22", features = ["esp32c6"] }
+esp-alloc = "99.0.0"
+critical-section = "=1.1.0"
+embassy-time = { path = "../embassy-time" }
+"#,
+        )
+        .unwrap();
+        let outdated = outdated(&manifest, &template)
+            .into_iter()
+            .map(|o| (o.name, o.requirement))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outdated,
+            [
+                ("esp-hal".to_string(), "0.22".to_string()),
+                ("critical-section".to_string(), "=1.1.0".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Provenance of generated projects.
+//!
+//! Every generated manifest records the inputs the project was rendered from,
+//! along with a hash of those inputs and of the embedded template files. This
+//! allows re-rendering the project later and checking which files were edited
+//! by hand since.
+
+use std::{error::Error, fs, path::Path, process};
+
+use clap::ValueEnum;
+use esp_metadata::Chip;
+use sha2::{Digest, Sha256};
+use toml_edit::Item;
+
+use crate::{
+    cargo::CargoToml, migrations, override_variables, render_template, template_variables,
+    templates::Templates, timings::Timings, write_project,
+};
+
+/// Compute the hash of everything a project is rendered from: the generator
+/// version, chip, options, variables and the contents of every template file.
+pub fn hash(
+    templates: &Templates,
+    chip: Chip,
+    options: &[String],
+    variables: &[(String, String)],
+) -> String {
+    let mut options = options.to_vec();
+    options.sort();
+    options.dedup();
+
+    let mut variables = variables.to_vec();
+    variables.sort();
+
+    let mut files = templates.files().to_vec();
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("esp-generate {}\n", env!("CARGO_PKG_VERSION")));
+    hasher.update(format!("chip {chip}\n"));
+    for option in options {
+        hasher.update(format!("option {option}\n"));
+    }
+    for (key, value) in variables {
+        hasher.update(format!("variable {key}={value}\n"));
+    }
+    for (path, contents) in files {
+        hasher.update(format!("file {path} {:x}\n", Sha256::digest(contents)));
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// Re-render the project in `project_dir` from the inputs recorded in its
+/// manifest, and report every generated file which has since been modified or
+/// removed.
+pub fn verify(project_dir: &Path) -> Result<(), Box<dyn Error>> {
+    let manifest = CargoToml::load(project_dir)?;
+    let Some(metadata) = manifest.generator_metadata() else {
+        return Err(format!(
+            "{} has no [package.metadata.esp-generate] table, it was not generated by esp-generate or predates provenance tracking",
+            project_dir.display()
+        )
+        .into());
+    };
+
+    let name = manifest
+        .package_name()
+        .ok_or("The manifest has no package name")?;
+    let field = |key: &str| {
+        metadata
+            .get(key)
+            .and_then(|item| item.as_str())
+            .ok_or(format!("[package.metadata.esp-generate] has no '{key}'"))
+    };
+    let version = field("version")?;
+    let recorded_hash = field("hash")?;
+    let chip = Chip::from_str(field("chip")?, true)?;
+    let options = metadata
+        .get("options")
+        .and_then(|item| item.as_array())
+        .ok_or("[package.metadata.esp-generate] has no 'options'")?
+        .iter()
+        .filter_map(|option| option.as_str().map(String::from))
+        .collect::<Vec<_>>();
+
+    if version != env!("CARGO_PKG_VERSION") {
+        log::warn!(
+            "The project was generated by esp-generate {version}, but is verified against {}",
+            env!("CARGO_PKG_VERSION")
+        );
+        migrations::print(version, Some(&options))?;
+    }
+
+    // Projects generated from the `git-main` templates record their commit
+    let templates = match metadata.get("templates").and_then(|item| item.as_str()) {
+        Some(revision) => Templates::fetch(Some(revision), true)?,
+        None => Templates::embedded(),
+    };
+
+    // Only the variables which were overridden are recorded
+    let overrides = metadata
+        .get("variables")
+        .and_then(Item::as_table_like)
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(key, value)| Some((key.to_string(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut variables = template_variables(name, chip);
+    override_variables(&mut variables, &overrides)?;
+    if hash(&templates, chip, &options, &variables) == recorded_hash {
+        log::info!("The recorded hash matches the inputs of this version of esp-generate");
+    } else {
+        log::warn!(
+            "The recorded hash does not match: the templates, generator version or variables differ from those the project was generated with"
+        );
+    }
+
+    let files = render_template(&templates, chip, &options, &variables);
+    let paths = files
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+
+    // Render into a temporary directory, so that the same formatting is applied
+    // as when the project was generated:
+    let temp_dir = tempfile::tempdir()?;
+    let rendered_dir = temp_dir.path().join(name);
+    fs::create_dir(&rendered_dir)?;
+    write_project(&rendered_dir, files, &[], &mut Timings::default())?;
+
+    let mut diverged = Vec::new();
+    for path in paths {
+        let expected = fs::read_to_string(rendered_dir.join(&path))?;
+        match fs::read_to_string(project_dir.join(&path)) {
+            Ok(actual) if actual == expected => {}
+            Ok(_) => diverged.push(format!("modified: {path}")),
+            Err(_) => diverged.push(format!("missing:  {path}")),
+        }
+    }
+
+    if diverged.is_empty() {
+        log::info!("All generated files are unmodified");
+    } else {
+        for file in &diverged {
+            log::warn!("{file}");
+        }
+        log::error!("{} generated file(s) have diverged", diverged.len());
+        process::exit(-1);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_provenance_hash_ignores_option_order() {
+        let variables = template_variables("test", Chip::Esp32c3);
+        let manifest = |options: &[&str]| {
+            let options = options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+            render_template(&Templates::embedded(), Chip::Esp32c3, &options, &variables)
+                .into_iter()
+                .find(|(path, _)| path == "Cargo.toml")
+                .unwrap()
+                .1
+        };
+
+        let manifest1 = manifest(&["wifi", "alloc"]);
+        let manifest2 = manifest(&["alloc", "wifi"]);
+        assert_eq!(manifest1, manifest2);
+
+        let manifest = CargoToml::parse(&manifest1).unwrap();
+        let metadata = manifest.generator_metadata().unwrap();
+        assert_eq!(
+            metadata["hash"].as_str().unwrap(),
+            hash(
+                &Templates::embedded(),
+                Chip::Esp32c3,
+                &["alloc".to_string(), "wifi".to_string()],
+                &variables
+            )
+        );
+        assert_eq!(metadata["options"].as_array().unwrap().len(), 2);
+        // Only recorded for the `git-main` templates
+        assert!(metadata.get("templates").is_none());
+        // Only recorded when overridden
+        assert!(metadata.get("variables").is_none());
+
+        let mut overridden = variables.clone();
+        let overrides = BTreeMap::from([(
+            "wokwi-board".to_string(),
+            "board-esp32-c3-rust-1".to_string(),
+        )]);
+        override_variables(&mut overridden, &overrides).unwrap();
+        let files = render_template(
+            &Templates::embedded(),
+            Chip::Esp32c3,
+            &["wokwi".to_string()],
+            &overridden,
+        );
+        let (_, manifest) = files.iter().find(|(path, _)| path == "Cargo.toml").unwrap();
+        assert!(manifest.contains(r#"variables = { wokwi-board = "board-esp32-c3-rust-1" }"#));
+    }
+}
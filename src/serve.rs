@@ -0,0 +1,187 @@
+//! A small local web UI exposing the option tree in a browser and producing a
+//! downloadable zip archive of the generated project.
+//!
+//! This is intended for workshops and classrooms, where a browser is often
+//! easier to use than a terminal.
+
+use std::{
+    error::Error,
+    io::{Cursor, Write},
+    net::SocketAddr,
+    sync::LazyLock,
+};
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use clap::ValueEnum;
+use esp_metadata::Chip;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{
+    config::{self, solver, GeneratorOptionItem},
+    format_cargo_toml, process_options, render_template, template_variables,
+    templates::Templates,
+};
+
+const INDEX_HTML: &str = include_str!("serve/index.html");
+
+/// The options offered, experimental ones are not.
+static VISIBLE_OPTIONS: LazyLock<&[GeneratorOptionItem]> =
+    LazyLock::new(|| config::visible_options(false));
+
+/// Start the web UI and serve requests until the process is terminated.
+pub fn serve(address: SocketAddr) -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        let app = Router::new()
+            .route("/", get(index))
+            .route("/api/chips", get(chips))
+            .route("/api/options/{chip}", get(options))
+            .route("/api/generate", post(generate));
+
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        log::info!("Serving esp-generate on http://{}", listener.local_addr()?);
+
+        axum::serve(listener, app).await?;
+
+        Ok(())
+    })
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn chips() -> Json<Value> {
+    let chips = Chip::value_variants()
+        .iter()
+        .map(|chip| json!({ "name": chip.to_string(), "pretty_name": chip.pretty_name() }))
+        .collect::<Vec<_>>();
+
+    Json(Value::Array(chips))
+}
+
+async fn options(Path(chip): Path<String>) -> Response {
+    let Ok(chip) = Chip::from_str(&chip, true) else {
+        return (StatusCode::NOT_FOUND, format!("Unknown chip '{chip}'")).into_response();
+    };
+
+    Json(option_tree(chip, &VISIBLE_OPTIONS)).into_response()
+}
+
+/// Serialize the option tree, marking the options which are not available for
+/// the given chip.
+fn option_tree(chip: Chip, items: &[GeneratorOptionItem]) -> Value {
+    let items = items
+        .iter()
+        .map(|item| {
+            let mut value = serde_json::to_value(item).unwrap();
+            if let GeneratorOptionItem::Category(category) = item {
+                value["options"] = option_tree(chip, category.options);
+            } else {
+                value["available"] = Value::Bool(item.supports(chip));
+            }
+            value
+        })
+        .collect::<Vec<_>>();
+
+    Value::Array(items)
+}
+
+#[derive(Deserialize)]
+struct GenerateRequest {
+    name: String,
+    chip: String,
+    #[serde(default)]
+    options: Vec<String>,
+}
+
+async fn generate(Json(request): Json<GenerateRequest>) -> Response {
+    let name = request.name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            "The project name may only contain ASCII letters, digits, '-' and '_'",
+        )
+            .into_response();
+    }
+
+    let Ok(chip) = Chip::from_str(&request.chip, true) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Unknown chip '{}'", request.chip),
+        )
+            .into_response();
+    };
+
+    // Experimental options are not offered, so not accepted either
+    let mut violations = solver::experimental(&request.options);
+    if let Err(invalid) = process_options(chip, &request.options) {
+        violations.extend(invalid);
+    }
+    if !violations.is_empty() {
+        let message = violations
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+
+    match zip_project(name, chip, &request.options) {
+        Ok(archive) => (
+            [
+                (header::CONTENT_TYPE, "application/zip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{name}.zip\""),
+                ),
+            ],
+            archive,
+        )
+            .into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response(),
+    }
+}
+
+/// Render the project and pack it into a zip archive, with all files placed in
+/// a directory named after the project.
+fn zip_project(name: &str, chip: Chip, options: &[String]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    let variables = template_variables(name, chip);
+    for (file_path, mut contents) in
+        render_template(&Templates::embedded(), chip, options, &variables)
+    {
+        // `cargo fmt` cannot be run here, but Taplo is a library:
+        if file_path == "Cargo.toml" {
+            contents = format_cargo_toml(&contents);
+        }
+
+        let permissions = if file_path.ends_with(".sh") {
+            0o755
+        } else {
+            0o644
+        };
+        let file_options = SimpleFileOptions::default().unix_permissions(permissions);
+
+        zip.start_file(format!("{name}/{file_path}"), file_options)?;
+        zip.write_all(contents.as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
@@ -0,0 +1,115 @@
+//! Snippets contributed by options to other template files.
+//!
+//! Rather than guarding its lines with `IF` in every file it touches, an option
+//! can provide them in `snippets/<option>.<ext>`. Each section of a snippet
+//! file starts with `//SNIPPET <anchor>` (or `#SNIPPET <anchor>`) and is
+//! inserted at every `//ANCHOR <anchor>` (or `#ANCHOR <anchor>`) line of the
+//! other templates, indented like the anchor. This happens before rendering,
+//! so sections can use the other directives, e.g. `REPLACE`.
+//!
+//! The snippets of the selected options are inserted in the alphabetical order
+//! of the options. Anchors without any snippet are removed.
+
+use crate::templates::Templates;
+
+/// The directory of the snippet files, which are not rendered themselves.
+pub const SNIPPETS_DIR: &str = "snippets/";
+
+/// A section of a snippet file.
+struct Section {
+    anchor: String,
+    /// The path of the snippet file.
+    path: String,
+    /// The lines of the section, with their line number in the file.
+    lines: Vec<(usize, String)>,
+}
+
+/// The snippets of the selected options.
+pub struct Snippets {
+    sections: Vec<Section>,
+}
+
+impl Snippets {
+    /// Collect the snippets of the selected options from the templates.
+    pub fn collect(templates: &Templates, selected: &[String]) -> Self {
+        let mut sections = Vec::new();
+
+        // The templates are sorted by path, hence by option
+        for (path, contents) in templates.files() {
+            let Some(file_name) = path.strip_prefix(SNIPPETS_DIR) else {
+                continue;
+            };
+            let option = file_name.split_once('.').map_or(file_name, |(o, _)| o);
+            if !selected.iter().any(|s| s == option) {
+                continue;
+            }
+
+            for (number, line) in contents.lines().enumerate() {
+                if let Some(anchor) = directive(line, "SNIPPET") {
+                    sections.push(Section {
+                        anchor: anchor.to_string(),
+                        path: path.clone(),
+                        lines: Vec::new(),
+                    });
+                } else if let Some(section) = sections.last_mut() {
+                    section.lines.push((number + 1, line.to_string()));
+                }
+            }
+        }
+
+        Self { sections }
+    }
+
+    /// Replace every anchor of a template with the snippets for it.
+    pub fn expand(&self, contents: &str) -> String {
+        let mut res = self
+            .expand_lines("", contents)
+            .into_iter()
+            .map(|(_, line)| line + "\n")
+            .collect::<String>();
+
+        if !contents.ends_with('\n') {
+            res.pop();
+        }
+
+        res
+    }
+
+    /// The lines of the template at `path` with its anchors replaced by the
+    /// snippets for them, each along with where it comes from, as
+    /// `<path>:<line>`.
+    pub fn expand_lines(&self, path: &str, contents: &str) -> Vec<(String, String)> {
+        let mut res = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let Some(anchor) = directive(line, "ANCHOR") else {
+                res.push((format!("{path}:{}", number + 1), line.to_string()));
+                continue;
+            };
+
+            let indent = &line[..line.len() - line.trim_start().len()];
+            for section in self.sections.iter().filter(|s| s.anchor == anchor) {
+                for (number, line) in &section.lines {
+                    let line = if line.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{indent}{line}")
+                    };
+                    res.push((format!("{}:{number}", section.path), line));
+                }
+            }
+        }
+
+        res
+    }
+}
+
+/// The argument of a `//<name>` or `#<name>` directive line.
+fn directive<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let trimmed = line.trim();
+    let rest = trimmed
+        .strip_prefix("//")
+        .or_else(|| trimmed.strip_prefix('#'))?;
+
+    rest.strip_prefix(name)?.strip_prefix(' ').map(str::trim)
+}
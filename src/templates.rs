@@ -0,0 +1,240 @@
+//! The template files projects are rendered from.
+//!
+//! By default, the templates embedded at build time are used. The `git-main`
+//! channel instead uses the templates of the `main` branch of the esp-generate
+//! repository, or of a given commit, so template fixes are available before
+//! they are released. These are fetched with `git` into the user's cache
+//! directory, and the commit they were fetched from is recorded in the
+//! generated manifest.
+
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{Duration, SystemTime},
+};
+
+use clap::ValueEnum;
+
+use crate::template_files;
+
+/// The repository the `git-main` templates are fetched from.
+const REPOSITORY: &str = "https://github.com/esp-rs/esp-generate";
+
+/// How long the fetched `main` branch is reused before fetching it again.
+const FETCH_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The file whose modification time is when `main` was last fetched, relative
+/// to the checkout. Git only rewrites the ref when `main` moved, and not at
+/// all once it is packed.
+const FETCHED_AT_FILE: &str = ".git/esp-generate-fetched-main";
+
+/// Where the templates come from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum TemplateChannel {
+    /// The templates embedded in this version of esp-generate
+    #[default]
+    Stable,
+    /// The templates of the `main` branch of the esp-generate repository
+    GitMain,
+}
+
+/// A set of template files, as paths relative to the project and their
+/// contents.
+pub struct Templates {
+    files: Vec<(String, String)>,
+    /// The commit the templates were fetched from, `None` if embedded.
+    revision: Option<String>,
+}
+
+impl Templates {
+    /// The templates embedded at build time.
+    pub fn embedded() -> Self {
+        Self {
+            files: template_files::TEMPLATE_FILES
+                .iter()
+                .map(|&(path, contents)| (path.to_string(), contents.to_string()))
+                .collect(),
+            revision: None,
+        }
+    }
+
+    /// The templates of the `main` branch, or of `revision` if given, which
+    /// must be a full commit hash.
+    ///
+    /// The `main` branch is fetched again once the last fetch is older than
+    /// an hour, or always if `use_cache` is `false`. If fetching fails, the
+    /// previously fetched templates are used. A commit, once fetched, is
+    /// always taken from the cache.
+    pub fn fetch(revision: Option<&str>, use_cache: bool) -> Result<Self, String> {
+        let dir = dirs::cache_dir()
+            .ok_or("No cache directory to fetch the templates into")?
+            .join("esp-generate")
+            .join("templates");
+
+        if !dir.join(".git").is_dir() {
+            fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+            git(&dir, &["init", "--quiet"])?;
+            git(&dir, &["remote", "add", "origin", REPOSITORY])?;
+            git(&dir, &["sparse-checkout", "set", "template"])?;
+        }
+
+        let target = match revision {
+            Some(revision) => {
+                if revision.len() != 40 || !revision.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!(
+                        "Template revision '{revision}' is not a full commit hash"
+                    ));
+                }
+
+                if git(&dir, &["cat-file", "-e", &format!("{revision}^{{commit}}")]).is_err() {
+                    git(
+                        &dir,
+                        &["fetch", "--quiet", "--depth", "1", "origin", revision],
+                    )?;
+                }
+
+                revision.to_string()
+            }
+            None => {
+                let cached =
+                    git(&dir, &["rev-parse", "--verify", "--quiet", "origin/main"]).is_ok();
+                let fresh = fs::metadata(dir.join(FETCHED_AT_FILE))
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|time| SystemTime::now().duration_since(time).ok())
+                    .is_some_and(|age| age < FETCH_TTL);
+
+                if !use_cache || !fresh {
+                    let fetch = git(
+                        &dir,
+                        &[
+                            "fetch",
+                            "--quiet",
+                            "--depth",
+                            "1",
+                            "origin",
+                            "+main:refs/remotes/origin/main",
+                        ],
+                    );
+                    match fetch {
+                        // Failing to record the time only costs fetching again
+                        Ok(_) => _ = fs::write(dir.join(FETCHED_AT_FILE), ""),
+                        Err(error) if cached => {
+                            log::warn!("{error}, using the templates fetched before")
+                        }
+                        Err(error) => return Err(error),
+                    }
+                }
+
+                "origin/main".to_string()
+            }
+        };
+
+        git(
+            &dir,
+            &["checkout", "--quiet", "--force", "--detach", &target],
+        )?;
+        let revision = git(&dir, &["rev-parse", "HEAD"])?;
+
+        let mut files = Vec::new();
+        read_files(&dir.join("template"), "", &mut files).map_err(|error| error.to_string())?;
+        if files.is_empty() {
+            return Err(format!("Commit {revision} has no templates"));
+        }
+        files.sort();
+
+        Ok(Self {
+            files,
+            revision: Some(revision),
+        })
+    }
+
+    /// The templates in `dir`, e.g. the `template/` directory of an
+    /// esp-generate checkout.
+    #[cfg(feature = "dev")]
+    pub fn from_dir(dir: &Path) -> std::io::Result<Self> {
+        let mut files = Vec::new();
+        read_files(dir, "", &mut files)?;
+        files.sort();
+
+        Ok(Self {
+            files,
+            revision: None,
+        })
+    }
+
+    /// Templates with the given files, sorted by path.
+    #[cfg(test)]
+    pub fn from_files(mut files: Vec<(String, String)>) -> Self {
+        files.sort();
+
+        Self {
+            files,
+            revision: None,
+        }
+    }
+
+    pub fn files(&self) -> &[(String, String)] {
+        &self.files
+    }
+
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+}
+
+/// Run `git` in `dir`, returning its output.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|error| format!("Failed to run git: {error}"))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Read the files below `dir`, the same way `build.rs` embeds them.
+fn read_files(dir: &Path, prefix: &str, files: &mut Vec<(String, String)>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        let relative_path = format!("{prefix}{name}");
+
+        if entry.file_type()?.is_dir() {
+            if relative_path != "target" {
+                read_files(&path, &format!("{relative_path}/"), files)?;
+            }
+        } else {
+            let contents = fs::read_to_string(&path)?.replace("\r\n", "\n");
+            files.push((relative_path, contents));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "dev"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_from_dir() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("template");
+        let templates = Templates::from_dir(&dir).unwrap();
+
+        assert_eq!(templates.files(), Templates::embedded().files());
+        assert_eq!(templates.revision(), None);
+    }
+}
@@ -0,0 +1,98 @@
+//! Measuring how long each phase of the generation takes, as printed with
+//! `--timings`.
+//!
+//! Only the phases esp-generate runs by itself are measured: the time spent
+//! in the TUI depends on the user. The report has one `<phase> <ms> ms` line
+//! per phase, which `xtask perf` parses to catch slowdowns.
+
+use std::time::{Duration, Instant};
+
+/// The durations of the phases run so far, in order.
+#[derive(Debug, Default)]
+pub struct Timings {
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    /// Run `f` as the given phase and record how long it took.
+    pub fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(phase, start.elapsed());
+
+        result
+    }
+
+    /// Record the duration of a phase, adding it to that of an earlier run of
+    /// the same phase.
+    pub fn record(&mut self, phase: &'static str, duration: Duration) {
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += duration,
+            None => self.phases.push((phase, duration)),
+        }
+    }
+
+    /// The duration of a phase, if it ran.
+    #[cfg(test)]
+    pub fn get(&self, phase: &str) -> Option<Duration> {
+        self.phases
+            .iter()
+            .find(|(name, _)| *name == phase)
+            .map(|(_, duration)| *duration)
+    }
+
+    /// The breakdown of the phases and their total, one per line.
+    pub fn report(&self) -> String {
+        let total = self.phases.iter().map(|(_, duration)| *duration).sum();
+        let width = self
+            .phases
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("total".len());
+
+        let mut report = String::new();
+        for (name, duration) in self.phases.iter().chain([&("total", total)]) {
+            let ms = duration.as_secs_f64() * 1000.0;
+            report.push_str(&format!("  {name:width$}  {ms:>9.1} ms\n"));
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use esp_metadata::Chip;
+
+    use super::*;
+    use crate::{render_template, templates::Templates};
+
+    #[test]
+    fn test_timings() {
+        let mut timings = Timings::default();
+        let files = timings.time("render", || {
+            render_template(&Templates::embedded(), Chip::Esp32c3, &[], &[])
+        });
+        assert!(!files.is_empty());
+        timings.record("rustfmt", Duration::from_millis(250));
+        timings.record("rustfmt", Duration::from_millis(500));
+
+        assert!(timings.get("render").is_some());
+        assert_eq!(timings.get("rustfmt"), Some(Duration::from_millis(750)));
+        assert_eq!(timings.get("git"), None);
+
+        // One `<phase> <ms> ms` line per phase, as parsed by `xtask perf`
+        let report = timings.report();
+        let phases = report
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(phases.len(), 3);
+        assert_eq!(phases[0][0], "render");
+        assert_eq!(phases[1], ["rustfmt", "750.0", "ms"]);
+        assert_eq!(phases[2][0], "total");
+        assert_eq!(phases[2][2], "ms");
+    }
+}
@@ -1,14 +1,25 @@
 use std::{error::Error, io};
 
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use esp_metadata::Chip;
 use ratatui::{prelude::*, style::palette::tailwind, widgets::*};
 
-use super::{GeneratorOption, GeneratorOptionItem};
+use crate::{
+    config::{solver, split_value, GeneratorOptionItem, GeneratorValueOption},
+    glyphs::Glyph,
+};
+
+pub use keymap::{Action, Keymap};
+pub use session_log::SessionLog;
+
+pub mod chip_picker;
+mod keymap;
+pub mod preflight;
+pub mod session_log;
 
 const TODO_HEADER_BG: Color = tailwind::BLUE.c950;
 const NORMAL_ROW_COLOR: Color = tailwind::SLATE.c950;
@@ -41,7 +52,7 @@ impl Repository {
         for &index in &self.path {
             current = match current[index] {
                 GeneratorOptionItem::Category(category) => category.options,
-                GeneratorOptionItem::Option(_) => unreachable!(),
+                GeneratorOptionItem::Option(_) | GeneratorOptionItem::Value(_) => unreachable!(),
             }
         }
 
@@ -55,9 +66,9 @@ impl Repository {
     fn toggle_current(&mut self, index: usize) {
         let current = self.current_level()[index];
         match current {
-            GeneratorOptionItem::Category(_) => unreachable!(),
+            GeneratorOptionItem::Category(_) | GeneratorOptionItem::Value(_) => unreachable!(),
             GeneratorOptionItem::Option(option) => {
-                if !option.chips.is_empty() && !option.chips.contains(&self.chip) {
+                if !option.supports(self.chip) {
                     return;
                 }
 
@@ -67,58 +78,177 @@ impl Repository {
                     self.selected.push(option.name.to_string());
                 }
 
-                let toggled_option = option;
-                let currently_selected = self.selected.clone();
-                for option in currently_selected {
-                    let Some(option) = find_option(&option, self.options) else {
-                        ratatui::restore();
-                        panic!("option not found");
-                    };
-                    for enable in option.enables {
-                        if !self.selected.contains(&enable.to_string()) {
-                            self.selected.push(enable.to_string());
-                        }
-                    }
-                    for disable in option.disables {
-                        if disable != &toggled_option.name
-                            && self.selected.contains(&disable.to_string())
-                        {
-                            let Some(idx) = self.selected.iter().position(|v| v == disable) else {
-                                ratatui::restore();
-                                panic!("disable option not found");
-                            };
-                            self.selected.remove(idx);
-                        }
-                    }
+                self.apply_constraints(option.name);
+            }
+        }
+    }
+
+    fn set_value(&mut self, index: usize, input: &str) -> Result<(), String> {
+        let Some(option) = self.value_option(index) else {
+            unreachable!()
+        };
+        let value = option.parse(input)?;
+
+        self.selected
+            .retain(|v| split_value(v).is_none_or(|(name, _)| name != option.name));
+        if value != option.default {
+            self.selected.push(format!("{}={value}", option.name));
+        }
+
+        self.apply_constraints(option.name);
+
+        Ok(())
+    }
+
+    /// Deselect every option and reset every value.
+    fn clear(&mut self) {
+        self.selected.clear();
+    }
+
+    /// Deselect the options and reset the values of the current level,
+    /// including those in its subcategories, unless they are required by
+    /// another selected option.
+    fn reset_current_level(&mut self) {
+        let names: Vec<String> = self
+            .current_level()
+            .iter()
+            .flat_map(|item| item.options())
+            .collect();
+
+        self.selected.retain(|option| {
+            let name = split_value(option).map_or(option.as_str(), |(name, _)| name);
+            !names.iter().any(|n| n == name)
+        });
+        self.selected = solver::implied(&self.selected);
+    }
+
+    /// Select every option of the current level which can be selected along
+    /// with the current selection. If there is none, deselect them all.
+    fn toggle_current_level(&mut self) {
+        let options: Vec<&str> = self
+            .current_level()
+            .iter()
+            .filter_map(|item| match item {
+                GeneratorOptionItem::Option(option) if option.supports(self.chip) => {
+                    Some(option.name)
                 }
+                _ => None,
+            })
+            .collect();
+
+        let mut selected_any = false;
+        for option in &options {
+            if self.selected.iter().any(|v| v == option) {
+                continue;
+            }
+
+            let mut candidate = self.selected.clone();
+            candidate.push(option.to_string());
+            let candidate = solver::implied(&candidate);
+            if solver::is_valid(self.chip, &candidate) {
+                self.selected = candidate;
+                selected_any = true;
             }
         }
+
+        if !selected_any {
+            self.selected.retain(|v| !options.contains(&v.as_str()));
+            self.selected = solver::implied(&self.selected);
+        }
+    }
+
+    /// Select everything required by, and deselect everything conflicting
+    /// with, the current selection. `toggled` is never deselected.
+    fn apply_constraints(&mut self, toggled: &str) {
+        self.selected = solver::implied(&self.selected);
+
+        let disabled = solver::disabled(&self.selected);
+        self.selected.retain(|option| {
+            let name = split_value(option).map_or(option.as_str(), |(name, _)| name);
+            name == toggled || !disabled.contains(&name)
+        });
+    }
+
+    fn value_option(&self, index: usize) -> Option<GeneratorValueOption> {
+        match self.current_level()[index] {
+            GeneratorOptionItem::Value(option) if option.supports(self.chip) => Some(option),
+            _ => None,
+        }
     }
 
     fn is_option(&self, index: usize) -> bool {
         matches!(self.current_level()[index], GeneratorOptionItem::Option(_))
     }
 
+    fn is_value(&self, index: usize) -> bool {
+        self.current_level()[index].is_value()
+    }
+
     fn up(&mut self) {
         self.path.pop();
     }
 
+    /// The chip followed by the titles of the categories leading to the
+    /// current level, e.g. `ESP32-C3 ▸ Heap placement`.
+    fn breadcrumb(&self) -> String {
+        let mut crumbs = vec![self.chip.pretty_name().to_string()];
+
+        let mut current = self.options;
+        for &index in &self.path {
+            crumbs.push(current[index].title());
+            current = match current[index] {
+                GeneratorOptionItem::Category(category) => category.options,
+                GeneratorOptionItem::Option(_) | GeneratorOptionItem::Value(_) => unreachable!(),
+            }
+        }
+
+        crumbs.join(&format!(" {} ", Glyph::Breadcrumb))
+    }
+
+    /// Whether selecting the option also selects `unstable-hal`, which
+    /// bare-metal projects only get when asking for it.
+    fn unlocks_unstable_hal(&self, item: &GeneratorOptionItem) -> bool {
+        !item.is_category()
+            && !self
+                .selected
+                .iter()
+                .any(|o| o == "esp-idf-std" || o == solver::UNSTABLE_HAL)
+            && solver::needs_unstable_hal(&item.name())
+    }
+
     fn current_level_desc(&self) -> Vec<(bool, String)> {
         self.current_level()
             .iter()
             .map(|v| {
                 (
-                    v.chips().is_empty() || v.chips().contains(&self.chip),
+                    v.supports(self.chip),
                     format!(
-                        " {} {}",
+                        " {} {}{}{}{}",
                         if self.selected.contains(&v.name()) {
-                            "✅"
+                            Glyph::Selected
                         } else if v.is_category() {
-                            "▶️"
+                            Glyph::Category
                         } else {
-                            "  "
+                            Glyph::Unselected
                         },
                         v.title(),
+                        match v {
+                            GeneratorOptionItem::Value(option) => {
+                                format!(": {} {}", option.value(&self.selected), option.unit)
+                            }
+                            _ => String::new(),
+                        },
+                        if self.unlocks_unstable_hal(v) {
+                            " [unstable-hal]"
+                        } else {
+                            ""
+                        },
+                        // The `esp` toolchain of Xtensa chips is a nightly one
+                        if v.requires_nightly() && !self.chip.is_xtensa() {
+                            " [nightly]"
+                        } else {
+                            ""
+                        },
                     ),
                 )
             })
@@ -126,28 +256,6 @@ impl Repository {
     }
 }
 
-fn find_option(
-    option: &str,
-    options: &'static [GeneratorOptionItem],
-) -> Option<&'static GeneratorOption> {
-    for item in options {
-        match item {
-            GeneratorOptionItem::Category(category) => {
-                let found_option = find_option(option, category.options);
-                if found_option.is_some() {
-                    return found_option;
-                }
-            }
-            GeneratorOptionItem::Option(item) => {
-                if item.name == option {
-                    return Some(item);
-                }
-            }
-        }
-    }
-    None
-}
-
 pub fn init_terminal() -> AppResult<Terminal<impl Backend>> {
     enable_raw_mode()?;
     io::stdout().execute(EnterAlternateScreen)?;
@@ -165,18 +273,26 @@ pub fn restore_terminal() -> AppResult<()> {
 pub struct App {
     state: Vec<ListState>,
     repository: Repository,
+    keymap: Keymap,
     confirm_quit: bool,
+    editing: Option<String>,
+    edit_error: Option<String>,
+    session_log: Option<SessionLog>,
 }
 
 impl App {
-    pub fn new(repository: Repository) -> Self {
+    pub fn new(repository: Repository, keymap: Keymap, session_log: Option<SessionLog>) -> Self {
         let mut initial_state = ListState::default();
         initial_state.select(Some(0));
 
         Self {
             repository,
             state: vec![initial_state],
+            keymap,
             confirm_quit: false,
+            editing: None,
+            edit_error: None,
+            session_log,
         }
     }
     pub fn selected(&self) -> usize {
@@ -215,52 +331,118 @@ impl App {
             self.draw(&mut terminal)?;
 
             if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    use KeyCode::*;
-
-                    if self.confirm_quit {
-                        match key.code {
-                            Char('y') | Char('Y') => return Ok(None),
-                            _ => self.confirm_quit = false,
-                        }
-                        continue;
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+
+                let context = self
+                    .session_log
+                    .is_some()
+                    .then(|| (self.location(), self.repository.selected.clone()));
+                let outcome = self.handle_key(key);
+
+                if let (Some(log), Some((location, before))) = (&mut self.session_log, context) {
+                    log.key(&key, &location, &before, &self.repository.selected);
+                    if let Some(selected) = &outcome {
+                        log.finish(selected.as_deref());
                     }
+                }
+                if let Some(selected) = outcome {
+                    return Ok(selected);
+                }
+            }
+        }
+    }
 
-                    match key.code {
-                        Char('q') => self.confirm_quit = true,
-                        Char('s') | Char('S') => return Ok(Some(self.repository.selected.clone())),
-                        Esc => {
-                            if self.state.len() == 1 {
-                                self.confirm_quit = true;
-                            } else {
-                                self.repository.up();
-                                self.exit_menu();
-                            }
-                        }
-                        Char('h') | Left => {
-                            self.repository.up();
-                            self.exit_menu();
-                        }
-                        Char('l') | Char(' ') | Right | Enter => {
-                            let selected = self.selected();
-                            if self.repository.is_option(selected) {
-                                self.repository.toggle_current(selected);
-                            } else {
-                                self.repository.select(self.selected());
-                                self.enter_menu();
-                            }
-                        }
-                        Char('j') | Down => {
-                            self.select_next();
-                        }
-                        Char('k') | Up => {
-                            self.select_previous();
-                        }
-                        _ => {}
+    /// Where the cursor is, e.g. `ESP32-C3 ▸ Heap placement ▸ alloc`.
+    fn location(&self) -> String {
+        let breadcrumb = self.repository.breadcrumb();
+        match self.repository.current_level().get(self.selected()) {
+            Some(item) => format!("{breadcrumb} {} {}", Glyph::Breadcrumb, item.name()),
+            None => breadcrumb,
+        }
+    }
+
+    /// Handle a key press, returning the outcome of the session once it ends:
+    /// the selected options, or `None` if the user quit.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Option<Vec<String>>> {
+        use KeyCode::*;
+
+        let selected = self.selected();
+        if let Some(input) = &mut self.editing {
+            match key.code {
+                Char(c) if c.is_ascii_digit() => input.push(c),
+                Backspace => {
+                    input.pop();
+                }
+                Enter => match self.repository.set_value(selected, input) {
+                    Ok(()) => {
+                        self.editing = None;
+                        self.edit_error = None;
+                    }
+                    Err(error) => self.edit_error = Some(error),
+                },
+                Esc => {
+                    self.editing = None;
+                    self.edit_error = None;
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        if self.confirm_quit {
+            match key.code {
+                Char('y') | Char('Y') => return Some(None),
+                _ => self.confirm_quit = false,
+            }
+            return None;
+        }
+
+        if key.code == Esc {
+            if self.state.len() == 1 {
+                self.confirm_quit = true;
+            } else {
+                self.repository.up();
+                self.exit_menu();
+            }
+            return None;
+        }
+
+        match self.keymap.action(&key) {
+            Some(Action::Quit) => self.confirm_quit = true,
+            Some(Action::Save) => return Some(Some(self.repository.selected.clone())),
+            Some(Action::Back) => {
+                self.repository.up();
+                self.exit_menu();
+            }
+            Some(Action::Select) => {
+                let selected = self.selected();
+                if self.repository.is_option(selected) {
+                    self.repository.toggle_current(selected);
+                } else if self.repository.is_value(selected) {
+                    if let Some(option) = self.repository.value_option(selected) {
+                        let value = option.value(&self.repository.selected);
+                        self.editing = Some(value.to_string());
                     }
+                } else {
+                    self.repository.select(self.selected());
+                    self.enter_menu();
                 }
             }
+            Some(Action::ToggleAll) => self.repository.toggle_current_level(),
+            Some(Action::Reset) => self.repository.reset_current_level(),
+            Some(Action::Clear) => self.repository.clear(),
+            Some(Action::Down) => {
+                self.select_next();
+            }
+            Some(Action::Up) => {
+                self.select_previous();
+            }
+            None => {}
         }
+
+        None
     }
 
     fn draw(&mut self, terminal: &mut Terminal<impl Backend>) -> AppResult<()> {
@@ -278,7 +460,7 @@ impl Widget for &mut App {
         let vertical = Layout::vertical([
             Constraint::Length(2),
             Constraint::Fill(1),
-            Constraint::Length(2),
+            Constraint::Length(3),
         ]);
         let [header_area, rest_area, footer_area] = vertical.areas(area);
 
@@ -295,10 +477,28 @@ impl Widget for &mut App {
 
 impl App {
     fn render_title(&self, area: Rect, buf: &mut Buffer) {
+        let [title_area, context_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(area);
+
         Paragraph::new("esp-generate")
             .bold()
             .centered()
-            .render(area, buf);
+            .render(title_area, buf);
+
+        let count = self.repository.selected.len();
+        let count = format!("{count} selected ");
+        let [breadcrumb_area, count_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(count.chars().count() as u16),
+        ])
+        .areas(context_area);
+
+        Paragraph::new(format!(" {}", self.repository.breadcrumb()))
+            .fg(SELECTED_STYLE_FG)
+            .render(breadcrumb_area, buf);
+        Paragraph::new(count)
+            .right_aligned()
+            .render(count_area, buf);
     }
 
     fn render_item(&mut self, area: Rect, buf: &mut Buffer) {
@@ -359,12 +559,38 @@ impl App {
     }
 
     fn render_footer(&self, area: Rect, buf: &mut Buffer) {
-        let text = if self.confirm_quit {
-            "Are you sure you want to quit? (y/N)"
+        let text = if let Some(input) = &self.editing {
+            let option = self.repository.value_option(self.selected()).unwrap();
+            format!(
+                "{} ({}-{} {}): {input}{} Enter to confirm, ESC to cancel\n{}",
+                option.display_name,
+                option.min,
+                option.max,
+                option.unit,
+                Glyph::Cursor,
+                self.edit_error.as_deref().unwrap_or_default()
+            )
+        } else if self.confirm_quit {
+            "Are you sure you want to quit? (y/N)".to_string()
         } else {
-            "Use ↓↑ to move, ESC/← to go up, → to go deeper or change the value, s/S to save and generate, ESC/q to cancel"
+            let keys = |action| self.keymap.keys(action);
+            format!(
+                "Use {}, {} to move, ESC/{} to go up, {} to go deeper or change the value, {} to toggle all, {} to reset the category, {} to clear all, {} to save and generate, ESC/{} to cancel",
+                keys(Action::Down),
+                keys(Action::Up),
+                keys(Action::Back),
+                keys(Action::Select),
+                keys(Action::ToggleAll),
+                keys(Action::Reset),
+                keys(Action::Clear),
+                keys(Action::Save),
+                keys(Action::Quit),
+            )
         };
 
-        Paragraph::new(text).centered().render(area, buf);
+        Paragraph::new(text)
+            .centered()
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
     }
 }
@@ -0,0 +1,282 @@
+use clap::ValueEnum;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use esp_metadata::{Chip, Config, Cores};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    keymap::{Action, Keymap},
+    AppResult, DISABLED_STYLE_FG, NORMAL_ROW_COLOR, SELECTED_STYLE_FG, TEXT_COLOR,
+};
+use crate::{
+    config::{capability::internal_ram_kib, Capability},
+    glyphs::Glyph,
+};
+
+/// A feature the chips can be filtered by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChipFeature {
+    Wifi,
+    Ble,
+    /// An IEEE 802.15.4 radio, for Thread and Zigbee.
+    Ieee802154,
+    /// A USB OTG or USB Serial/JTAG controller.
+    Usb,
+    Psram,
+    DualCore,
+}
+
+impl ChipFeature {
+    pub const ALL: &'static [ChipFeature] = &[
+        ChipFeature::Wifi,
+        ChipFeature::Ble,
+        ChipFeature::Ieee802154,
+        ChipFeature::Usb,
+        ChipFeature::Psram,
+        ChipFeature::DualCore,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            ChipFeature::Wifi => "Wi-Fi",
+            ChipFeature::Ble => "BLE",
+            ChipFeature::Ieee802154 => "802.15.4",
+            ChipFeature::Usb => "USB",
+            ChipFeature::Psram => "PSRAM",
+            ChipFeature::DualCore => "Dual core",
+        }
+    }
+
+    pub fn supported_by(self, chip: Chip) -> bool {
+        let config = Config::for_chip(&chip);
+        match self {
+            ChipFeature::Wifi => Capability::Wifi.supported_by(chip),
+            ChipFeature::Ble => Capability::Bt.supported_by(chip),
+            ChipFeature::Ieee802154 => config.contains("ieee802154"),
+            ChipFeature::Usb => config.contains("usb0") || config.contains("usb_device"),
+            ChipFeature::Psram => Capability::Psram.supported_by(chip),
+            ChipFeature::DualCore => config.cores() == Cores::Multi,
+        }
+    }
+}
+
+/// The chips having all of the required features.
+pub fn chips_with(required: &[ChipFeature]) -> Vec<Chip> {
+    Chip::value_variants()
+        .iter()
+        .copied()
+        .filter(|&chip| required.iter().all(|feature| feature.supported_by(chip)))
+        .collect()
+}
+
+/// The cells of the comparison table row of a chip.
+fn row(chip: Chip) -> [String; 7] {
+    let config = Config::for_chip(&chip);
+    let yes_no = |feature: ChipFeature| {
+        if feature.supported_by(chip) {
+            Glyph::Supported.to_string()
+        } else {
+            "-".to_string()
+        }
+    };
+    let usb = match (config.contains("usb0"), config.contains("usb_device")) {
+        (true, true) => "OTG, Serial/JTAG",
+        (true, false) => "OTG",
+        (false, true) => "Serial/JTAG",
+        (false, false) => "-",
+    };
+
+    [
+        chip.pretty_name().to_string(),
+        match config.cores() {
+            Cores::Single => "1".to_string(),
+            Cores::Multi => "2".to_string(),
+        },
+        internal_ram_kib(chip).map_or("?".to_string(), |size| format!("{size} KiB")),
+        format!(
+            "{} / {} / {}",
+            yes_no(ChipFeature::Wifi),
+            yes_no(ChipFeature::Ble),
+            yes_no(ChipFeature::Ieee802154)
+        ),
+        usb.to_string(),
+        yes_no(ChipFeature::Psram),
+        chip.target().to_string(),
+    ]
+}
+
+/// Lets the user pick a chip from a comparison table, filtered by the
+/// features they need.
+pub struct ChipPicker {
+    keymap: Keymap,
+    required: Vec<ChipFeature>,
+    state: TableState,
+}
+
+impl ChipPicker {
+    pub fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            required: Vec::new(),
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn chips(&self) -> Vec<Chip> {
+        chips_with(&self.required)
+    }
+
+    fn toggle_filter(&mut self, feature: ChipFeature) {
+        if let Some(i) = self.required.iter().position(|f| *f == feature) {
+            self.required.remove(i);
+        } else {
+            self.required.push(feature);
+        }
+        self.state.select(Some(0));
+    }
+
+    /// Run the picker, returning the selected chip or `None` if cancelled.
+    pub fn run(&mut self, mut terminal: Terminal<impl Backend>) -> AppResult<Option<Chip>> {
+        loop {
+            terminal.draw(|f| f.render_widget(&mut *self, f.area()))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            // The digits toggle the filters, in the order they are listed
+            if let KeyCode::Char(c @ '1'..='9') = key.code {
+                let index = c as usize - '1' as usize;
+                if let Some(&feature) = ChipFeature::ALL.get(index) {
+                    self.toggle_filter(feature);
+                }
+                continue;
+            }
+
+            if key.code == KeyCode::Esc {
+                return Ok(None);
+            }
+
+            match self.keymap.action(&key) {
+                Some(Action::Up) => self.state.select_previous(),
+                Some(Action::Down) => self.state.select_next(),
+                Some(Action::Select) => {
+                    let chips = self.chips();
+                    if let Some(&chip) = self.state.selected().and_then(|i| chips.get(i)) {
+                        return Ok(Some(chip));
+                    }
+                }
+                Some(Action::Quit) => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Widget for &mut ChipPicker {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [header_area, filter_area, table_area, footer_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .areas(area);
+
+        Paragraph::new("esp-generate\nSelect a chip")
+            .bold()
+            .centered()
+            .render(header_area, buf);
+
+        let filters = ChipFeature::ALL
+            .iter()
+            .enumerate()
+            .flat_map(|(i, feature)| {
+                let style = if self.required.contains(feature) {
+                    Style::default().fg(SELECTED_STYLE_FG).bold()
+                } else {
+                    Style::default().fg(DISABLED_STYLE_FG)
+                };
+                [
+                    Span::raw(format!(" {} ", i + 1)),
+                    Span::styled(feature.title(), style),
+                    Span::raw(" "),
+                ]
+            });
+        Line::from_iter(std::iter::once(Span::raw(" Needs:")).chain(filters))
+            .render(filter_area, buf);
+
+        let rows = self.chips().into_iter().map(|chip| Row::new(row(chip)));
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(10),
+                Constraint::Length(6),
+                Constraint::Length(9),
+                Constraint::Length(22),
+                Constraint::Length(16),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new([
+                "Chip",
+                "Cores",
+                "RAM",
+                "Wi-Fi / BLE / 802.15.4",
+                "USB",
+                "PSRAM",
+                "Target",
+            ])
+            .bold(),
+        )
+        .fg(TEXT_COLOR)
+        .bg(NORMAL_ROW_COLOR)
+        .row_highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .add_modifier(Modifier::REVERSED)
+                .fg(SELECTED_STYLE_FG),
+        )
+        .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(table, table_area, buf, &mut self.state);
+
+        let keys = |action| self.keymap.keys(action);
+        Paragraph::new(format!(
+            "Use {}, {} to move, 1-{} to toggle a required feature, {} to select the chip, ESC/{} to cancel",
+            keys(Action::Down),
+            keys(Action::Up),
+            ChipFeature::ALL.len(),
+            keys(Action::Select),
+            keys(Action::Quit),
+        ))
+        .centered()
+        .wrap(Wrap { trim: true })
+        .render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chip_picker_filter() {
+        assert_eq!(chips_with(&[]).len(), 7);
+        assert_eq!(
+            chips_with(&[ChipFeature::Ble, ChipFeature::Usb]),
+            [Chip::Esp32c3, Chip::Esp32c6, Chip::Esp32h2, Chip::Esp32s3]
+        );
+        assert_eq!(
+            chips_with(&[ChipFeature::Wifi, ChipFeature::Ieee802154]),
+            [Chip::Esp32c6]
+        );
+        assert_eq!(
+            chips_with(&[ChipFeature::DualCore, ChipFeature::Psram]),
+            [Chip::Esp32, Chip::Esp32s3]
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use std::{collections::BTreeMap, fmt, fs, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::glyphs::Glyph;
+
+/// An action of the TUI which can be bound to keys. ESC is always bound to
+/// going up and cancelling, in addition to the configured keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    /// Move to the previous item.
+    Up,
+    /// Move to the next item.
+    Down,
+    /// Go up to the parent category.
+    Back,
+    /// Enter a category, toggle an option or edit a value.
+    Select,
+    /// Select all options of the current category, or deselect them if all
+    /// those that can be selected already are.
+    ToggleAll,
+    /// Reset the options of the current category.
+    Reset,
+    /// Reset all options.
+    Clear,
+    /// Save the selection and generate the project.
+    Save,
+    /// Cancel without generating the project.
+    Quit,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Back => "back",
+            Action::Select => "select",
+            Action::ToggleAll => "toggle-all",
+            Action::Reset => "reset",
+            Action::Clear => "clear",
+            Action::Save => "save",
+            Action::Quit => "quit",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A set of default key bindings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    /// `hjkl` and the arrow keys.
+    #[default]
+    Vim,
+    /// The arrow keys only.
+    Arrows,
+    /// `C-p`, `C-n`, `C-b` and `C-f`, and the arrow keys.
+    Emacs,
+}
+
+/// A key, optionally pressed along with Ctrl or Alt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Parse a key like `k`, `Up`, `Space`, `F2` or `Ctrl-s`.
+    pub fn parse(key: &str) -> Result<Self, String> {
+        let (modifiers, name) = if let Some(name) = key.strip_prefix("Ctrl-") {
+            (KeyModifiers::CONTROL, name)
+        } else if let Some(name) = key.strip_prefix("Alt-") {
+            (KeyModifiers::ALT, name)
+        } else {
+            (KeyModifiers::NONE, key)
+        };
+
+        let code = match name {
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Enter" => KeyCode::Enter,
+            "Space" => KeyCode::Char(' '),
+            "Tab" => KeyCode::Tab,
+            "Backspace" => KeyCode::Backspace,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_graphic() => KeyCode::Char(c),
+                    _ => match name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+                        Some(n @ 1..=12) => KeyCode::F(n),
+                        _ => return Err(format!("Unknown key '{key}'")),
+                    },
+                }
+            }
+        };
+
+        Ok(Self { code, modifiers })
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        // Shift is implied by the character itself, e.g. `S`
+        let modifiers = event.modifiers - KeyModifiers::SHIFT;
+        self.code == event.code && self.modifiers == modifiers
+    }
+}
+
+impl From<&KeyEvent> for Key {
+    fn from(event: &KeyEvent) -> Self {
+        Self {
+            code: event.code,
+            modifiers: event.modifiers - KeyModifiers::SHIFT,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "C-")?;
+        } else if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "M-")?;
+        }
+
+        match self.code {
+            KeyCode::Up => write!(f, "{}", Glyph::Up),
+            KeyCode::Down => write!(f, "{}", Glyph::Down),
+            KeyCode::Left => write!(f, "{}", Glyph::Left),
+            KeyCode::Right => write!(f, "{}", Glyph::Right),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code}"),
+        }
+    }
+}
+
+/// The contents of the `keybindings.toml` user config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeymapConfig {
+    #[serde(default)]
+    preset: Preset,
+    /// Keys replacing those of the preset, per action.
+    #[serde(default)]
+    bindings: BTreeMap<Action, Vec<String>>,
+}
+
+/// The keys bound to each action.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<Key>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset(Preset::default())
+    }
+}
+
+impl Keymap {
+    pub fn preset(preset: Preset) -> Self {
+        use KeyCode::*;
+
+        let bindings: [(Action, &[Key]); 6] = match preset {
+            Preset::Vim => [
+                (Action::Up, &[Key::new(Char('k')), Key::new(Up)]),
+                (Action::Down, &[Key::new(Char('j')), Key::new(Down)]),
+                (Action::Back, &[Key::new(Char('h')), Key::new(Left)]),
+                (
+                    Action::Select,
+                    &[
+                        Key::new(Char('l')),
+                        Key::new(Char(' ')),
+                        Key::new(Right),
+                        Key::new(Enter),
+                    ],
+                ),
+                (Action::Save, &[Key::new(Char('s')), Key::new(Char('S'))]),
+                (Action::Quit, &[Key::new(Char('q'))]),
+            ],
+            Preset::Arrows => [
+                (Action::Up, &[Key::new(Up)]),
+                (Action::Down, &[Key::new(Down)]),
+                (Action::Back, &[Key::new(Left), Key::new(Backspace)]),
+                (
+                    Action::Select,
+                    &[Key::new(Right), Key::new(Enter), Key::new(Char(' '))],
+                ),
+                (Action::Save, &[Key::new(F(2))]),
+                (Action::Quit, &[Key::new(F(10))]),
+            ],
+            Preset::Emacs => [
+                (Action::Up, &[Key::ctrl('p'), Key::new(Up)]),
+                (Action::Down, &[Key::ctrl('n'), Key::new(Down)]),
+                (Action::Back, &[Key::ctrl('b'), Key::new(Left)]),
+                (
+                    Action::Select,
+                    &[Key::ctrl('f'), Key::new(Right), Key::new(Enter)],
+                ),
+                (Action::Save, &[Key::ctrl('s')]),
+                (Action::Quit, &[Key::ctrl('g')]),
+            ],
+        };
+
+        // The bulk actions are rare enough to share their keys across presets
+        let common: [(Action, &[Key]); 3] = [
+            (Action::ToggleAll, &[Key::new(Char('a'))]),
+            (Action::Reset, &[Key::new(Char('r'))]),
+            (Action::Clear, &[Key::new(Char('C'))]),
+        ];
+
+        Self {
+            bindings: bindings
+                .into_iter()
+                .chain(common)
+                .map(|(action, keys)| (action, keys.to_vec()))
+                .collect(),
+        }
+    }
+
+    /// The path of the user config file.
+    pub fn path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join("esp-generate")
+                .join("keybindings.toml"),
+        )
+    }
+
+    /// Load the key bindings from the user config file, using the default
+    /// preset if there is none.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Ok(Self::default());
+        };
+
+        Self::parse(&contents).map_err(|error| format!("{}: {error}", path.display()))
+    }
+
+    /// Parse and validate the contents of a `keybindings.toml` file.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let config: KeymapConfig =
+            toml_edit::de::from_str(contents).map_err(|error| error.to_string())?;
+
+        let mut keymap = Self::preset(config.preset);
+        for (action, keys) in config.bindings {
+            let keys = keys
+                .iter()
+                .map(|key| Key::parse(key))
+                .collect::<Result<Vec<_>, _>>()?;
+            if keys.is_empty() {
+                return Err(format!("No keys bound to {action}"));
+            }
+            keymap.bindings.insert(action, keys);
+        }
+
+        // A key bound to several actions would only ever trigger the first one
+        for (action, keys) in &keymap.bindings {
+            for key in keys {
+                if let Some((other, _)) = keymap
+                    .bindings
+                    .iter()
+                    .find(|(other, keys)| *other < action && keys.contains(key))
+                {
+                    return Err(format!("Key '{key}' is bound to both {other} and {action}"));
+                }
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// The action bound to the key, if any.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// The keys bound to the action, as shown in the footer, e.g. `k/↑`.
+    pub fn keys(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .into_iter()
+            .flatten()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        let vim = Keymap::default();
+        assert_eq!(vim.action(&key(KeyCode::Char('j'))), Some(Action::Down));
+        assert_eq!(
+            vim.action(&KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT)),
+            Some(Action::Save)
+        );
+        assert_eq!(vim.keys(Action::Up), "k/↑");
+
+        let emacs = Keymap::parse("preset = \"emacs\"\n").unwrap();
+        assert_eq!(emacs.action(&ctrl('n')), Some(Action::Down));
+        assert_eq!(emacs.action(&key(KeyCode::Char('j'))), None);
+        assert_eq!(emacs.keys(Action::Save), "C-s");
+
+        let custom =
+            Keymap::parse("preset = \"arrows\"\n[bindings]\nsave = [\"Ctrl-w\", \"F5\"]\n")
+                .unwrap();
+        assert_eq!(custom.action(&ctrl('w')), Some(Action::Save));
+        assert_eq!(custom.action(&key(KeyCode::F(2))), None);
+        assert_eq!(custom.keys(Action::Save), "C-w/F5");
+
+        assert!(Keymap::parse("preset = \"nano\"\n").is_err());
+        assert!(Keymap::parse("[bindings]\nsave = [\"Hyper-s\"]\n").is_err());
+        assert!(Keymap::parse("[bindings]\nsave = []\n").is_err());
+        // `j` is already bound to moving down
+        assert!(Keymap::parse("[bindings]\nquit = [\"j\"]\n").is_err());
+    }
+}
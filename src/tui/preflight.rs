@@ -0,0 +1,110 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{
+    keymap::{Action, Keymap},
+    AppResult, NORMAL_ROW_COLOR, TEXT_COLOR,
+};
+use crate::check::CheckResult;
+
+/// Lists the tools which are missing or too old before options are
+/// selected, letting the user fix them first or continue anyway.
+pub struct Preflight {
+    keymap: Keymap,
+    failures: Vec<CheckResult>,
+}
+
+impl Preflight {
+    pub fn new(keymap: Keymap, failures: Vec<CheckResult>) -> Self {
+        Self { keymap, failures }
+    }
+
+    /// Show the failures, returning whether to continue.
+    pub fn run(&mut self, mut terminal: Terminal<impl Backend>) -> AppResult<bool> {
+        loop {
+            terminal.draw(|f| f.render_widget(&*self, f.area()))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if key.code == KeyCode::Esc {
+                return Ok(false);
+            }
+
+            match self.keymap.action(&key) {
+                Some(Action::Select) => return Ok(true),
+                Some(Action::Quit) => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Widget for &Preflight {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [header_area, list_area, footer_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Length(2),
+        ])
+        .areas(area);
+
+        Paragraph::new(
+            "esp-generate\nSome tools needed to build or flash the project are missing or outdated",
+        )
+        .bold()
+        .centered()
+        .wrap(Wrap { trim: true })
+        .render(header_area, buf);
+
+        let items = self
+            .failures
+            .iter()
+            .map(|failure| ListItem::new(format!(" {failure}")));
+        Widget::render(
+            List::new(items).fg(TEXT_COLOR).bg(NORMAL_ROW_COLOR),
+            list_area,
+            buf,
+        );
+
+        Paragraph::new(format!(
+            "Fix them and run esp-generate again, or press {} to continue anyway. ESC/{} to quit",
+            self.keymap.keys(Action::Select),
+            self.keymap.keys(Action::Quit),
+        ))
+        .centered()
+        .wrap(Wrap { trim: true })
+        .render(footer_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::Status;
+
+    #[test]
+    fn test_preflight() {
+        let failure = CheckResult {
+            status: Status::Error,
+            message: "espflash not found".to_string(),
+        };
+        assert!(!failure.is_ok());
+
+        let preflight = Preflight::new(Keymap::default(), vec![failure]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 100, 10));
+        (&preflight).render(buf.area, &mut buf);
+
+        let text = buf
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(text.contains("espflash not found"));
+        assert!(text.contains("to continue anyway"));
+    }
+}
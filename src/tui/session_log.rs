@@ -0,0 +1,114 @@
+//! A plain text transcript of a TUI session, written with `--log-session`.
+//!
+//! Rather than the raw terminal output, every key press is recorded with
+//! where the cursor was and how it changed the selection, followed by the
+//! final selection. This is what a bug report about the options or their
+//! constraints needs, and reads without replaying it.
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
+
+use crossterm::event::KeyEvent;
+use esp_metadata::Chip;
+
+use super::keymap::Key;
+
+/// The transcript being written, line by line, so that it is complete up to
+/// the last key even if esp-generate does not exit normally.
+pub struct SessionLog {
+    out: Box<dyn Write>,
+}
+
+impl SessionLog {
+    /// Create the transcript at `path`, starting with the chip and the
+    /// options selected on the command line.
+    pub fn create(path: &Path, chip: Chip, selected: &[String]) -> io::Result<Self> {
+        Self::new(Box::new(File::create(path)?), chip, selected)
+    }
+
+    fn new(mut out: Box<dyn Write>, chip: Chip, selected: &[String]) -> io::Result<Self> {
+        writeln!(
+            out,
+            "esp-generate {} session for the {}",
+            env!("CARGO_PKG_VERSION"),
+            chip.pretty_name()
+        )?;
+        writeln!(out, "initial selection: {}", selected.join(" "))?;
+
+        Ok(Self { out })
+    }
+
+    /// Record a key pressed at `location`, which changed the selection from
+    /// `before` to `after`.
+    pub fn key(&mut self, key: &KeyEvent, location: &str, before: &[String], after: &[String]) {
+        let mut line = format!("{:<8} {location}", Key::from(key).to_string());
+
+        let added = after.iter().filter(|option| !before.contains(option));
+        let removed = before.iter().filter(|option| !after.contains(option));
+        let changes = added
+            .map(|option| format!("+{option}"))
+            .chain(removed.map(|option| format!("-{option}")))
+            .collect::<Vec<_>>();
+        if !changes.is_empty() {
+            line.push_str(&format!(" => {}", changes.join(" ")));
+        }
+
+        self.write(&line);
+    }
+
+    /// Record how the session ended: with the selected options, or `None` if
+    /// the user quit.
+    pub fn finish(&mut self, selected: Option<&[String]>) {
+        match selected {
+            Some(selected) => self.write(&format!("saved: {}", selected.join(" "))),
+            None => self.write("quit without saving"),
+        }
+    }
+
+    /// Write a line, ignoring failures: the transcript must not end the
+    /// session.
+    fn write(&mut self, line: &str) {
+        let _ = writeln!(self.out, "{line}");
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crossterm::event::KeyCode;
+
+    use super::*;
+
+    #[test]
+    fn test_session_log() {
+        let options = |options: &[&str]| options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        let path = env::temp_dir().join(format!("esp-generate-session-{}.txt", process::id()));
+        let mut log = SessionLog::create(&path, Chip::Esp32c3, &options(&["log"])).unwrap();
+        log.key(
+            &KeyEvent::from(KeyCode::Char(' ')),
+            "ESP32-C3 ▸ alloc",
+            &options(&["log"]),
+            &options(&["alloc", "unstable-hal"]),
+        );
+        log.key(&KeyEvent::from(KeyCode::Down), "ESP32-C3 ▸ alloc", &[], &[]);
+        log.finish(Some(&options(&["alloc", "unstable-hal"])));
+        drop(log);
+
+        let transcript = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines = transcript.lines().collect::<Vec<_>>();
+        assert!(lines[0].contains("session for the ESP32-C3"));
+        assert_eq!(lines[1], "initial selection: log");
+        assert_eq!(
+            lines[2],
+            "Space    ESP32-C3 ▸ alloc => +alloc +unstable-hal -log"
+        );
+        assert_eq!(lines[3], "↓        ESP32-C3 ▸ alloc");
+        assert_eq!(lines[4], "saved: alloc unstable-hal");
+    }
+}
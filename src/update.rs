@@ -0,0 +1,116 @@
+//! Checking crates.io for a new version of the application, and installing
+//! it.
+//!
+//! The check runs in the background while the application does its work, and
+//! is only reported at the end, so a slow or unreachable crates.io never
+//! delays the TUI.
+
+use std::{
+    error::Error,
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use update_informer::{registry, Check};
+
+use crate::glyphs::Glyph;
+
+/// How long the latest version found on crates.io is remembered, in the cache
+/// directory, before asking crates.io again.
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long to wait for a response from crates.io.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long to wait for a check still running when reporting its result.
+const REPORT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// An update check running in the background.
+pub struct UpdateCheck {
+    name: &'static str,
+    result: mpsc::Receiver<Option<String>>,
+}
+
+/// Start checking crates.io for a new version of the application.
+pub fn spawn(name: &'static str, version: &'static str) -> UpdateCheck {
+    let (sender, result) = mpsc::channel();
+
+    thread::spawn(move || {
+        let informer = update_informer::new(registry::Crates, name, version)
+            .interval(CHECK_INTERVAL)
+            .timeout(REQUEST_TIMEOUT);
+        let latest = informer.check_version().ok().flatten();
+
+        // The receiver is gone if the application finished first
+        let _ = sender.send(latest.map(|version| version.to_string()));
+    });
+
+    UpdateCheck { name, result }
+}
+
+impl UpdateCheck {
+    /// Print a notice if a new version is available. A check which has not
+    /// finished shortly after is abandoned.
+    pub fn report(self) {
+        if let Ok(Some(version)) = self.result.recv_timeout(REPORT_TIMEOUT) {
+            log::warn!(
+                "{} A new version of {} is available: {version}",
+                Glyph::NewVersion,
+                self.name
+            );
+        }
+    }
+}
+
+/// Install the latest version of the application from crates.io, using a
+/// prebuilt binary through `cargo binstall` when available.
+pub fn self_update(name: &str, version: &str) -> Result<(), Box<dyn Error>> {
+    // Ask crates.io directly, the cached version may be outdated
+    let informer = update_informer::new(registry::Crates, name, version)
+        .interval(Duration::ZERO)
+        .timeout(REQUEST_TIMEOUT);
+    let latest = informer
+        .check_version()
+        .map_err(|error| format!("Failed to check crates.io for the latest version: {error}"))?;
+    let Some(latest) = latest else {
+        log::info!("{name} {version} is the latest version");
+        return Ok(());
+    };
+    let latest = latest.to_string();
+    let latest = latest.trim_start_matches('v');
+
+    // Prebuilt binaries are built with the default features only
+    let features = [
+        ("serve", cfg!(feature = "serve")),
+        ("dev", cfg!(feature = "dev")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect::<Vec<_>>();
+    let binstall = features.is_empty()
+        && Command::new("cargo")
+            .args(["binstall", "-V"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+
+    let mut command = Command::new("cargo");
+    if binstall {
+        command.args(["binstall", "--no-confirm", &format!("{name}@{latest}")]);
+    } else {
+        command.args(["install", "--locked", name, "--version", latest]);
+        if !features.is_empty() {
+            command.args(["--features", &features.join(",")]);
+        }
+    }
+
+    log::info!("Updating {name} from {version} to {latest}");
+    if !command.status()?.success() {
+        return Err(format!("Failed to install {name} {latest}").into());
+    }
+
+    Ok(())
+}
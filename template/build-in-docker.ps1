@@ -0,0 +1,34 @@
+#INCLUDEFILE docker-build
+# Builds the release firmware image with the pinned tools of Dockerfile.build.
+# Dependencies are pinned by Cargo.lock, which must be committed.
+# The PowerShell equivalent of build-in-docker.sh.
+
+$ErrorActionPreference = "Stop"
+
+#REPLACE project-name project-name
+$Image = "project-name-build"
+#REPLACE esp32c3 mcu
+$Chip = "esp32c3"
+#REPLACE riscv32imac-unknown-none-elf rust_target
+$Target = "riscv32imac-unknown-none-elf"
+#REPLACE project-name project-name
+$Name = "project-name"
+#IF option("xtensa")
+#+# Puts the Xtensa linker on the PATH
+#+$Environment = ". /export-esp.sh && "
+#ELSE
+$Environment = ""
+#ENDIF
+
+Get-Content -Raw Dockerfile.build | docker build --tag $Image -
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+
+docker run --rm `
+    --volume "${PWD}:/project" `
+    --workdir /project `
+    --env CARGO_HOME=/project/target/docker-cargo-home `
+    $Image `
+    bash -c "${Environment}cargo build --release --locked && espflash save-image --chip $Chip target/$Target/release/$Name $Name.bin"
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+
+Write-Output "Built $Name.bin"
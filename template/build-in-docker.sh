@@ -0,0 +1,33 @@
+#INCLUDEFILE docker-build
+#!/usr/bin/env bash
+# Builds the release firmware image with the pinned tools of Dockerfile.build.
+# Dependencies are pinned by Cargo.lock, which must be committed.
+
+set -euo pipefail
+
+#REPLACE project-name project-name
+IMAGE="project-name-build"
+#REPLACE esp32c3 mcu
+CHIP="esp32c3"
+#REPLACE riscv32imac-unknown-none-elf rust_target
+TARGET="riscv32imac-unknown-none-elf"
+#REPLACE project-name project-name
+NAME="project-name"
+#IF option("xtensa")
+#+# Puts the Xtensa linker on the PATH
+#+ENVIRONMENT=". /export-esp.sh && "
+#ELSE
+ENVIRONMENT=""
+#ENDIF
+
+docker build --tag "$IMAGE" - <Dockerfile.build
+
+docker run --rm \
+    --user "$(id -u):$(id -g)" \
+    --volume "$PWD:/project" \
+    --workdir /project \
+    --env CARGO_HOME=/project/target/docker-cargo-home \
+    "$IMAGE" \
+    bash -c "${ENVIRONMENT}cargo build --release --locked && espflash save-image --chip $CHIP target/$TARGET/release/$NAME $NAME.bin"
+
+echo "Built $NAME.bin"
@@ -2,6 +2,105 @@ fn main() {
     //IF option("probe-rs")
     println!("cargo:rustc-link-arg=-Tdefmt.x");
     //ENDIF
+    //IF option("ui-slint")
+    slint_build::compile_with_config(
+        "ui/main.slint",
+        slint_build::CompilerConfiguration::new()
+            .embed_resources(slint_build::EmbedResourcesKind::EmbedForSoftwareRenderer),
+    )
+    .unwrap();
+    //ENDIF
+    //IF option("c-interop")
+    // The C code of src/bridge.rs, built with the GCC of the chip's toolchain
+    println!("cargo:rerun-if-changed=csrc");
+    cc::Build::new()
+        //IF option("riscv")
+        .compiler("riscv32-esp-elf-gcc")
+        //ELSE
+        //REPLACE esp32 mcu
+        //+.compiler("xtensa-esp32-elf-gcc")
+        //+.flag("-mlongcalls")
+        //ENDIF
+        .include("csrc")
+        .file("csrc/bridge.c")
+        //IF option("c-logging")
+        .file("csrc/log.c")
+        //ENDIF
+        .compile("bridge");
+    //ENDIF
+    //IF option("panic-policy") || option("app-config")
+    // The settings of the application, `APP_CONFIG_*` in .cargo/config.toml.
+    // esp-config rejects the `APP_CONFIG_*` variables it is not given, so they
+    // are all declared in this one call
+    esp_config::generate_config(
+        "app",
+        &[
+            //IF option("panic-policy")
+            (
+                "panic-policy",
+                "What to do after a panic",
+                esp_config::Value::String("reboot".to_string()),
+                Some(esp_config::Validator::Enumeration(vec![
+                    "halt".to_string(),
+                    "reboot".to_string(),
+                    //IF option("has-deep-sleep")
+                    "deep-sleep".to_string(),
+                    //ENDIF
+                ])),
+            ),
+            (
+                "panic-delay-ms",
+                "How long to wait after a panic before rebooting, in milliseconds",
+                esp_config::Value::Integer(5000),
+                Some(esp_config::Validator::NonNegativeInteger),
+            ),
+            //ENDIF
+            //IF option("app-config")
+            (
+                "interval-ms",
+                "How often the main loop runs, in milliseconds",
+                esp_config::Value::Integer(1000),
+                Some(esp_config::Validator::PositiveInteger),
+            ),
+            (
+                "threshold",
+                "The level, in percent, above which the application reacts",
+                esp_config::Value::Integer(80),
+                Some(esp_config::Validator::IntegerInRange(0..101)),
+            ),
+            (
+                "endpoint-url",
+                "Where the application reports to",
+                esp_config::Value::String("http://example.com/".to_string()),
+                None,
+            ),
+            //ENDIF
+        ],
+        false,
+    );
+    //ENDIF
+    //IF option("map-file")
+    // The linker map of release builds, next to the firmware, summarized by
+    // scripts/map-summary.py
+    if std::env::var("PROFILE").as_deref() == Ok("release") {
+        // OUT_DIR is target/<target>/<profile>/build/<package>-<hash>/out
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let profile_dir = std::path::Path::new(&out_dir).ancestors().nth(3).unwrap();
+        //REPLACE project-name project-name
+        let map = profile_dir.join("project-name.map");
+        //IF option("xtensa")
+        // Linked by GCC, which passes the option on to the linker
+        //+println!("cargo:rustc-link-arg-bins=-Wl,-Map={}", map.display());
+        //ELSE
+        println!("cargo:rustc-link-arg-bins=-Map={}", map.display());
+        //ENDIF
+    }
+    //ENDIF
+    //IF option("flip-link")
+    // linkall.x must be the last linker script: with the `flip-link` feature of
+    // esp-hal, it sizes the stack from the sections placed before it
+    //ELSE
     // make sure linkall.x is the last linker script (otherwise might cause problems with flip-link)
+    //ENDIF
     println!("cargo:rustc-link-arg=-Tlinkall.x");
 }
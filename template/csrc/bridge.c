@@ -0,0 +1,24 @@
+//INCLUDEFILE c-interop
+#include "bridge.h"
+//IF option("c-logging")
+#include "log.h"
+
+static const char *TAG = "bridge";
+//ENDIF
+
+uint16_t bridge_checksum(const uint8_t *data, size_t len)
+{
+    //IF option("c-logging")
+    ESP_LOGI(TAG, "Computing the checksum of %u bytes", (unsigned)len);
+
+    //ENDIF
+    uint16_t sum1 = 0;
+    uint16_t sum2 = 0;
+
+    for (size_t i = 0; i < len; i++) {
+        sum1 = (sum1 + data[i]) % 255;
+        sum2 = (sum2 + sum1) % 255;
+    }
+
+    return (uint16_t)((sum2 << 8) | sum1);
+}
@@ -0,0 +1,15 @@
+//INCLUDEFILE c-interop
+// The C functions called from Rust through src/bridge.rs.
+//
+// Run scripts/bindgen.sh after changing this header to regenerate the Rust
+// declarations in src/bridge/bindings.rs.
+#ifndef BRIDGE_H
+#define BRIDGE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The Fletcher-16 checksum of `len` bytes at `data`.
+uint16_t bridge_checksum(const uint8_t *data, size_t len);
+
+#endif
@@ -0,0 +1,203 @@
+//INCLUDEFILE c-logging
+#include <stdbool.h>
+#include <stdint.h>
+
+#include "log.h"
+
+// The longest message passed to Rust, including room for a terminating null
+#define LOG_BUFFER_SIZE 256
+
+struct buffer {
+    char *data;
+    size_t len;
+    size_t capacity;
+};
+
+static void push(struct buffer *buffer, char c)
+{
+    if (buffer->len < buffer->capacity - 1) {
+        buffer->data[buffer->len++] = c;
+    }
+}
+
+static void push_padded(struct buffer *buffer, const char *s, size_t len, unsigned width, bool left,
+                        char pad)
+{
+    size_t padding = width > len ? width - len : 0;
+
+    if (!left) {
+        for (size_t i = 0; i < padding; i++) {
+            push(buffer, pad);
+        }
+    }
+    for (size_t i = 0; i < len; i++) {
+        push(buffer, s[i]);
+    }
+    if (left) {
+        for (size_t i = 0; i < padding; i++) {
+            push(buffer, ' ');
+        }
+    }
+}
+
+// Writes the digits of `value` before `end`, returning the first one
+static char *format_unsigned(char *end, unsigned long long value, unsigned base, bool upper)
+{
+    const char *symbols = upper ? "0123456789ABCDEF" : "0123456789abcdef";
+
+    do {
+        *--end = symbols[value % base];
+        value /= base;
+    } while (value != 0);
+
+    return end;
+}
+
+static void format_message(struct buffer *buffer, const char *format, va_list args)
+{
+    for (const char *p = format; *p != '\0'; p++) {
+        if (*p != '%') {
+            push(buffer, *p);
+            continue;
+        }
+        p++;
+
+        bool left = false;
+        char pad = ' ';
+        for (;; p++) {
+            if (*p == '-') {
+                left = true;
+            } else if (*p == '0') {
+                pad = '0';
+            } else {
+                break;
+            }
+        }
+        if (left) {
+            pad = ' ';
+        }
+
+        unsigned width = 0;
+        while (*p >= '0' && *p <= '9') {
+            width = width * 10 + (unsigned)(*p++ - '0');
+        }
+
+        // Shorter arguments are promoted to `int`, and converted back
+        enum { CHAR, SHORT, INT, LONG, LONG_LONG, SIZE } length = INT;
+        if (*p == 'h') {
+            p++;
+            length = SHORT;
+            if (*p == 'h') {
+                p++;
+                length = CHAR;
+            }
+        } else if (*p == 'l') {
+            p++;
+            length = LONG;
+            if (*p == 'l') {
+                p++;
+                length = LONG_LONG;
+            }
+        } else if (*p == 'z') {
+            p++;
+            length = SIZE;
+        }
+
+        char digits[24];
+        char *end = digits + sizeof(digits);
+        char *start;
+        switch (*p) {
+        case 'd':
+        case 'i': {
+            long long value = length == LONG_LONG ? va_arg(args, long long)
+                              : length == LONG    ? va_arg(args, long)
+                              : length == SIZE    ? (long long)va_arg(args, size_t)
+                                                  : va_arg(args, int);
+            if (length == CHAR) {
+                value = (signed char)value;
+            } else if (length == SHORT) {
+                value = (short)value;
+            }
+            bool negative = value < 0;
+            start = format_unsigned(end, negative ? -(unsigned long long)value : (unsigned long long)value,
+                                    10, false);
+            if (negative && pad == '0') {
+                push(buffer, '-');
+                width = width > 0 ? width - 1 : 0;
+            } else if (negative) {
+                *--start = '-';
+            }
+            push_padded(buffer, start, (size_t)(end - start), width, left, pad);
+            break;
+        }
+        case 'u':
+        case 'x':
+        case 'X': {
+            unsigned long long value = length == LONG_LONG ? va_arg(args, unsigned long long)
+                                       : length == LONG    ? va_arg(args, unsigned long)
+                                       : length == SIZE    ? va_arg(args, size_t)
+                                                           : va_arg(args, unsigned int);
+            if (length == CHAR) {
+                value = (unsigned char)value;
+            } else if (length == SHORT) {
+                value = (unsigned short)value;
+            }
+            start = format_unsigned(end, value, *p == 'u' ? 10 : 16, *p == 'X');
+            push_padded(buffer, start, (size_t)(end - start), width, left, pad);
+            break;
+        }
+        case 'p':
+            start = format_unsigned(end, (uintptr_t)va_arg(args, void *), 16, false);
+            *--start = 'x';
+            *--start = '0';
+            push_padded(buffer, start, (size_t)(end - start), width, left, ' ');
+            break;
+        case 's': {
+            const char *s = va_arg(args, const char *);
+            if (s == NULL) {
+                s = "(null)";
+            }
+            size_t len = 0;
+            while (s[len] != '\0') {
+                len++;
+            }
+            push_padded(buffer, s, len, width, left, ' ');
+            break;
+        }
+        case 'c': {
+            char c = (char)va_arg(args, int);
+            push_padded(buffer, &c, 1, width, left, ' ');
+            break;
+        }
+        case '%':
+            push(buffer, '%');
+            break;
+        case '\0':
+            return;
+        default:
+            push(buffer, '%');
+            push(buffer, *p);
+            break;
+        }
+    }
+}
+
+void esp_log_writev(esp_log_level_t level, const char *tag, const char *format, va_list args)
+{
+    char data[LOG_BUFFER_SIZE];
+    struct buffer buffer = { data, 0, sizeof(data) };
+
+    format_message(&buffer, format, args);
+    buffer.data[buffer.len] = '\0';
+
+    bridge_log(level, tag, buffer.data, buffer.len);
+}
+
+void esp_log_write(esp_log_level_t level, const char *tag, const char *format, ...)
+{
+    va_list args;
+
+    va_start(args, format);
+    esp_log_writev(level, tag, format, args);
+    va_end(args);
+}
@@ -0,0 +1,38 @@
+//INCLUDEFILE c-logging
+// The ESP-IDF logging API, so that C code ported from ESP-IDF logs through the
+// logger of the firmware.
+//
+// Messages are formatted in C, truncated to 255 bytes, and passed to
+// `bridge_log` in src/bridge/logging.rs. The format supports the `d`, `i`,
+// `u`, `x`, `X`, `c`, `s` and `p` conversions, with the `-` and `0` flags,
+// a width and the `h`, `l`, `ll` and `z` length modifiers.
+#ifndef LOG_H
+#define LOG_H
+
+#include <stdarg.h>
+#include <stddef.h>
+
+// Keep in sync with the levels of src/bridge/logging.rs
+typedef enum {
+    ESP_LOG_NONE,
+    ESP_LOG_ERROR,
+    ESP_LOG_WARN,
+    ESP_LOG_INFO,
+    ESP_LOG_DEBUG,
+    ESP_LOG_VERBOSE,
+} esp_log_level_t;
+
+void esp_log_write(esp_log_level_t level, const char *tag, const char *format, ...)
+    __attribute__((format(printf, 3, 4)));
+void esp_log_writev(esp_log_level_t level, const char *tag, const char *format, va_list args);
+
+#define ESP_LOGE(tag, format, ...) esp_log_write(ESP_LOG_ERROR, tag, format, ##__VA_ARGS__)
+#define ESP_LOGW(tag, format, ...) esp_log_write(ESP_LOG_WARN, tag, format, ##__VA_ARGS__)
+#define ESP_LOGI(tag, format, ...) esp_log_write(ESP_LOG_INFO, tag, format, ##__VA_ARGS__)
+#define ESP_LOGD(tag, format, ...) esp_log_write(ESP_LOG_DEBUG, tag, format, ##__VA_ARGS__)
+#define ESP_LOGV(tag, format, ...) esp_log_write(ESP_LOG_VERBOSE, tag, format, ##__VA_ARGS__)
+
+// Implemented in Rust: logs the `len` bytes of `message`
+void bridge_log(esp_log_level_t level, const char *tag, const char *message, size_t len);
+
+#endif
@@ -0,0 +1,4 @@
+fn main() {
+    // Builds ESP-IDF, and passes its linker arguments and configuration on
+    embuild::espidf::sysenv::output();
+}
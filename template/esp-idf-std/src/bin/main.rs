@@ -0,0 +1,83 @@
+use std::{thread, time::Duration};
+
+//IF option("wifi")
+use esp_idf_svc::{
+    eventloop::EspSystemEventLoop,
+    hal::prelude::Peripherals,
+    nvs::EspDefaultNvsPartition,
+    sys::EspError,
+    wifi::{AuthMethod, BlockingWifi, ClientConfiguration, Configuration, EspWifi},
+};
+//ENDIF
+use log::info;
+//IF option("wifi")
+use log::warn;
+//ENDIF
+
+fn main() {
+    //REPLACE generate-version generate-version
+    // generator version: generate-version
+
+    // Links the patches esp-idf-sys applies to the ESP-IDF runtime, some of
+    // them are otherwise left out, see https://github.com/esp-rs/esp-idf-template/issues/71
+    esp_idf_svc::sys::link_patches();
+
+    // Forwards the `log` crate to the ESP-IDF logging facilities
+    esp_idf_svc::log::EspLogger::initialize_default();
+
+    //IF option("wifi")
+    let peripherals = Peripherals::take().unwrap();
+    let sys_loop = EspSystemEventLoop::take().unwrap();
+    let nvs = EspDefaultNvsPartition::take().unwrap();
+
+    let mut wifi = BlockingWifi::wrap(
+        EspWifi::new(peripherals.modem, sys_loop.clone(), Some(nvs)).unwrap(),
+        sys_loop,
+    )
+    .unwrap();
+    connect_wifi(&mut wifi).unwrap();
+
+    //ENDIF
+    loop {
+        info!("Hello world!");
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    // for inspiration have a look at the examples at https://github.com/esp-rs/esp-idf-svc/tree/v0.51.0/examples
+}
+
+//IF option("wifi")
+/// The network to connect to, set in .cargo/config.toml
+const WIFI_SSID: &str = env!("WIFI_SSID");
+const WIFI_PASSWORD: &str = env!("WIFI_PASSWORD");
+
+/// Starts Wi-Fi in station mode and connects to the configured network, if
+/// any, waiting until an IP address was obtained.
+fn connect_wifi(wifi: &mut BlockingWifi<EspWifi<'static>>) -> Result<(), EspError> {
+    wifi.set_configuration(&Configuration::Client(ClientConfiguration {
+        ssid: WIFI_SSID.try_into().unwrap(),
+        password: WIFI_PASSWORD.try_into().unwrap(),
+        auth_method: if WIFI_PASSWORD.is_empty() {
+            AuthMethod::None
+        } else {
+            AuthMethod::WPA2Personal
+        },
+        ..Default::default()
+    }))?;
+    wifi.start()?;
+    info!("Wi-Fi started");
+
+    if WIFI_SSID.is_empty() {
+        warn!("WIFI_SSID is not set in .cargo/config.toml, not connecting");
+        return Ok(());
+    }
+
+    wifi.connect()?;
+    wifi.wait_netif_up()?;
+
+    let ip_info = wifi.wifi().sta_netif().get_ip_info()?;
+    info!("Wi-Fi connected, IP address: {}", ip_info.ip);
+
+    Ok(())
+}
+//ENDIF
@@ -0,0 +1,44 @@
+//INCLUDEFILE examples
+//! Blinks an LED, run with `cargo run --example blinky`.
+//!
+//! The LED of most Espressif DevKits is an addressable RGB LED, which does not
+//! light up when driving its pin high. Connect a plain LED, with a resistor in
+//! series, to this pin or change it to match your board.
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_hal::{
+    clock::CpuClock,
+    delay::Delay,
+    gpio::{Level, Output},
+    main,
+};
+
+//IF option("probe-rs")
+//+ use defmt_rtt as _;
+//+ use defmt::info;
+//ELSE
+use log::info;
+//ENDIF
+
+#[main]
+fn main() -> ! {
+    let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
+    let peripherals = esp_hal::init(config);
+
+    //IF !option("probe-rs")
+    esp_println::logger::init_logger_from_env();
+    //ENDIF
+
+    //REPLACE 8 pin-led
+    let mut led = Output::new(peripherals.GPIO8, Level::Low);
+    let delay = Delay::new();
+
+    info!("Blinking");
+    loop {
+        led.toggle();
+        delay.delay_millis(500);
+    }
+}
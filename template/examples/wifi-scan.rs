@@ -0,0 +1,55 @@
+//INCLUDEFILE option("examples") && option("wifi")
+//! Lists the Wi-Fi networks in range every few seconds, run with
+//! `cargo run --release --example wifi-scan`.
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_hal::{clock::CpuClock, delay::Delay, main, rng::Rng, timer::timg::TimerGroup};
+use esp_wifi::wifi::{self, WifiStaDevice};
+
+//IF option("probe-rs")
+//+ use defmt_rtt as _;
+//+ use defmt::info;
+//ELSE
+use log::info;
+//ENDIF
+
+extern crate alloc;
+
+#[main]
+fn main() -> ! {
+    let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
+    let peripherals = esp_hal::init(config);
+
+    //REPLACE 72 heap-size
+    esp_alloc::heap_allocator!(72 * 1024);
+
+    //IF !option("probe-rs")
+    esp_println::logger::init_logger_from_env();
+    //ENDIF
+
+    let timg0 = TimerGroup::new(peripherals.TIMG0);
+    let init = esp_wifi::init(timg0.timer0, Rng::new(peripherals.RNG), peripherals.RADIO_CLK)
+        .unwrap();
+    let (_device, mut controller) =
+        wifi::new_with_mode(&init, peripherals.WIFI, WifiStaDevice).unwrap();
+    controller.start().unwrap();
+
+    let delay = Delay::new();
+    loop {
+        let (networks, count) = controller.scan_n::<10>().unwrap();
+        info!("Found {} networks", count);
+        for network in networks {
+            info!(
+                "{} ({} dBm, channel {})",
+                network.ssid.as_str(),
+                network.signal_strength,
+                network.channel
+            );
+        }
+
+        delay.delay_millis(5000);
+    }
+}
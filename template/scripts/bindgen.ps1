@@ -0,0 +1,31 @@
+#INCLUDEFILE c-interop
+# Regenerates src/bridge/bindings.rs from csrc/bridge.h.
+# The PowerShell equivalent of bindgen.sh.
+#
+# Requires `cargo install bindgen-cli` and libclang, see docs/c-interop.md.
+
+$ErrorActionPreference = "Stop"
+
+Set-Location (Join-Path $PSScriptRoot "..")
+
+#IF option("xtensa")
+#+# Upstream clang does not support Xtensa, use the one installed by espup
+#+if (-not $env:LIBCLANG_PATH) {
+#+    Write-Error "LIBCLANG_PATH is not set, run the export-esp.ps1 script of espup"
+#+}
+#+
+#ENDIF
+bindgen csrc/bridge.h `
+    --output src/bridge/bindings.rs `
+    --use-core `
+    --allowlist-function 'bridge_.*' `
+    --no-layout-tests `
+    '--' `
+#IF option("riscv")
+    --target=riscv32-unknown-elf `
+#ELSE
+#REPLACE esp32 mcu
+#+    --target=xtensa-esp-elf -mcpu=esp32 `
+#ENDIF
+    -I csrc
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
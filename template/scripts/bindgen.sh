@@ -0,0 +1,30 @@
+#INCLUDEFILE c-interop
+#!/usr/bin/env bash
+# Regenerates src/bridge/bindings.rs from csrc/bridge.h.
+#
+# Requires `cargo install bindgen-cli` and libclang, see docs/c-interop.md.
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+#IF option("xtensa")
+# Upstream clang does not support Xtensa, use the one installed by espup
+#+if [[ -z "${LIBCLANG_PATH:-}" ]]; then
+#+    echo "LIBCLANG_PATH is not set, source the export-esp.sh script of espup" >&2
+#+    exit 1
+#+fi
+#+
+#ENDIF
+bindgen csrc/bridge.h \
+    --output src/bridge/bindings.rs \
+    --use-core \
+    --allowlist-function 'bridge_.*' \
+    --no-layout-tests \
+    -- \
+#IF option("riscv")
+    --target=riscv32-unknown-elf \
+#ELSE
+#REPLACE esp32 mcu
+#+    --target=xtensa-esp-elf -mcpu=esp32 \
+#ENDIF
+    -I csrc
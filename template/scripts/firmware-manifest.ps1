@@ -0,0 +1,44 @@
+#INCLUDEFILE firmware-manifest
+# Builds the firmware image and describes it in `firmware-manifest.json`, with
+# the version, chip, flash offset, size and SHA-256 OTA update servers need.
+# The PowerShell equivalent of firmware-manifest.sh.
+
+$ErrorActionPreference = "Stop"
+
+#REPLACE esp32c3 mcu
+$Chip = "esp32c3"
+#REPLACE riscv32imac-unknown-none-elf rust_target
+$Target = "riscv32imac-unknown-none-elf"
+#REPLACE project-name project-name
+$Name = "project-name"
+# The factory app partition of espflash's default partition table
+$Offset = "0x10000"
+
+$Version = (Select-String -Path Cargo.toml -Pattern '^version = "(.*)"' | Select-Object -First 1).Matches[0].Groups[1].Value
+$Image = "$Name-$Version.bin"
+
+cargo build --release
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+espflash save-image --chip $Chip "target/$Target/release/$Name" $Image
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+
+$Sha256 = (Get-FileHash -Algorithm SHA256 $Image).Hash.ToLower()
+$Size = (Get-Item $Image).Length
+
+@"
+{
+  "name": "$Name",
+  "version": "$Version",
+  "chip": "$Chip",
+  "images": [
+    {
+      "file": "$Image",
+      "offset": "$Offset",
+      "size": $Size,
+      "sha256": "$Sha256"
+    }
+  ]
+}
+"@ | Set-Content -Encoding ascii firmware-manifest.json
+
+Write-Output "Wrote firmware-manifest.json for $Image"
@@ -0,0 +1,46 @@
+#INCLUDEFILE firmware-manifest
+#!/usr/bin/env bash
+# Builds the firmware image and describes it in `firmware-manifest.json`, with
+# the version, chip, flash offset, size and SHA-256 OTA update servers need.
+
+set -euo pipefail
+
+#REPLACE esp32c3 mcu
+CHIP="esp32c3"
+#REPLACE riscv32imac-unknown-none-elf rust_target
+TARGET="riscv32imac-unknown-none-elf"
+#REPLACE project-name project-name
+NAME="project-name"
+# The factory app partition of espflash's default partition table
+OFFSET="0x10000"
+
+VERSION=$(sed -n 's/^version = "\(.*\)"/\1/p' Cargo.toml | head -n 1)
+IMAGE="$NAME-$VERSION.bin"
+
+cargo build --release
+espflash save-image --chip "$CHIP" "target/$TARGET/release/$NAME" "$IMAGE"
+
+if command -v sha256sum >/dev/null; then
+    SHA256=$(sha256sum "$IMAGE" | cut -d ' ' -f 1)
+else
+    SHA256=$(shasum -a 256 "$IMAGE" | cut -d ' ' -f 1)
+fi
+SIZE=$(wc -c <"$IMAGE" | tr -d ' ')
+
+cat >firmware-manifest.json <<EOF
+{
+  "name": "$NAME",
+  "version": "$VERSION",
+  "chip": "$CHIP",
+  "images": [
+    {
+      "file": "$IMAGE",
+      "offset": "$OFFSET",
+      "size": $SIZE,
+      "sha256": "$SHA256"
+    }
+  ]
+}
+EOF
+
+echo "Wrote firmware-manifest.json for $IMAGE"
@@ -0,0 +1,21 @@
+#INCLUDEFILE manufacturing
+# Builds the factory image: the bootloader, partition table and release
+# firmware merged into a single image, written at offset 0 of every device.
+# The PowerShell equivalent of factory-image.sh.
+
+$ErrorActionPreference = "Stop"
+
+#REPLACE esp32c3 mcu
+$Chip = "esp32c3"
+#REPLACE riscv32imac-unknown-none-elf rust_target
+$Target = "riscv32imac-unknown-none-elf"
+#REPLACE project-name project-name
+$Name = "project-name"
+
+New-Item -ItemType Directory -Force manufacturing | Out-Null
+cargo build --release
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+espflash save-image --chip $Chip --merge "target/$Target/release/$Name" manufacturing/factory.bin
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+
+Write-Output "Wrote manufacturing/factory.bin"
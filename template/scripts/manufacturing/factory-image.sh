@@ -0,0 +1,19 @@
+#INCLUDEFILE manufacturing
+#!/usr/bin/env bash
+# Builds the factory image: the bootloader, partition table and release
+# firmware merged into a single image, written at offset 0 of every device.
+
+set -euo pipefail
+
+#REPLACE esp32c3 mcu
+CHIP="esp32c3"
+#REPLACE riscv32imac-unknown-none-elf rust_target
+TARGET="riscv32imac-unknown-none-elf"
+#REPLACE project-name project-name
+NAME="project-name"
+
+mkdir -p manufacturing
+cargo build --release
+espflash save-image --chip "$CHIP" --merge "target/$TARGET/release/$NAME" manufacturing/factory.bin
+
+echo "Wrote manufacturing/factory.bin"
@@ -0,0 +1,55 @@
+#INCLUDEFILE manufacturing
+# Flashes the factory image, and the device's own data if there is any, to the
+# device connected to the given serial port, and records it in
+# `manufacturing/devices.csv`. See docs/manufacturing.md.
+# The PowerShell equivalent of flash-device.sh.
+#
+# Usage: scripts/manufacturing/flash-device.ps1 <port>
+
+param(
+    [Parameter(Mandatory)]
+    [string] $Port
+)
+
+$ErrorActionPreference = "Stop"
+
+#REPLACE esp32c3 mcu
+$Chip = "esp32c3"
+#REPLACE project-name project-name
+$SerialPrefix = if ($env:SERIAL_PREFIX) { $env:SERIAL_PREFIX } else { "project-name" }
+# Where the per-device data is written, by default the NVS partition of
+# espflash's default partition table
+$DeviceDataOffset = if ($env:DEVICE_DATA_OFFSET) { $env:DEVICE_DATA_OFFSET } else { "0x9000" }
+
+if (-not (Test-Path manufacturing/factory.bin)) {
+    Write-Error "manufacturing/factory.bin not found, run scripts/manufacturing/factory-image.ps1 first"
+}
+
+$BoardInfo = espflash board-info --chip $Chip --port $Port
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+$Match = $BoardInfo | Select-String -Pattern '^MAC address: *(\S+)' | Select-Object -First 1
+if (-not $Match) {
+    Write-Error "Could not read the MAC address of the device on $Port"
+}
+$Mac = $Match.Matches[0].Groups[1].Value.ToLower()
+# The last three bytes of the MAC address, as the `ident` module names devices
+$Serial = "$SerialPrefix-" + $Mac.Replace(":", "").Substring(6, 6)
+
+espflash write-bin --chip $Chip --port $Port 0x0 manufacturing/factory.bin
+if ($LASTEXITCODE) { exit $LASTEXITCODE }
+
+$DeviceData = "manufacturing/devices/$Serial.bin"
+if (Test-Path $DeviceData) {
+    espflash write-bin --chip $Chip --port $Port $DeviceDataOffset $DeviceData
+    if ($LASTEXITCODE) { exit $LASTEXITCODE }
+} else {
+    $DeviceData = ""
+}
+
+if (-not (Test-Path manufacturing/devices.csv)) {
+    "serial,mac,device_data,flashed_at" | Set-Content -Encoding ascii manufacturing/devices.csv
+}
+$FlashedAt = (Get-Date).ToUniversalTime().ToString("yyyy-MM-dd'T'HH:mm:ss'Z'")
+"$Serial,$Mac,$DeviceData,$FlashedAt" | Add-Content -Encoding ascii manufacturing/devices.csv
+
+Write-Output "Flashed $Serial ($Mac)"
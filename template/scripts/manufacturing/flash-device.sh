@@ -0,0 +1,47 @@
+#INCLUDEFILE manufacturing
+#!/usr/bin/env bash
+# Flashes the factory image, and the device's own data if there is any, to the
+# device connected to the given serial port, and records it in
+# `manufacturing/devices.csv`. See docs/manufacturing.md.
+#
+# Usage: scripts/manufacturing/flash-device.sh <port>
+
+set -euo pipefail
+
+PORT="${1:?Usage: $0 <port>}"
+#REPLACE esp32c3 mcu
+CHIP="esp32c3"
+#REPLACE project-name project-name
+SERIAL_PREFIX="${SERIAL_PREFIX:-project-name}"
+# Where the per-device data is written, by default the NVS partition of
+# espflash's default partition table
+DEVICE_DATA_OFFSET="${DEVICE_DATA_OFFSET:-0x9000}"
+
+if [ ! -f manufacturing/factory.bin ]; then
+    echo "manufacturing/factory.bin not found, run scripts/manufacturing/factory-image.sh first" >&2
+    exit 1
+fi
+
+MAC=$(espflash board-info --chip "$CHIP" --port "$PORT" | sed -n 's/^MAC address: *//p' | tr 'A-F' 'a-f')
+if [ -z "$MAC" ]; then
+    echo "Could not read the MAC address of the device on $PORT" >&2
+    exit 1
+fi
+# The last three bytes of the MAC address, as the `ident` module names devices
+SERIAL="$SERIAL_PREFIX-$(echo "$MAC" | tr -d ':' | cut -c 7-12)"
+
+espflash write-bin --chip "$CHIP" --port "$PORT" 0x0 manufacturing/factory.bin
+
+DEVICE_DATA="manufacturing/devices/$SERIAL.bin"
+if [ -f "$DEVICE_DATA" ]; then
+    espflash write-bin --chip "$CHIP" --port "$PORT" "$DEVICE_DATA_OFFSET" "$DEVICE_DATA"
+else
+    DEVICE_DATA=""
+fi
+
+if [ ! -f manufacturing/devices.csv ]; then
+    echo "serial,mac,device_data,flashed_at" >manufacturing/devices.csv
+fi
+echo "$SERIAL,$MAC,$DEVICE_DATA,$(date -u +%Y-%m-%dT%H:%M:%SZ)" >>manufacturing/devices.csv
+
+echo "Flashed $SERIAL ($MAC)"
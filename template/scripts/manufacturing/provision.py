@@ -0,0 +1,64 @@
+#INCLUDEFILE provisioning
+#!/usr/bin/env python3
+"""Writes the provisioning image of every device in a CSV file.
+
+The first column of `manufacturing/provisioning.csv` is the serial number of
+the device, as `flash-device.sh` derives it from the MAC address, every other
+column is an entry named after its header. The image of each device is written
+to `manufacturing/devices/<serial>.bin`, which `flash-device.sh` writes at the
+offset `src/provisioning.rs` reads it from.
+
+Usage: scripts/manufacturing/provision.py [csv]
+"""
+
+import csv
+import struct
+import sys
+import zlib
+from pathlib import Path
+
+MAGIC = b"PROV"
+VERSION = 1
+# `MAX_SIZE` in src/provisioning.rs
+MAX_SIZE = 1024
+
+
+def image(entries):
+    data = MAGIC + struct.pack("<BB", VERSION, len(entries))
+    for key, value in entries:
+        key = key.encode()
+        value = value.encode()
+        if len(key) > 0xFF or len(value) > 0xFFFF:
+            raise ValueError(f"entry '{key.decode()}' is too long")
+        data += struct.pack("<B", len(key)) + key + struct.pack("<H", len(value)) + value
+    data += struct.pack("<I", zlib.crc32(data))
+
+    if len(data) > MAX_SIZE:
+        raise ValueError(f"{len(data)} bytes, at most {MAX_SIZE} are read")
+    return data
+
+
+def main():
+    source = Path(sys.argv[1] if len(sys.argv) > 1 else "manufacturing/provisioning.csv")
+    devices = Path("manufacturing/devices")
+    devices.mkdir(parents=True, exist_ok=True)
+
+    with source.open(newline="") as file:
+        rows = list(csv.reader(file))
+    if len(rows[0]) > 0x100:
+        sys.exit(f"{source}: at most 255 entries per device")
+
+    keys = rows[0][1:]
+    for row in rows[1:]:
+        serial = row[0]
+        try:
+            data = image(list(zip(keys, row[1:])))
+        except ValueError as error:
+            sys.exit(f"{serial}: {error}")
+
+        (devices / f"{serial}.bin").write_bytes(data)
+        print(f"Wrote {devices / serial}.bin")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,154 @@
+#INCLUDEFILE map-file
+#!/usr/bin/env python3
+"""Lists the largest symbols of each section of the firmware.
+
+`build.rs` has the linker write a map of release builds next to the firmware,
+which this summarizes: the size of every loaded section and its largest
+symbols, i.e. what to look at first when the firmware does not fit. Maps of
+both GNU ld (Xtensa) and LLD (RISC-V) are read.
+
+Usage: scripts/map-summary.py [map] [-n count]
+"""
+
+import argparse
+import re
+import sys
+from pathlib import Path
+
+#REPLACE riscv32imac-unknown-none-elf rust_target && project-name project-name
+DEFAULT_MAP = "target/riscv32imac-unknown-none-elf/release/project-name.map"
+
+# The escapes of legacy Rust symbol names
+ESCAPES = {
+    "$LT$": "<",
+    "$GT$": ">",
+    "$RF$": "&",
+    "$BP$": "*",
+    "$C$": ",",
+    "$SP$": "@",
+    "$u20$": " ",
+    "$u27$": "'",
+    "$u5b$": "[",
+    "$u5d$": "]",
+    "$u7b$": "{",
+    "$u7d$": "}",
+    "$u7e$": "~",
+    "..": "::",
+}
+
+
+def demangle(symbol):
+    """`foo::bar` for `_ZN3foo3bar17h<hash>E`, other symbols as they are."""
+    if not (symbol.startswith("_ZN") and symbol.endswith("E")):
+        return symbol
+
+    parts = []
+    i = 3
+    while i < len(symbol) - 1:
+        length = re.match(r"\d+", symbol[i:])
+        if not length:
+            return symbol
+        start = i + len(length[0])
+        parts.append(symbol[start : start + int(length[0])])
+        i = start + int(length[0])
+    if parts and re.fullmatch(r"h[0-9a-f]{16}", parts[-1]):
+        parts.pop()
+
+    name = "::".join(parts)
+    for escape, character in ESCAPES.items():
+        name = name.replace(escape, character)
+    return name
+
+
+def symbol(section):
+    """The symbol of an input section, e.g. `foo` for `.text.foo`."""
+    parts = section.split(".", 2)
+    return demangle(parts[2] if len(parts) == 3 and parts[2] else section)
+
+
+def parse_lld(lines):
+    """`(section, address, size)` and `(symbol, size)` entries of an LLD map.
+
+    Columns are `VMA LMA Size Align Out In Symbol`, each column indented by
+    eight more spaces than the previous one.
+    """
+    for line in lines:
+        m = re.match(r"\s*([0-9a-f]+)\s+[0-9a-f]+\s+([0-9a-f]+)\s+\d+( +)(\S.*)$", line)
+        if not m:
+            continue
+        indent = len(m[3])
+        if indent < 8:
+            yield "out", m[4], int(m[1], 16), int(m[2], 16)
+        elif indent < 16:
+            section = re.search(r":\(([^)]*)\)$", m[4])
+            yield "in", section[1] if section else m[4], 0, int(m[2], 16)
+
+
+def parse_gnu(lines):
+    """The entries of a GNU ld map, after its `Linker script and memory map`.
+
+    Output sections start at the first column, input sections are indented by
+    one space. Either's address and size follow on the next line if its name is
+    too long.
+    """
+    lines = iter(lines)
+    for line in lines:
+        if line.startswith("Linker script and memory map"):
+            break
+
+    pending = None
+    for line in lines:
+        m = re.match(r"\s+0x([0-9a-f]+)\s+0x([0-9a-f]+)", line)
+        if pending and m:
+            yield pending[0], pending[1], int(m[1], 16), int(m[2], 16)
+            pending = None
+            continue
+        pending = None
+
+        m = re.match(r"( ?)(\.\S+|COMMON)(?:\s+0x([0-9a-f]+)\s+0x([0-9a-f]+))?", line)
+        if not m:
+            continue
+        kind = "in" if m[1] else "out"
+        if m[4] is None:
+            pending = (kind, m[2])
+        else:
+            yield kind, m[2], int(m[3], 16), int(m[4], 16)
+
+
+def summarize(lines):
+    """The loaded sections, as `(name, size, {symbol: size})`."""
+    parse = parse_lld if lines and lines[0].split()[:2] == ["VMA", "LMA"] else parse_gnu
+
+    sections = []
+    for kind, name, address, size in parse(lines):
+        if kind == "out":
+            # Sections at address 0, e.g. debug information, are not loaded
+            sections.append((name, size, {}) if address else None)
+        elif sections and sections[-1] is not None and size:
+            symbols = sections[-1][2]
+            symbols[symbol(name)] = symbols.get(symbol(name), 0) + size
+
+    return [section for section in sections if section and section[1]]
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("map", nargs="?", default=DEFAULT_MAP, help=f"default: {DEFAULT_MAP}")
+    parser.add_argument("-n", "--count", type=int, default=10, help="symbols per section")
+    args = parser.parse_args()
+
+    path = Path(args.map)
+    if not path.exists():
+        sys.exit(f"{path} does not exist, build the firmware with `cargo build --release` first")
+
+    sections = summarize(path.read_text(errors="replace").splitlines())
+    for name, size, symbols in sorted(sections, key=lambda s: s[1], reverse=True):
+        print(f"{name}  {size} bytes")
+        largest = sorted(symbols.items(), key=lambda s: s[1], reverse=True)
+        for symbol_name, symbol_size in largest[: args.count]:
+            print(f"  {symbol_size:>8}  {symbol_name}")
+        print()
+
+
+if __name__ == "__main__":
+    main()
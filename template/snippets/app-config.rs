@@ -0,0 +1,13 @@
+//SNIPPET modules
+
+pub mod cfg;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::cfg;
+//SNIPPET init
+info!(
+    "Running every {} ms, threshold {}%, reporting to {}",
+    cfg::INTERVAL_MS,
+    cfg::THRESHOLD,
+    cfg::ENDPOINT_URL
+);
@@ -0,0 +1,9 @@
+//SNIPPET modules
+
+pub mod bridge;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::bridge;
+//SNIPPET init
+info!("Checksum computed in C: {}", bridge::checksum(b"Hello from C"));
+
@@ -0,0 +1,9 @@
+//SNIPPET modules
+
+pub mod ident;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::ident;
+//SNIPPET init
+info!("Device name: {}", ident::device_name().as_str());
+
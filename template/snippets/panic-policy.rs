@@ -0,0 +1,8 @@
+//SNIPPET modules
+
+pub mod panic;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::panic;
+//SNIPPET init
+info!("Panic policy: {}", panic::POLICY);
@@ -0,0 +1,15 @@
+//SNIPPET modules
+
+pub mod provisioning;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::provisioning::Provisioning;
+//SNIPPET init
+match Provisioning::read() {
+    Ok(provisioning) => info!(
+        "Provisioned as {}",
+        provisioning.get("device_id").unwrap_or("<no device_id>")
+    ),
+    Err(error) => info!("No provisioning data: {}", error.as_str()),
+}
+
@@ -0,0 +1,15 @@
+//SNIPPET modules
+
+pub mod placement;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::placement;
+//SNIPPET init
+//IF option("has-rtc-ram")
+info!("Boot #{} since power-on", placement::count_boot());
+//ENDIF
+info!("CRC-32 computed in RAM: {:#x}", placement::checksum(&placement::PATTERN));
+// DMA cannot read `PATTERN` from flash, copy it into a buffer in internal RAM
+let mut dma_buffer = esp_hal::dma_tx_buffer!(placement::PATTERN.len()).unwrap();
+dma_buffer.fill(&placement::PATTERN);
+// TODO: Hand `dma_buffer` to a driver with DMA, e.g. SPI or I2S
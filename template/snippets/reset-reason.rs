@@ -0,0 +1,9 @@
+//SNIPPET modules
+
+pub mod reset_reason;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::reset_reason::ResetReason;
+//SNIPPET init
+let reset_reason = ResetReason::read();
+info!("Reset reason: {}", reset_reason.as_str());
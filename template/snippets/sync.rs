@@ -0,0 +1,9 @@
+//SNIPPET modules
+
+pub mod sync;
+//SNIPPET imports
+//REPLACE project_name crate-name
+use project_name::sync;
+//SNIPPET init
+// Runs `sync::app_core` on the second core, see `src/sync.rs`
+sync::start_app_core(peripherals.CPU_CTRL);
@@ -0,0 +1,60 @@
+//INCLUDEFILE audio-out
+//! Tone generation for playback over I2S.
+//!
+//! The samples are streamed by DMA to an I2S amplifier or DAC, e.g. a
+//! MAX98357A, as 16-bit stereo frames in the Philips format.
+
+/// The sample rate of the I2S output, in Hz.
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// The frequency of the generated tone, in Hz.
+pub const TONE_HZ: u32 = 440;
+
+/// The peak amplitude of the tone, out of `i16::MAX`.
+const AMPLITUDE: i32 = 8_000;
+
+/// Generates a triangle wave tone.
+pub struct Tone {
+    phase: u32,
+    step: u32,
+}
+
+impl Tone {
+    pub const fn new(frequency: u32) -> Self {
+        // The phase wraps around once per period
+        let step = ((frequency as u64) << 32) / SAMPLE_RATE as u64;
+
+        Self {
+            phase: 0,
+            step: step as u32,
+        }
+    }
+
+    fn next_sample(&mut self) -> i16 {
+        self.phase = self.phase.wrapping_add(self.step);
+
+        // Rises during the first half of the period, falls during the second
+        let position = (self.phase >> 16) as i32;
+        let triangle = if position < 0x8000 {
+            position
+        } else {
+            0xFFFF - position
+        };
+
+        ((triangle - 0x4000) * AMPLITUDE / 0x4000) as i16
+    }
+
+    /// Fills the buffer with as many stereo frames as fit, returning the
+    /// number of bytes written.
+    pub fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        let mut written = 0;
+        for frame in buffer.chunks_exact_mut(4) {
+            let sample = self.next_sample().to_le_bytes();
+            frame[..2].copy_from_slice(&sample);
+            frame[2..].copy_from_slice(&sample);
+            written += frame.len();
+        }
+
+        written
+    }
+}
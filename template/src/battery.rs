@@ -0,0 +1,46 @@
+//INCLUDEFILE battery
+//! Battery voltage measurement through a voltage divider on an ADC pin.
+//!
+//! The battery is connected to the ADC pin through a voltage divider, which
+//! keeps the pin voltage within the ADC's range. The ADC readings are
+//! calibrated by `esp-hal`, the divider ratio has to match the resistors used.
+
+use esp_hal::analog::adc::{Adc, AdcCalCurve, AdcConfig, AdcPin, Attenuation};
+use esp_hal::gpio::GpioPin;
+use esp_hal::peripherals::ADC1;
+
+/// The ratio of the voltage divider, battery voltage / ADC pin voltage, in
+/// hundredths. Two equal resistors halve the voltage, i.e. a ratio of 200.
+//REPLACE 200 battery-divider
+pub const DIVIDER_RATIO: u32 = 200;
+
+/// The battery voltage in millivolts below which the battery is low.
+//REPLACE 3300 battery-low-mv
+pub const LOW_BATTERY_MV: u32 = 3300;
+
+/// The ADC pin the voltage divider is connected to.
+//REPLACE 3 pin-adc
+pub type BatteryPin = GpioPin<3>;
+
+/// Measures the battery voltage.
+pub struct Battery<'d> {
+    adc: Adc<'d, ADC1>,
+    pin: AdcPin<BatteryPin, ADC1, AdcCalCurve<ADC1>>,
+}
+
+impl Battery<'_> {
+    pub fn new(adc: ADC1, pin: BatteryPin) -> Self {
+        let mut config = AdcConfig::new();
+        let pin = config.enable_pin_with_cal::<_, AdcCalCurve<ADC1>>(pin, Attenuation::_11dB);
+        let adc = Adc::new(adc, config);
+
+        Self { adc, pin }
+    }
+
+    /// The battery voltage in millivolts.
+    pub fn millivolts(&mut self) -> u32 {
+        let pin_millivolts = nb::block!(self.adc.read_oneshot(&mut self.pin)).unwrap();
+
+        u32::from(pin_millivolts) * DIVIDER_RATIO / 100
+    }
+}
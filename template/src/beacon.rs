@@ -0,0 +1,116 @@
+//INCLUDEFILE beacon
+//! BLE beacon advertising.
+//!
+//! The beacon only advertises, no GATT server is running. Receivers estimate
+//! their distance to the beacon from the received signal strength and the
+//! advertised signal strength at 1 m, `MEASURED_POWER`, which should be
+//! calibrated for the device and antenna.
+
+//IF option("beacon-eddystone")
+use bleps::att::Uuid;
+//ENDIF
+use bleps::ad_structure::{
+    create_advertising_data, AdStructure, BR_EDR_NOT_SUPPORTED, LE_GENERAL_DISCOVERABLE,
+};
+use bleps::{
+    AdvertisingFilterPolicy, AdvertisingParameters, AdvertisingType, Ble, HciConnector,
+    OwnAddressType, PeerAddressType,
+};
+use esp_hal::peripherals::BT;
+use esp_wifi::ble::controller::BleConnector;
+use esp_wifi::EspWifiController;
+
+/// The received signal strength at 1 m from the beacon, in dBm.
+//REPLACE 59 beacon-tx-power
+pub const MEASURED_POWER: i8 = -59;
+
+//IF option("beacon-eddystone")
+/// The advertised URL, without its `https://` prefix and using the
+/// Eddystone-URL abbreviations, e.g. `0x08` for `.org`.
+pub const URL: &[u8] = b"esp-rs\x08";
+
+/// The 16-bit UUID of the Eddystone service.
+const EDDYSTONE_UUID: u16 = 0xFEAA;
+//ELSE
+/// The proximity UUID, identifying the beacons of an application.
+pub const UUID: [u8; 16] = [
+    0xE2, 0xC5, 0x6D, 0xB5, 0xDF, 0xFB, 0x48, 0xD2, 0xB0, 0x60, 0xD0, 0xF5, 0xA7, 0x10, 0x96, 0xE0,
+];
+
+/// Identifies a group of beacons, e.g. those of a building.
+pub const MAJOR: u16 = 1;
+
+/// Identifies a beacon within its group.
+pub const MINOR: u16 = 1;
+
+/// Apple's company identifier, which iBeacon advertisements are sent as.
+const APPLE_COMPANY_ID: u16 = 0x004C;
+//ENDIF
+
+/// The advertising interval, in units of 0.625 ms, i.e. 100 ms.
+const ADVERTISING_INTERVAL: u16 = 160;
+
+/// Starts advertising the beacon. The controller keeps advertising after this
+/// returns.
+pub fn advertise(init: &EspWifiController<'_>, bt: BT) {
+    let connector = BleConnector::new(init, bt);
+    let hci = HciConnector::new(connector, now);
+    let mut ble = Ble::new(&hci);
+
+    ble.init().unwrap();
+    // Non-connectable, receivers cannot connect to a beacon
+    ble.cmd_set_le_advertising_parameters_custom(&AdvertisingParameters {
+        advertising_interval_min: ADVERTISING_INTERVAL,
+        advertising_interval_max: ADVERTISING_INTERVAL,
+        advertising_type: AdvertisingType::AdvNonConnInd,
+        own_address_type: OwnAddressType::Public,
+        peer_address_type: PeerAddressType::Public,
+        peer_address: [0; 6],
+        advertising_channel_map: 0b111,
+        filter_policy: AdvertisingFilterPolicy::All,
+    })
+    .unwrap();
+
+    //IF option("beacon-eddystone")
+    // A URL frame: frame type, signal strength at 0 m and the `https://` scheme
+    let mut frame = [0; 3 + URL.len()];
+    frame[0] = 0x10;
+    frame[1] = (MEASURED_POWER + 41) as u8;
+    frame[2] = 0x03;
+    frame[3..].copy_from_slice(URL);
+
+    let data = create_advertising_data(&[
+        AdStructure::Flags(LE_GENERAL_DISCOVERABLE | BR_EDR_NOT_SUPPORTED),
+        AdStructure::ServiceUuids16(&[Uuid::Uuid16(EDDYSTONE_UUID)]),
+        AdStructure::ServiceData16 {
+            uuid: EDDYSTONE_UUID,
+            data: &frame,
+        },
+    ])
+    .unwrap();
+    //ELSE
+    // The iBeacon type and length, followed by the beacon's identity
+    let mut payload = [0; 23];
+    payload[0] = 0x02;
+    payload[1] = 0x15;
+    payload[2..18].copy_from_slice(&UUID);
+    payload[18..20].copy_from_slice(&MAJOR.to_be_bytes());
+    payload[20..22].copy_from_slice(&MINOR.to_be_bytes());
+    payload[22] = MEASURED_POWER as u8;
+
+    let data = create_advertising_data(&[
+        AdStructure::Flags(LE_GENERAL_DISCOVERABLE | BR_EDR_NOT_SUPPORTED),
+        AdStructure::ManufacturerSpecificData {
+            company_identifier: APPLE_COMPANY_ID,
+            payload: &payload,
+        },
+    ])
+    .unwrap();
+    //ENDIF
+    ble.cmd_set_le_advertising_data(data).unwrap();
+    ble.cmd_set_le_advertise_enable(true).unwrap();
+}
+
+fn now() -> u64 {
+    esp_hal::time::now().duration_since_epoch().to_millis()
+}
@@ -3,7 +3,12 @@
 #![no_main]
 
 use esp_backtrace as _;
+//IF option("hal-facade")
+//REPLACE project_name crate-name
+//+use project_name::hal::{self, CpuClock};
+//ELSE
 use esp_hal::clock::CpuClock;
+//ENDIF
 //IF option("probe-rs")
 //+ use defmt_rtt as _;
 //+ use defmt::info;
@@ -14,6 +19,78 @@ use log::info;
 
 use embassy_executor::Spawner;
 use embassy_time::{Duration, Timer};
+//IF option("channels")
+//REPLACE project_name crate-name
+use project_name::ipc::{Reading, LATEST, READINGS, RECEIVED};
+//ENDIF
+//IF option("battery")
+//REPLACE project_name crate-name
+use project_name::battery::{Battery, LOW_BATTERY_MV};
+//REPLACE project_name crate-name
+use project_name::ipc::LOW_BATTERY;
+//IF option("probe-rs")
+//+ use defmt::warn;
+//ELSE
+use log::warn;
+//ENDIF
+//ENDIF
+//IF option("audio-out")
+use esp_hal::dma_buffers;
+use esp_hal::i2s::master::{DataFormat, I2s, I2sTx, Standard};
+use esp_hal::time::RateExtU32;
+use esp_hal::Async;
+//REPLACE project_name crate-name
+use project_name::audio::{Tone, SAMPLE_RATE, TONE_HZ};
+//ENDIF
+//IF option("hw-crypto")
+//IF option("has-aes")
+use esp_hal::aes::Aes;
+use esp_hal::peripherals::AES;
+//ENDIF
+//IF option("has-rsa")
+use esp_hal::peripherals::RSA;
+use esp_hal::rsa::Rsa;
+//ENDIF
+use esp_hal::peripherals::SHA;
+use esp_hal::sha::Sha;
+//REPLACE project_name crate-name
+use project_name::crypto;
+//ENDIF
+//ANCHOR imports
+//IF option("beacon")
+//REPLACE project_name crate-name
+use project_name::beacon;
+//ENDIF
+//IF option("wifi-sta")
+use esp_wifi::wifi::WifiStaDevice;
+use esp_wifi::EspWifiController;
+//REPLACE project_name crate-name
+use project_name::wifi::{self, IS_CONNECTED};
+use static_cell::StaticCell;
+//IF option("net-tools") || option("metrics") || option("remote-logging")
+use embassy_net::{Runner, StackResources};
+use esp_wifi::wifi::WifiDevice;
+//ENDIF
+//IF option("net-tools")
+//REPLACE project_name crate-name
+use project_name::net;
+//ENDIF
+//IF option("metrics")
+//REPLACE project_name crate-name
+use project_name::metrics;
+//ENDIF
+//IF option("remote-logging")
+//REPLACE project_name crate-name
+use project_name::remote_log;
+//ENDIF
+//ENDIF
+//IF option("heartbeat")
+//IF option("has-led")
+use esp_hal::gpio::{Level, Output};
+//ENDIF
+//REPLACE project_name crate-name
+use project_name::heartbeat::{Heartbeat, PERIOD_MS};
+//ENDIF
 
 //IF option("alloc")
 extern crate alloc;
@@ -24,44 +101,463 @@ async fn main(spawner: Spawner) {
     //REPLACE generate-version generate-version
     // generator version: generate-version
 
+    //IF option("hal-facade")
+    //+let config = hal::Config::default().with_cpu_clock(CpuClock::max());
+    //+let peripherals = hal::init(config);
+    //ELSE
     let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
     let peripherals = esp_hal::init(config);
+    //ENDIF
 
     //IF option("alloc")
+    //REPLACE 72 heap-size
     esp_alloc::heap_allocator!(72 * 1024);
+    //IF option("heap-dram2") || (option("wifi") && option("ble"))
+    // RAM otherwise only used by the 2nd stage bootloader. Wi-Fi and BLE
+    // coexistence needs it to not run out of memory at runtime.
+    {
+        #[link_section = ".dram2_uninit"]
+        static mut HEAP_DRAM2: core::mem::MaybeUninit<[u8; 64 * 1024]> =
+            core::mem::MaybeUninit::uninit();
+
+        unsafe {
+            esp_alloc::HEAP.add_region(esp_alloc::HeapRegion::new(
+                core::ptr::addr_of_mut!(HEAP_DRAM2).cast(),
+                64 * 1024,
+                esp_alloc::MemoryCapability::Internal.into(),
+            ));
+        }
+    }
+    //ENDIF
+    //IF option("heap-psram")
+    esp_alloc::psram_allocator!(peripherals.PSRAM, esp_hal::psram);
+    //ENDIF
     //ENDIF
 
+    //IF option("remote-logging")
+    remote_log::init();
+    //ELSE
     //IF !option("probe-rs")
     esp_println::logger::init_logger_from_env();
     //ENDIF
+    //ENDIF
 
-    //IF !option("esp32")
+    //IF option("chip-revision-check")
+    check_chip_revision();
+    //ENDIF
+
+    //ANCHOR init
+    //IF option("devkit")
+    //IF option("heartbeat") && option("has-led")
+    //REPLACE project_name crate-name
+    let pins = project_name::pins!(peripherals);
+    //ELSE
+    //REPLACE project_name crate-name
+    let _pins = project_name::pins!(peripherals);
+    //ENDIF
+    //ENDIF
+
+    //IF option("has-systimer")
+    //IF option("hal-facade")
+    //+let timer0 = hal::SystemTimer::new(peripherals.SYSTIMER);
+    //ELSE
     let timer0 = esp_hal::timer::systimer::SystemTimer::new(peripherals.SYSTIMER);
+    //ENDIF
     esp_hal_embassy::init(timer0.alarm0);
     //ELSE
+    //IF option("hal-facade")
+    //+let timer0 = hal::TimerGroup::new(peripherals.TIMG1);
+    //ELSE
     let timer0 = esp_hal::timer::timg::TimerGroup::new(peripherals.TIMG1);
+    //ENDIF
     esp_hal_embassy::init(timer0.timer0);
     //ENDIF
 
     info!("Embassy initialized!");
 
+    //IF option("hw-crypto")
+    benchmark_crypto(
+        peripherals.SHA,
+        //IF option("has-aes")
+        peripherals.AES,
+        //ENDIF
+        //IF option("has-rsa")
+        peripherals.RSA,
+        //ENDIF
+    );
+
+    //ENDIF
     //IF option("wifi") || option("ble")
+    //IF option("hal-facade")
+    //+let timer1 = hal::TimerGroup::new(peripherals.TIMG0);
+    //ELSE
     let timer1 = esp_hal::timer::timg::TimerGroup::new(peripherals.TIMG0);
-    let _init = esp_wifi::init(
-        timer1.timer0,
-        esp_hal::rng::Rng::new(peripherals.RNG),
-        peripherals.RADIO_CLK,
+    //ENDIF
+    //IF option("hal-facade")
+    //+let rng = hal::Rng::new(peripherals.RNG);
+    //ELSE
+    let rng = esp_hal::rng::Rng::new(peripherals.RNG);
+    //ENDIF
+    //IF option("beacon") || option("wifi-sta")
+    let init = esp_wifi::init(timer1.timer0, rng, peripherals.RADIO_CLK).unwrap();
+    //ELSE
+    let _init = esp_wifi::init(timer1.timer0, rng, peripherals.RADIO_CLK).unwrap();
+    //ENDIF
+    //IF option("wifi-sta")
+    // The connection task borrows the driver for `'static`
+    static WIFI_INIT: StaticCell<EspWifiController<'static>> = StaticCell::new();
+    let init = &*WIFI_INIT.init(init);
+    //IF option("net-tools") || option("metrics") || option("remote-logging")
+    let (device, controller) =
+    //ELSE
+    let (_device, controller) =
+    //ENDIF
+        esp_wifi::wifi::new_with_mode(init, peripherals.WIFI, WifiStaDevice).unwrap();
+    spawner.spawn(wifi::connection(controller)).unwrap();
+    spawner.spawn(uplink()).unwrap();
+    //IF option("net-tools") || option("metrics") || option("remote-logging")
+
+    // `Rng` is `Copy`, `esp-wifi` has a copy of its own
+    let mut rng = rng;
+    let seed = (u64::from(rng.random()) << 32) | u64::from(rng.random());
+    // Up to 5 sockets open at once, including those of DHCP and DNS lookups
+    static NET_RESOURCES: StaticCell<StackResources<5>> = StaticCell::new();
+    let (stack, runner) = embassy_net::new(
+        device,
+        embassy_net::Config::dhcpv4(Default::default()),
+        NET_RESOURCES.init(StackResources::new()),
+        seed,
+    );
+    spawner.spawn(net_task(runner)).unwrap();
+    //IF option("net-tools")
+    spawner.spawn(net::diagnostics(stack)).unwrap();
+    //ENDIF
+    //IF option("metrics")
+    spawner.spawn(metrics::serve(stack)).unwrap();
+    //ENDIF
+    //IF option("remote-logging")
+    spawner.spawn(remote_log::forward(stack)).unwrap();
+    //ENDIF
+    //ELSE
+    // `_device` is the network interface, to run an `embassy-net` stack on
+    //ENDIF
+    //ENDIF
+    //IF option("beacon")
+    beacon::advertise(&init, peripherals.BT);
+    info!("Advertising as a beacon");
+    //ENDIF
+    //ENDIF
+
+    //IF option("audio-out")
+    //IF option("has-gdma")
+    let dma_channel = peripherals.DMA_CH0;
+    //ELSE
+    //+let dma_channel = peripherals.DMA_I2S0;
+    //ENDIF
+    let (_, rx_descriptors, tx_buffer, tx_descriptors) = dma_buffers!(0, 4 * 4092);
+    let i2s = I2s::new(
+        peripherals.I2S0,
+        Standard::Philips,
+        DataFormat::Data16Channel16,
+        SAMPLE_RATE.Hz(),
+        dma_channel,
+        rx_descriptors,
+        tx_descriptors,
     )
-    .unwrap();
+    .into_async();
+    let i2s_tx = i2s
+        .i2s_tx
+        //REPLACE 5 pin-audio-bclk
+        .with_bclk(peripherals.GPIO5)
+        //REPLACE 6 pin-audio-ws
+        .with_ws(peripherals.GPIO6)
+        //REPLACE 7 pin-audio-dout
+        .with_dout(peripherals.GPIO7)
+        .build();
+    spawner.spawn(play_tone(i2s_tx, tx_buffer)).unwrap();
+
+    //ENDIF
+    //IF option("heartbeat")
+    //IF option("has-led")
+    //IF option("devkit")
+    let led = Output::new(pins.led, Level::Low);
+    //ELSE
+    //REPLACE 8 pin-led
+    let led = Output::new(peripherals.GPIO8, Level::Low);
+    //ENDIF
+    spawner.spawn(heartbeat(Heartbeat::new(led))).unwrap();
+    //ELSE
+    spawner.spawn(heartbeat(Heartbeat::new())).unwrap();
+    //ENDIF
+
+    //ENDIF
+    //IF option("stack-usage")
+    // Tasks are allocated from the task arena, document their budget in docs/tasks.md
+    //ENDIF
+    //IF option("channels")
+    spawner.spawn(producer()).unwrap();
+    spawner.spawn(consumer()).unwrap();
+    //IF option("battery")
+    //REPLACE 3 pin-adc
+    let battery = Battery::new(peripherals.ADC1, peripherals.GPIO3);
+    spawner.spawn(battery_monitor(battery)).unwrap();
+    spawner.spawn(power_manager()).unwrap();
     //ENDIF
 
+    // Any number of tasks can observe the latest reading, up to the number of
+    // receivers `LATEST` was declared with
+    let mut latest = LATEST.receiver().unwrap();
+    loop {
+        let received = RECEIVED.wait().await;
+        //IF option("sync")
+        sync::record();
+        //ENDIF
+        let reading = latest.get().await;
+        info!(
+            "{} readings received, the latest is #{}",
+            received, reading.sequence
+        );
+    }
+    //ELSE
     // TODO: Spawn some tasks
     let _ = spawner;
 
     loop {
         info!("Hello world!");
+        //IF option("sync")
+        sync::record();
+        //ENDIF
+        //IF option("app-config")
+        //+Timer::after(Duration::from_millis(cfg::INTERVAL_MS.into())).await;
+        //ELSE
         Timer::after(Duration::from_secs(1)).await;
+        //ENDIF
     }
+    //ENDIF
 
     // for inspiration have a look at the examples at https://github.com/esp-rs/esp-hal/tree/v0.23.1/examples/src/bin
 }
+
+//IF option("wifi-sta")
+/// Works only while the Wi-Fi station is connected, waiting for it otherwise.
+#[embassy_executor::task]
+async fn uplink() {
+    let mut connected = IS_CONNECTED.receiver().unwrap();
+    loop {
+        connected.get_and(|connected| *connected).await;
+        // TODO: Use the network, e.g. with an `embassy-net` stack
+        info!("Connected, ready to send");
+        Timer::after(Duration::from_secs(10)).await;
+    }
+}
+
+//IF option("net-tools") || option("metrics") || option("remote-logging")
+/// Runs the `embassy-net` stack on the station's interface.
+#[embassy_executor::task]
+async fn net_task(mut runner: Runner<'static, WifiDevice<'static, WifiStaDevice>>) {
+    runner.run().await
+}
+
+//ENDIF
+//ENDIF
+//IF option("channels")
+/// Sends a reading every 500 ms, waiting while the channel is full.
+#[embassy_executor::task]
+async fn producer() {
+    let latest = LATEST.sender();
+    let mut sequence = 0;
+    loop {
+        let reading = Reading { sequence };
+        READINGS.send(reading).await;
+        latest.send(reading);
+
+        sequence = sequence.wrapping_add(1);
+        Timer::after(Duration::from_millis(500)).await;
+    }
+}
+
+/// Receives the readings, signalling every tenth one.
+#[embassy_executor::task]
+async fn consumer() {
+    let mut received = 0;
+    loop {
+        let reading = READINGS.receive().await;
+        info!("Received reading #{}", reading.sequence);
+
+        received += 1;
+        if received % 10 == 0 {
+            RECEIVED.signal(received);
+        }
+    }
+}
+
+//ENDIF
+//IF option("battery")
+/// Measures the battery voltage every 10 s, signalling when it is low.
+#[embassy_executor::task]
+async fn battery_monitor(mut battery: Battery<'static>) {
+    loop {
+        let millivolts = battery.millivolts();
+        info!("Battery voltage: {} mV", millivolts);
+        if millivolts < LOW_BATTERY_MV {
+            LOW_BATTERY.signal(millivolts);
+        }
+
+        Timer::after(Duration::from_secs(10)).await;
+    }
+}
+
+/// Reacts to a low battery.
+#[embassy_executor::task]
+async fn power_manager() {
+    loop {
+        let millivolts = LOW_BATTERY.wait().await;
+        warn!("Battery low: {} mV", millivolts);
+        // TODO: Reduce the power consumption, e.g. by entering deep sleep
+    }
+}
+
+//ENDIF
+//IF option("heartbeat")
+/// Beats every `PERIOD_MS`, which stops when the executor is blocked.
+#[embassy_executor::task]
+async fn heartbeat(mut heartbeat: Heartbeat) {
+    loop {
+        heartbeat.tick();
+        Timer::after(Duration::from_millis(PERIOD_MS)).await;
+    }
+}
+
+//ENDIF
+//IF option("audio-out")
+/// Plays a tone, refilling the looping DMA buffer as it is played.
+#[embassy_executor::task]
+async fn play_tone(i2s_tx: I2sTx<'static, Async>, buffer: &'static mut [u8]) {
+    info!("Playing a {} Hz tone", TONE_HZ);
+
+    let mut tone = Tone::new(TONE_HZ);
+    let mut transfer = i2s_tx.write_dma_circular_async(buffer).unwrap();
+    loop {
+        transfer.push_with(|buffer| tone.fill(buffer)).await.unwrap();
+    }
+}
+
+//ENDIF
+//IF option("hw-crypto")
+/// Verifies the cryptographic accelerators against their software
+/// implementations, logging how long each takes.
+fn benchmark_crypto(
+    sha: SHA,
+    //IF option("has-aes")
+    aes: AES,
+    //ENDIF
+    //IF option("has-rsa")
+    rsa: RSA,
+    //ENDIF
+) {
+    let data = [0xA5; 4096];
+    let mut sha = Sha::new(sha);
+    let (hardware, hardware_us) = crypto::time(|| crypto::sha256_hardware(&mut sha, &data));
+    let (software, software_us) = crypto::time(|| crypto::sha256_software(&data));
+    assert_eq!(hardware, software);
+    info!(
+        "SHA-256 of {} bytes: {} us in hardware, {} us in software",
+        data.len(),
+        hardware_us,
+        software_us
+    );
+    //IF option("has-aes")
+
+    let key = *b"esp-generate key";
+    let mut hardware = [[0x5A; 16]; 256];
+    let mut software = hardware;
+    let mut aes = Aes::new(aes);
+    let ((), hardware_us) = crypto::time(|| crypto::aes128_hardware(&mut aes, key, &mut hardware));
+    let ((), software_us) = crypto::time(|| crypto::aes128_software(key, &mut software));
+    assert_eq!(hardware, software);
+    info!(
+        "AES-128 of {} bytes: {} us in hardware, {} us in software",
+        hardware.len() * 16,
+        hardware_us,
+        software_us
+    );
+    //ENDIF
+    //IF option("has-rsa")
+
+    // Not a real key, any odd modulus will do for benchmarking
+    let mut modulus = [0xFFFF_FFFF; 16];
+    modulus[0] = 0xFFFF_FFC5;
+    let base = [0x1234_5678; 16];
+    let mut exponent = [0; 16];
+    exponent[0] = 65537;
+    let mut rsa = Rsa::new(rsa);
+    nb::block!(rsa.ready()).unwrap();
+    let (hardware, hardware_us) = crypto::time(|| {
+        crypto::rsa512_mod_exp_hardware(&mut rsa, &base, &exponent, &modulus)
+    });
+    let (software, software_us) =
+        crypto::time(|| crypto::rsa512_mod_exp_software(&base, &exponent, &modulus));
+    assert_eq!(hardware, software);
+    info!(
+        "RSA-512 public key operation: {} us in hardware, {} us in software",
+        hardware_us, software_us
+    );
+    //ENDIF
+}
+
+//ENDIF
+//IF option("chip-revision-check")
+/// Warns when running on a chip revision older than the selected features
+/// support.
+fn check_chip_revision() {
+    //REPLACE 300 min-chip-revision
+    const MIN_CHIP_REVISION: u16 = 300;
+
+    let revision = chip_revision();
+    if revision < MIN_CHIP_REVISION {
+        //IF option("probe-rs")
+        //+defmt::warn!(
+        //ELSE
+        log::warn!(
+        //ENDIF
+            "Chip revision v{}.{} is not supported, v{}.{} or newer is required",
+            revision / 100,
+            revision % 100,
+            MIN_CHIP_REVISION / 100,
+            MIN_CHIP_REVISION % 100
+        );
+    }
+}
+
+/// The chip revision as `major * 100 + minor`, read from the eFuses.
+fn chip_revision() -> u16 {
+    use esp_hal::efuse::{self, Efuse};
+
+    //IF !option("has-wafer-version")
+    // The major revision is encoded in two eFuse bits and, since v3.0, the
+    // APB_CTRL_DATE register
+    let apb_ctrl_date = unsafe { core::ptr::read_volatile(0x3FF6_607C as *const u32) };
+    let eco_bits = Efuse::read_bit(efuse::CHIP_VER_REV1) as u8
+        | (Efuse::read_bit(efuse::CHIP_VER_REV2) as u8) << 1
+        | ((apb_ctrl_date >> 31) as u8) << 2;
+    let major = match eco_bits {
+        1 => 1,
+        3 => 2,
+        7 => 3,
+        _ => 0,
+    };
+    let minor = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR);
+    //ENDIF
+    //IF option("has-wafer-version") && !option("has-split-wafer-minor")
+    let major = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MAJOR);
+    let minor = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR);
+    //ENDIF
+    //IF option("has-split-wafer-minor")
+    let major = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MAJOR);
+    let minor = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR_HI) << 3
+        | Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR_LO);
+    //ENDIF
+
+    major as u16 * 100 + minor as u16
+}
+//ENDIF
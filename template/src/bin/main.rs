@@ -3,10 +3,80 @@
 #![no_main]
 
 use esp_backtrace as _;
-use esp_hal::{clock::CpuClock, delay::Delay, main};
+//IF option("hal-facade")
+//REPLACE project_name crate-name
+//+use project_name::hal::{self, main, CpuClock};
+//IF option("unstable-hal") && !option("audio-out")
+//REPLACE project_name crate-name
+//+use project_name::hal::Delay;
+//ENDIF
+//ELSE
+use esp_hal::{clock::CpuClock, main};
+//IF option("unstable-hal") && !option("audio-out")
+use esp_hal::delay::Delay;
+//ENDIF
+//ENDIF
+//IF option("ui-slint")
+use embedded_hal_bus::spi::ExclusiveDevice;
+use esp_hal::gpio::{Level, Output};
+use esp_hal::spi::master::{Config as SpiConfig, Spi};
+use esp_hal::time::RateExtU32;
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7789;
+use mipidsi::options::ColorInversion;
+//REPLACE project_name crate-name
+use project_name::ui;
+//ENDIF
+//IF option("audio-out")
+use esp_hal::dma_buffers;
+use esp_hal::i2s::master::{DataFormat, I2s, Standard};
+use esp_hal::time::RateExtU32;
+//REPLACE project_name crate-name
+use project_name::audio::{Tone, SAMPLE_RATE, TONE_HZ};
+//ENDIF
+//IF option("hw-crypto")
+//IF option("has-aes")
+use esp_hal::aes::Aes;
+use esp_hal::peripherals::AES;
+//ENDIF
+//IF option("has-rsa")
+use esp_hal::peripherals::RSA;
+use esp_hal::rsa::Rsa;
+//ENDIF
+use esp_hal::peripherals::SHA;
+use esp_hal::sha::Sha;
+//REPLACE project_name crate-name
+use project_name::crypto;
+//ENDIF
+//ANCHOR imports
+//IF option("beacon")
+//REPLACE project_name crate-name
+use project_name::beacon;
+//ENDIF
+//IF option("heartbeat")
+//IF option("has-led")
+use esp_hal::gpio::{Level, Output};
+//ENDIF
+//REPLACE project_name crate-name
+use project_name::heartbeat::Heartbeat;
+//ENDIF
+//IF option("interrupts")
+//IF option("has-systimer")
+use esp_hal::timer::systimer::SystemTimer;
+//ELSE
+use esp_hal::timer::timg::TimerGroup;
+//ENDIF
+//REPLACE project_name crate-name
+use project_name::interrupts;
+//ENDIF
+//IF (option("wifi") || option("ble")) && option("hal-facade")
+//REPLACE project_name crate-name
+//+use project_name::hal::TimerGroup;
+//ELSE
 //IF option("wifi") || option("ble")
 use esp_hal::timer::timg::TimerGroup;
 //ENDIF
+//ENDIF
 
 //IF option("probe-rs")
 //+ use defmt_rtt as _;
@@ -24,36 +94,356 @@ fn main() -> ! {
     //REPLACE generate-version generate-version
     // generator version: generate-version
 
+    //IF option("hal-facade")
+    //+let config = hal::Config::default().with_cpu_clock(CpuClock::max());
+    //ELSE
     let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
-    //IF option("wifi") || option("ble")
+    //ENDIF
+    //IF option("wifi") || option("ble") || option("heap-psram") || option("devkit") || option("ui-slint") || option("audio-out") || option("hw-crypto") || (option("heartbeat") && option("has-led")) || option("interrupts") || option("sync")
+    //IF option("hal-facade")
+    //+let peripherals = hal::init(config);
+    //ELSE
     let peripherals = esp_hal::init(config);
+    //ENDIF
+    //ELSE
+    //IF option("hal-facade")
+    //+let _peripherals = hal::init(config);
     //ELSE
     //+let _peripherals = esp_hal::init(config);
     //ENDIF
+    //ENDIF
+
+    //IF option("alloc")
+    //REPLACE 72 heap-size
+    esp_alloc::heap_allocator!(72 * 1024);
+    //IF option("heap-dram2") || (option("wifi") && option("ble"))
+    // RAM otherwise only used by the 2nd stage bootloader. Wi-Fi and BLE
+    // coexistence needs it to not run out of memory at runtime.
+    {
+        #[link_section = ".dram2_uninit"]
+        static mut HEAP_DRAM2: core::mem::MaybeUninit<[u8; 64 * 1024]> =
+            core::mem::MaybeUninit::uninit();
+
+        unsafe {
+            esp_alloc::HEAP.add_region(esp_alloc::HeapRegion::new(
+                core::ptr::addr_of_mut!(HEAP_DRAM2).cast(),
+                64 * 1024,
+                esp_alloc::MemoryCapability::Internal.into(),
+            ));
+        }
+    }
+    //ENDIF
+    //IF option("heap-psram")
+    esp_alloc::psram_allocator!(peripherals.PSRAM, esp_hal::psram);
+    //ENDIF
+    //ENDIF
 
     //IF !option("probe-rs")
     esp_println::logger::init_logger_from_env();
     //ENDIF
 
-    //IF option("alloc")
-    esp_alloc::heap_allocator!(72 * 1024);
+    //IF option("chip-revision-check")
+    check_chip_revision();
+    //ENDIF
+
+    //ANCHOR init
+    //IF option("devkit")
+    //IF (option("heartbeat") && option("has-led")) || option("interrupts")
+    //REPLACE project_name crate-name
+    let pins = project_name::pins!(peripherals);
+    //ELSE
+    //REPLACE project_name crate-name
+    let _pins = project_name::pins!(peripherals);
+    //ENDIF
+    //ENDIF
+
+    //IF option("heartbeat")
+    //IF option("has-led")
+    //IF option("devkit")
+    let led = Output::new(pins.led, Level::Low);
+    //ELSE
+    //REPLACE 8 pin-led
+    let led = Output::new(peripherals.GPIO8, Level::Low);
+    //ENDIF
+    let mut heartbeat = Heartbeat::new(led);
+    //ELSE
+    let mut heartbeat = Heartbeat::new();
     //ENDIF
 
+    //ENDIF
+    //IF option("interrupts")
+    // Counts the presses of the BOOT button and the ticks of a timer in
+    // interrupt handlers, see `src/interrupts.rs`
+    interrupts::init(
+        peripherals.IO_MUX,
+        //IF option("devkit")
+        pins.button,
+        //ELSE
+        //REPLACE 9 pin-button
+        peripherals.GPIO9,
+        //ENDIF
+        //IF option("has-systimer")
+        SystemTimer::new(peripherals.SYSTIMER).alarm0,
+        //ELSE
+        // TIMG0 is left to `esp-wifi`
+        TimerGroup::new(peripherals.TIMG1).timer0,
+        //ENDIF
+    );
+
+    //ENDIF
+    //IF option("hw-crypto")
+    benchmark_crypto(
+        peripherals.SHA,
+        //IF option("has-aes")
+        peripherals.AES,
+        //ENDIF
+        //IF option("has-rsa")
+        peripherals.RSA,
+        //ENDIF
+    );
+
+    //ENDIF
     //IF option("wifi") || option("ble")
     let timg0 = TimerGroup::new(peripherals.TIMG0);
+    //IF option("beacon")
+    let init = esp_wifi::init(
+    //ELSE
     let _init = esp_wifi::init(
+    //ENDIF
         timg0.timer0,
+        //IF option("hal-facade")
+        //+hal::Rng::new(peripherals.RNG),
+        //ELSE
         esp_hal::rng::Rng::new(peripherals.RNG),
+        //ENDIF
         peripherals.RADIO_CLK,
     )
     .unwrap();
+    //IF option("beacon")
+    beacon::advertise(&init, peripherals.BT);
+    info!("Advertising as a beacon");
+    //ENDIF
+    //ENDIF
+
+    //IF option("ui-slint")
+    // A 240x320 ST7789 display, see `src/ui.rs` to use a different one
+    let spi = Spi::new(peripherals.SPI2, SpiConfig::default().with_frequency(40.MHz()))
+        .unwrap()
+        //REPLACE 12 pin-display-sck
+        .with_sck(peripherals.GPIO12)
+        //REPLACE 11 pin-display-mosi
+        .with_mosi(peripherals.GPIO11);
+    //REPLACE 10 pin-display-cs
+    let cs = Output::new(peripherals.GPIO10, Level::High);
+    //REPLACE 13 pin-display-dc
+    let dc = Output::new(peripherals.GPIO13, Level::Low);
+    //REPLACE 14 pin-display-reset
+    let reset = Output::new(peripherals.GPIO14, Level::Low);
+
+    let spi = ExclusiveDevice::new_no_delay(spi, cs).unwrap();
+    let mut buffer = [0; 512];
+    let display = mipidsi::Builder::new(ST7789, SpiInterface::new(spi, dc, &mut buffer))
+        .display_size(ui::WIDTH as u16, ui::HEIGHT as u16)
+        .invert_colors(ColorInversion::Inverted)
+        .reset_pin(reset)
+        .init(&mut Delay::new())
+        .unwrap();
+
+    info!("Display initialized!");
+
+    // TODO: Return the touched position, if any, from the touch controller
+    ui::run(display, || None)
+    //ELSE
+    //IF option("audio-out")
+    //IF option("has-gdma")
+    let dma_channel = peripherals.DMA_CH0;
+    //ELSE
+    //+let dma_channel = peripherals.DMA_I2S0;
     //ENDIF
+    let (_, rx_descriptors, tx_buffer, tx_descriptors) = dma_buffers!(0, 4 * 4092);
+    let i2s = I2s::new(
+        peripherals.I2S0,
+        Standard::Philips,
+        DataFormat::Data16Channel16,
+        SAMPLE_RATE.Hz(),
+        dma_channel,
+        rx_descriptors,
+        tx_descriptors,
+    );
+    let mut i2s_tx = i2s
+        .i2s_tx
+        //REPLACE 5 pin-audio-bclk
+        .with_bclk(peripherals.GPIO5)
+        //REPLACE 6 pin-audio-ws
+        .with_ws(peripherals.GPIO6)
+        //REPLACE 7 pin-audio-dout
+        .with_dout(peripherals.GPIO7)
+        .build();
+
+    info!("Playing a {} Hz tone", TONE_HZ);
 
+    // The DMA transfer loops over the buffer, refill it as it is played
+    let mut tone = Tone::new(TONE_HZ);
+    let mut transfer = i2s_tx.write_dma_circular(&tx_buffer).unwrap();
+    loop {
+        transfer.push_with(|buffer| tone.fill(buffer)).unwrap();
+        //IF option("heartbeat")
+        heartbeat.tick();
+        //ENDIF
+    }
+    //ELSE
+    //IF option("unstable-hal")
     let delay = Delay::new();
+    //ENDIF
     loop {
         info!("Hello world!");
+        //IF option("sync")
+        sync::record();
+        //ENDIF
+        //IF option("interrupts")
+        info!(
+            "Button presses: {}, timer ticks: {}",
+            interrupts::presses(),
+            interrupts::ticks()
+        );
+        //ENDIF
+        //IF option("heartbeat")
+        heartbeat.tick();
+        //ENDIF
+        //IF option("unstable-hal") && option("app-config")
+        //+delay.delay_millis(cfg::INTERVAL_MS);
+        //ELSE
+        //IF option("unstable-hal")
         delay.delay_millis(500);
+        //ELSE
+        // `esp_hal::delay` is only available with the `unstable-hal` option
+        for _ in 0..10_000_000 {
+            core::hint::spin_loop();
+        }
+        //ENDIF
+        //ENDIF
     }
+    //ENDIF
+    //ENDIF
 
     // for inspiration have a look at the examples at https://github.com/esp-rs/esp-hal/tree/v0.23.1/examples/src/bin
 }
+
+//IF option("hw-crypto")
+/// Verifies the cryptographic accelerators against their software
+/// implementations, logging how long each takes.
+fn benchmark_crypto(
+    sha: SHA,
+    //IF option("has-aes")
+    aes: AES,
+    //ENDIF
+    //IF option("has-rsa")
+    rsa: RSA,
+    //ENDIF
+) {
+    let data = [0xA5; 4096];
+    let mut sha = Sha::new(sha);
+    let (hardware, hardware_us) = crypto::time(|| crypto::sha256_hardware(&mut sha, &data));
+    let (software, software_us) = crypto::time(|| crypto::sha256_software(&data));
+    assert_eq!(hardware, software);
+    info!(
+        "SHA-256 of {} bytes: {} us in hardware, {} us in software",
+        data.len(),
+        hardware_us,
+        software_us
+    );
+    //IF option("has-aes")
+
+    let key = *b"esp-generate key";
+    let mut hardware = [[0x5A; 16]; 256];
+    let mut software = hardware;
+    let mut aes = Aes::new(aes);
+    let ((), hardware_us) = crypto::time(|| crypto::aes128_hardware(&mut aes, key, &mut hardware));
+    let ((), software_us) = crypto::time(|| crypto::aes128_software(key, &mut software));
+    assert_eq!(hardware, software);
+    info!(
+        "AES-128 of {} bytes: {} us in hardware, {} us in software",
+        hardware.len() * 16,
+        hardware_us,
+        software_us
+    );
+    //ENDIF
+    //IF option("has-rsa")
+
+    // Not a real key, any odd modulus will do for benchmarking
+    let mut modulus = [0xFFFF_FFFF; 16];
+    modulus[0] = 0xFFFF_FFC5;
+    let base = [0x1234_5678; 16];
+    let mut exponent = [0; 16];
+    exponent[0] = 65537;
+    let mut rsa = Rsa::new(rsa);
+    nb::block!(rsa.ready()).unwrap();
+    let (hardware, hardware_us) = crypto::time(|| {
+        crypto::rsa512_mod_exp_hardware(&mut rsa, &base, &exponent, &modulus)
+    });
+    let (software, software_us) =
+        crypto::time(|| crypto::rsa512_mod_exp_software(&base, &exponent, &modulus));
+    assert_eq!(hardware, software);
+    info!(
+        "RSA-512 public key operation: {} us in hardware, {} us in software",
+        hardware_us, software_us
+    );
+    //ENDIF
+}
+
+//ENDIF
+//IF option("chip-revision-check")
+/// Warns when running on a chip revision older than the selected features
+/// support.
+fn check_chip_revision() {
+    //REPLACE 300 min-chip-revision
+    const MIN_CHIP_REVISION: u16 = 300;
+
+    let revision = chip_revision();
+    if revision < MIN_CHIP_REVISION {
+        //IF option("probe-rs")
+        //+defmt::warn!(
+        //ELSE
+        log::warn!(
+        //ENDIF
+            "Chip revision v{}.{} is not supported, v{}.{} or newer is required",
+            revision / 100,
+            revision % 100,
+            MIN_CHIP_REVISION / 100,
+            MIN_CHIP_REVISION % 100
+        );
+    }
+}
+
+/// The chip revision as `major * 100 + minor`, read from the eFuses.
+fn chip_revision() -> u16 {
+    use esp_hal::efuse::{self, Efuse};
+
+    //IF !option("has-wafer-version")
+    // The major revision is encoded in two eFuse bits and, since v3.0, the
+    // APB_CTRL_DATE register
+    let apb_ctrl_date = unsafe { core::ptr::read_volatile(0x3FF6_607C as *const u32) };
+    let eco_bits = Efuse::read_bit(efuse::CHIP_VER_REV1) as u8
+        | (Efuse::read_bit(efuse::CHIP_VER_REV2) as u8) << 1
+        | ((apb_ctrl_date >> 31) as u8) << 2;
+    let major = match eco_bits {
+        1 => 1,
+        3 => 2,
+        7 => 3,
+        _ => 0,
+    };
+    let minor = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR);
+    //ENDIF
+    //IF option("has-wafer-version") && !option("has-split-wafer-minor")
+    let major = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MAJOR);
+    let minor = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR);
+    //ENDIF
+    //IF option("has-split-wafer-minor")
+    let major = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MAJOR);
+    let minor = Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR_HI) << 3
+        | Efuse::read_field_le::<u8>(efuse::WAFER_VERSION_MINOR_LO);
+    //ENDIF
+
+    major as u16 * 100 + minor as u16
+}
+//ENDIF
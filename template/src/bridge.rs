@@ -0,0 +1,25 @@
+//INCLUDEFILE c-interop
+//! Safe wrappers around the C code in `csrc/`, which build.rs compiles with
+//! the GCC of the chip's toolchain and links into the firmware.
+//!
+//! The declarations in `bindings.rs` are generated from `csrc/bridge.h` by
+//! `scripts/bindgen.sh`. Keep the `unsafe` calls into C in this module, so
+//! that the rest of the firmware does not depend on the C signatures.
+//! See docs/c-interop.md.
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_snake_case,
+    non_upper_case_globals
+)]
+mod bindings;
+//IF option("c-logging")
+mod logging;
+//ENDIF
+
+/// The Fletcher-16 checksum of `data`, computed in C.
+pub fn checksum(data: &[u8]) -> u16 {
+    // SAFETY: `bridge_checksum` only reads `len` bytes from `data`
+    unsafe { bindings::bridge_checksum(data.as_ptr(), data.len()) }
+}
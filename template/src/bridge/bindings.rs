@@ -0,0 +1,6 @@
+//INCLUDEFILE c-interop
+/* automatically generated by rust-bindgen 0.71.1 */
+
+unsafe extern "C" {
+    pub fn bridge_checksum(data: *const u8, len: usize) -> u16;
+}
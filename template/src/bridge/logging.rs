@@ -0,0 +1,61 @@
+//INCLUDEFILE c-logging
+//! The logger of the C code: `ESP_LOGE` to `ESP_LOGV` of `csrc/log.h` format
+//! their message in C and pass it here, so that it is logged along with those
+//! of the firmware, as `<tag>: <message>`.
+
+use core::ffi::{c_char, c_int, CStr};
+
+//IF option("probe-rs")
+use defmt::{debug, error, info, trace, warn};
+//ELSE
+use log::Level;
+//ENDIF
+
+// The levels of `esp_log_level_t` in `csrc/log.h`
+const ERROR: c_int = 1;
+const WARN: c_int = 2;
+const INFO: c_int = 3;
+const DEBUG: c_int = 4;
+
+/// Log a message of the C code, called by `esp_log_writev`.
+///
+/// # Safety
+///
+/// `tag` must be null or a null-terminated string, and `message` must point
+/// to `len` bytes.
+#[no_mangle]
+unsafe extern "C" fn bridge_log(level: c_int, tag: *const c_char, message: *const u8, len: usize) {
+    let tag = if tag.is_null() {
+        ""
+    } else {
+        // SAFETY: guaranteed by the caller
+        unsafe { CStr::from_ptr(tag) }.to_str().unwrap_or("?")
+    };
+    // SAFETY: guaranteed by the caller
+    let message = unsafe { core::slice::from_raw_parts(message, len) };
+    // A truncated message may end in the middle of a character
+    let message = match core::str::from_utf8(message) {
+        Ok(message) => message,
+        Err(error) => core::str::from_utf8(&message[..error.valid_up_to()]).unwrap_or_default(),
+    };
+    let message = message.trim_end_matches('\n');
+
+    //IF option("probe-rs")
+    match level {
+        ERROR => error!("{=str}: {=str}", tag, message),
+        WARN => warn!("{=str}: {=str}", tag, message),
+        INFO => info!("{=str}: {=str}", tag, message),
+        DEBUG => debug!("{=str}: {=str}", tag, message),
+        _ => trace!("{=str}: {=str}", tag, message),
+    }
+    //ELSE
+    let level = match level {
+        ERROR => Level::Error,
+        WARN => Level::Warn,
+        INFO => Level::Info,
+        DEBUG => Level::Debug,
+        _ => Level::Trace,
+    };
+    log::log!(level, "{tag}: {message}");
+    //ENDIF
+}
@@ -0,0 +1,26 @@
+//INCLUDEFILE app-config
+//! The configuration of the application, fixed at build time.
+//!
+//! Each value is an `APP_CONFIG_*` variable in the `[env]` section of
+//! `.cargo/config.toml`, which can be overridden for a single build from the
+//! environment, e.g. `APP_CONFIG_INTERVAL_MS=100 cargo run`. `build.rs`
+//! declares every variable with its default and validates it with
+//! `esp-config`, so an invalid or misspelled value fails the build instead of
+//! the device.
+//!
+//! The values below are examples, replace them with those of your
+//! application. To add one:
+//!
+//! 1. Declare it in `build.rs`, e.g. `retries` for `APP_CONFIG_RETRIES`.
+//! 2. Set it in `.cargo/config.toml`, or rely on its default.
+//! 3. Read it here with `esp_config_int!`, `esp_config_bool!` or
+//!    `esp_config_str!`.
+
+/// How often the main loop runs, in milliseconds.
+pub const INTERVAL_MS: u32 = esp_config::esp_config_int!(u32, "APP_CONFIG_INTERVAL_MS");
+
+/// The level, in percent, above which the application reacts.
+pub const THRESHOLD: u8 = esp_config::esp_config_int!(u8, "APP_CONFIG_THRESHOLD");
+
+/// Where the application reports to.
+pub const ENDPOINT_URL: &str = esp_config::esp_config_str!("APP_CONFIG_ENDPOINT_URL");
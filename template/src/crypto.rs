@@ -0,0 +1,163 @@
+//INCLUDEFILE hw-crypto
+//! The cryptographic accelerators, along with software implementations of the
+//! same operations to verify and benchmark them against.
+//!
+//! The RSA accelerator only performs the modular arithmetic, padding and key
+//! handling are left to the application.
+
+//IF option("has-aes")
+use aes::cipher::{BlockEncrypt, KeyInit};
+use esp_hal::aes::{Aes, Mode};
+//ENDIF
+//IF option("has-rsa")
+use esp_hal::rsa::operand_sizes::Op512;
+use esp_hal::rsa::{Rsa, RsaModularExponentiation};
+use esp_hal::Blocking;
+//ENDIF
+use esp_hal::sha::{Sha, Sha256};
+use sha2::Digest;
+
+/// Runs `f`, returning its result and how long it took in microseconds.
+pub fn time<R>(f: impl FnOnce() -> R) -> (R, u64) {
+    let start = esp_hal::time::now();
+    let result = f();
+
+    (result, (esp_hal::time::now() - start).to_micros())
+}
+
+/// The SHA-256 digest of the data, computed by the SHA accelerator.
+pub fn sha256_hardware(sha: &mut Sha<'_>, mut data: &[u8]) -> [u8; 32] {
+    let mut hasher = sha.start::<Sha256>();
+    while !data.is_empty() {
+        data = nb::block!(hasher.update(data)).unwrap();
+    }
+
+    let mut digest = [0; 32];
+    nb::block!(hasher.finish(&mut digest)).unwrap();
+
+    digest
+}
+
+/// The SHA-256 digest of the data, computed in software.
+pub fn sha256_software(data: &[u8]) -> [u8; 32] {
+    sha2::Sha256::digest(data).into()
+}
+//IF option("has-aes")
+
+/// Encrypts the blocks in place with AES-128 in ECB mode, using the AES
+/// accelerator.
+pub fn aes128_hardware(aes: &mut Aes<'_>, key: [u8; 16], blocks: &mut [[u8; 16]]) {
+    for block in blocks {
+        aes.process(block, Mode::Encryption128, key);
+    }
+}
+
+/// Encrypts the blocks in place with AES-128 in ECB mode, in software.
+pub fn aes128_software(key: [u8; 16], blocks: &mut [[u8; 16]]) {
+    let cipher = aes::Aes128::new(&key.into());
+    for block in blocks {
+        cipher.encrypt_block(block.into());
+    }
+}
+//ENDIF
+//IF option("has-rsa")
+
+/// A 512-bit RSA operand, as little endian words.
+pub type Operand = [u32; 16];
+
+/// `base ^ exponent mod modulus`, computed by the RSA accelerator. The modulus
+/// must be odd and the base smaller than it.
+pub fn rsa512_mod_exp_hardware(
+    rsa: &mut Rsa<'_, Blocking>,
+    base: &Operand,
+    exponent: &Operand,
+    modulus: &Operand,
+) -> Operand {
+    // The accelerator uses Montgomery multiplication, which needs these two
+    // values derived from the modulus
+    let mut mod_exp =
+        RsaModularExponentiation::<Op512, Blocking>::new(rsa, exponent, modulus, m_prime(modulus));
+    mod_exp.start_exponentiation(base, &montgomery_r(modulus));
+
+    let mut result = [0; 16];
+    mod_exp.read_results(&mut result);
+
+    result
+}
+
+/// `base ^ exponent mod modulus`, computed in software by square-and-multiply.
+/// The modulus must be odd and the base smaller than it.
+pub fn rsa512_mod_exp_software(base: &Operand, exponent: &Operand, modulus: &Operand) -> Operand {
+    let mut result = [0; 16];
+    result[0] = 1;
+    for bit in (0..512).rev() {
+        result = mul_mod(&result, &result, modulus);
+        if exponent[bit / 32] & (1 << (bit % 32)) != 0 {
+            result = mul_mod(&result, base, modulus);
+        }
+    }
+
+    result
+}
+
+/// `-modulus^-1 mod 2^32`.
+fn m_prime(modulus: &Operand) -> u32 {
+    // Newton's method, each iteration doubles the number of correct bits
+    let mut inverse = 1u32;
+    for _ in 0..5 {
+        inverse = inverse.wrapping_mul(2u32.wrapping_sub(modulus[0].wrapping_mul(inverse)));
+    }
+
+    inverse.wrapping_neg()
+}
+
+/// `2^1024 mod modulus`.
+fn montgomery_r(modulus: &Operand) -> Operand {
+    let mut r = [0; 16];
+    r[0] = 1;
+    for _ in 0..1024 {
+        r = add_mod(&r, &r, modulus);
+    }
+
+    r
+}
+
+/// `a * b mod modulus`, by double-and-add.
+fn mul_mod(a: &Operand, b: &Operand, modulus: &Operand) -> Operand {
+    let mut result = [0; 16];
+    for bit in (0..512).rev() {
+        result = add_mod(&result, &result, modulus);
+        if b[bit / 32] & (1 << (bit % 32)) != 0 {
+            result = add_mod(&result, a, modulus);
+        }
+    }
+
+    result
+}
+
+/// `a + b mod modulus`, where `a` and `b` are smaller than the modulus.
+fn add_mod(a: &Operand, b: &Operand, modulus: &Operand) -> Operand {
+    let mut sum = [0; 16];
+    let mut carry = 0;
+    for i in 0..16 {
+        let word = a[i] as u64 + b[i] as u64 + carry;
+        sum[i] = word as u32;
+        carry = word >> 32;
+    }
+
+    if carry != 0 || !is_less(&sum, modulus) {
+        let mut borrow = 0;
+        for i in 0..16 {
+            let word = (sum[i] as u64).wrapping_sub(modulus[i] as u64 + borrow);
+            sum[i] = word as u32;
+            borrow = word >> 63;
+        }
+    }
+
+    sum
+}
+
+fn is_less(a: &Operand, b: &Operand) -> bool {
+    a.iter().rev().lt(b.iter().rev())
+}
+//ENDIF
@@ -0,0 +1,40 @@
+//INCLUDEFILE hal-facade
+//! The parts of `esp-hal` this project uses, re-exported in one place.
+//!
+//! The project imports its `esp-hal` types from here, e.g. with
+//! `use crate::hal::CpuClock` in the library, rather than from `esp_hal`
+//! directly. When an `esp-hal` release moves or renames one of them, only
+//! its line below changes, e.g. to `pub use esp_hal::clock::Clock as
+//! CpuClock;`, and the rest of the project keeps compiling. This list is also
+//! what to look for in the release notes when migrating.
+//!
+//! To keep it that way:
+//!
+//! - Re-export what the project uses, not whole modules: a glob re-export
+//!   hides which items a migration touches.
+//! - When a release changes a signature rather than a path, add a function
+//!   here adapting the new one to the old, and update its callers at your
+//!   own pace.
+//! - Import new `esp-hal` types through this module as well. The modules the
+//!   options of esp-generate added still import theirs from `esp_hal`, move
+//!   them here as you change them.
+
+pub use esp_hal::{clock::CpuClock, init, Config};
+//IF !option("embassy")
+pub use esp_hal::main;
+//ENDIF
+//IF option("unstable-hal")
+
+// Behind the `unstable` feature of `esp-hal`, which is more likely to change
+// between releases
+pub use esp_hal::delay::Delay;
+//IF option("embassy") && option("has-systimer")
+pub use esp_hal::timer::systimer::SystemTimer;
+//ENDIF
+//IF (option("embassy") && !option("has-systimer")) || option("wifi") || option("ble")
+pub use esp_hal::timer::timg::TimerGroup;
+//ENDIF
+//IF option("wifi") || option("ble")
+pub use esp_hal::rng::Rng;
+//ENDIF
+//ENDIF
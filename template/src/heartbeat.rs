@@ -0,0 +1,76 @@
+//INCLUDEFILE heartbeat
+//! A sign of life, so that a freshly flashed board visibly runs.
+//!
+//IF option("has-led")
+//REPLACE board-name board-name
+//! Every second, the LED of the board-name toggles.
+//ELSE
+//REPLACE board-name board-name
+//! Every second, a message is logged. The LED of the board-name is an
+//! addressable RGB LED, which toggling its pin does not light up.
+//ENDIF
+
+//IF option("has-led")
+use esp_hal::gpio::Output;
+//ELSE
+//IF option("probe-rs")
+//+use defmt::info;
+//ELSE
+use log::info;
+//ENDIF
+//ENDIF
+
+/// The time between two beats, in milliseconds.
+pub const PERIOD_MS: u64 = 1000;
+
+pub struct Heartbeat {
+    //IF option("has-led")
+    led: Output<'static>,
+    //ELSE
+    beats: u32,
+    //ENDIF
+    next_beat_us: u64,
+}
+
+impl Heartbeat {
+    //IF option("has-led")
+    pub fn new(led: Output<'static>) -> Self {
+        Self {
+            led,
+            next_beat_us: 0,
+        }
+    }
+    //ELSE
+    pub fn new() -> Self {
+        Self {
+            beats: 0,
+            next_beat_us: 0,
+        }
+    }
+    //ENDIF
+
+    /// Beats if a period has passed since the last beat. To be called from a
+    /// loop at least every `PERIOD_MS`.
+    pub fn tick(&mut self) {
+        let now_us = esp_hal::time::now().ticks();
+        if now_us < self.next_beat_us {
+            return;
+        }
+        self.next_beat_us = now_us + PERIOD_MS * 1000;
+
+        //IF option("has-led")
+        self.led.toggle();
+        //ELSE
+        self.beats = self.beats.wrapping_add(1);
+        info!("Heartbeat #{}", self.beats);
+        //ENDIF
+    }
+}
+//IF !option("has-led")
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+//ENDIF
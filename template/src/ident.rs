@@ -0,0 +1,59 @@
+//INCLUDEFILE ident
+//! The identity of the device, derived from the MAC address in its eFuses.
+//!
+//! The MAC address is unique to each chip, so the names derived from it tell
+//! devices running the same firmware apart, e.g. as BLE advertising name or
+//! MQTT client ID.
+
+use esp_hal::efuse::Efuse;
+
+/// The prefix of the device name.
+//REPLACE project-name project-name
+const NAME_PREFIX: &str = "project-name";
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// The base MAC address, most significant byte first. This is read from the
+/// eFuses, unless overridden by `Efuse::set_mac_address`.
+pub fn mac_address() -> [u8; 6] {
+    Efuse::mac_address()
+}
+
+/// The name of the device, the project name followed by the last three bytes
+/// of its MAC address, e.g. `my-project-a1b2c3`.
+pub fn device_name() -> DeviceName {
+    let mac = mac_address();
+
+    let mut name = [0; NAME_PREFIX.len() + 7];
+    name[..NAME_PREFIX.len()].copy_from_slice(NAME_PREFIX.as_bytes());
+    name[NAME_PREFIX.len()] = b'-';
+    for (i, byte) in mac[3..].iter().enumerate() {
+        name[NAME_PREFIX.len() + 1 + 2 * i] = HEX_DIGITS[usize::from(byte >> 4)];
+        name[NAME_PREFIX.len() + 2 + 2 * i] = HEX_DIGITS[usize::from(byte & 0xF)];
+    }
+
+    DeviceName(name)
+}
+
+/// A device name returned by [device_name].
+pub struct DeviceName([u8; NAME_PREFIX.len() + 7]);
+
+impl DeviceName {
+    pub fn as_str(&self) -> &str {
+        // Only ever contains the project name and ASCII hex digits
+        core::str::from_utf8(&self.0).unwrap()
+    }
+}
+//IF option("ble")
+
+/// A BLE static random address derived from the MAC address, most significant
+/// byte first. It stays the same across reboots, unlike a randomly generated
+/// one, so bonded centrals recognize the device.
+pub fn ble_address() -> [u8; 6] {
+    let mut address = mac_address();
+    // The two most significant bits of a static random address are set
+    address[0] |= 0xC0;
+
+    address
+}
+//ENDIF
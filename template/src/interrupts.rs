@@ -0,0 +1,99 @@
+//INCLUDEFILE interrupts
+//! Interrupt handlers for the BOOT button and a periodic timer.
+//!
+//! A handler runs in between any two instructions of `main`, so the state it
+//! shares with `main` is kept in a `critical_section::Mutex`, which can only be
+//! accessed within `critical_section::with`. Interrupts are disabled while its
+//! closure runs, so neither side ever sees the state half-updated. Keep these
+//! closures, and the handlers, short: other interrupts wait until they end.
+//!
+//! Drivers a handler needs, e.g. to clear the interrupt, are moved into such
+//! a mutex once set up, before their interrupt is enabled.
+
+use core::cell::{Cell, RefCell};
+
+use critical_section::Mutex;
+use esp_hal::{
+    gpio::{Event, Input, InputPin, Io, Pull},
+    handler,
+    interrupt::InterruptConfigurable,
+    peripheral::Peripheral,
+    peripherals::IO_MUX,
+    time::ExtU64,
+    timer::{PeriodicTimer, Timer},
+    Blocking,
+};
+
+/// The period of the timer interrupt, in milliseconds.
+pub const TICK_MS: u64 = 1000;
+
+static BUTTON: Mutex<RefCell<Option<Input<'static>>>> = Mutex::new(RefCell::new(None));
+static TIMER: Mutex<RefCell<Option<PeriodicTimer<'static, Blocking>>>> =
+    Mutex::new(RefCell::new(None));
+
+static PRESSES: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+static TICKS: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+
+/// Count the presses of the button, active low, and the ticks of the timer
+/// in their interrupt handlers.
+pub fn init(
+    io_mux: IO_MUX,
+    button: impl Peripheral<P = impl InputPin> + 'static,
+    timer: impl Peripheral<P = impl Timer> + 'static,
+) {
+    let mut io = Io::new(io_mux);
+    io.set_interrupt_handler(on_gpio);
+    let mut button = Input::new(button, Pull::Up);
+
+    let mut timer = PeriodicTimer::new(timer);
+    timer.set_interrupt_handler(on_timer);
+
+    // Interrupts only fire once the critical section ends, when the handlers
+    // find the drivers in place
+    critical_section::with(|cs| {
+        button.listen(Event::FallingEdge);
+        BUTTON.borrow_ref_mut(cs).replace(button);
+
+        timer.enable_interrupt(true);
+        timer.start(TICK_MS.millis()).unwrap();
+        TIMER.borrow_ref_mut(cs).replace(timer);
+    });
+}
+
+/// The number of presses of the button so far.
+pub fn presses() -> u32 {
+    critical_section::with(|cs| PRESSES.borrow(cs).get())
+}
+
+/// The number of timer ticks so far.
+pub fn ticks() -> u32 {
+    critical_section::with(|cs| TICKS.borrow(cs).get())
+}
+
+/// Handles the interrupts of all GPIOs, only the button listens for any.
+#[handler]
+fn on_gpio() {
+    critical_section::with(|cs| {
+        let mut button = BUTTON.borrow_ref_mut(cs);
+        let Some(button) = button.as_mut() else {
+            return;
+        };
+
+        if button.is_interrupt_set() {
+            button.clear_interrupt();
+            let presses = PRESSES.borrow(cs);
+            presses.set(presses.get().wrapping_add(1));
+        }
+    });
+}
+
+#[handler]
+fn on_timer() {
+    critical_section::with(|cs| {
+        if let Some(timer) = TIMER.borrow_ref_mut(cs).as_mut() {
+            timer.clear_interrupt();
+        }
+        let ticks = TICKS.borrow(cs);
+        ticks.set(ticks.get().wrapping_add(1));
+    });
+}
@@ -0,0 +1,39 @@
+//INCLUDEFILE channels
+//! Communication between tasks.
+//!
+//! - A [`Channel`] queues values from one or more senders to one or more
+//!   receivers, each value is received once. Senders wait while it is full.
+//! - A [`Signal`] holds the latest value for a single waiting task, signalling
+//!   again overwrites a value which was not taken yet.
+//! - A [`Watch`] holds the latest value for any number of receivers, each of
+//!   them is notified of changes.
+//!
+//! All of these are safe to share between tasks, and interrupts, as `static`s
+//! thanks to the [`CriticalSectionRawMutex`].
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::Channel;
+use embassy_sync::signal::Signal;
+use embassy_sync::watch::Watch;
+
+/// A value sent from the producer to the consumer task.
+#[derive(Clone, Copy)]
+pub struct Reading {
+    pub sequence: u32,
+}
+
+/// The readings, from the producer to the consumer task. Up to 4 readings are
+/// queued.
+pub static READINGS: Channel<CriticalSectionRawMutex, Reading, 4> = Channel::new();
+
+/// The number of readings received by the consumer task, signalled every ten
+/// readings.
+pub static RECEIVED: Signal<CriticalSectionRawMutex, u32> = Signal::new();
+
+/// The latest reading, for up to 2 receivers.
+pub static LATEST: Watch<CriticalSectionRawMutex, Reading, 2> = Watch::new();
+//IF option("battery")
+
+/// The battery voltage in millivolts, signalled when it is low.
+pub static LOW_BATTERY: Signal<CriticalSectionRawMutex, u32> = Signal::new();
+//ENDIF
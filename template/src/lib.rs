@@ -1 +1,63 @@
+//INCLUDEFILE !esp-idf-std
 #![no_std]
+//IF option("battery")
+
+pub mod battery;
+//ENDIF
+//IF option("devkit")
+
+pub mod pins;
+//ENDIF
+//IF option("channels")
+
+pub mod ipc;
+//ENDIF
+//IF option("ui-slint")
+
+pub mod ui;
+//ENDIF
+//IF option("audio-out")
+
+pub mod audio;
+//ENDIF
+//IF option("hw-crypto")
+
+pub mod crypto;
+//ENDIF
+//IF option("beacon")
+
+pub mod beacon;
+//ENDIF
+//IF option("trace")
+
+pub mod trace;
+//ENDIF
+//IF option("heartbeat")
+
+pub mod heartbeat;
+//ENDIF
+//IF option("interrupts")
+
+pub mod interrupts;
+//ENDIF
+//IF option("hal-facade")
+
+pub mod hal;
+//ENDIF
+//IF option("wifi-sta")
+
+pub mod wifi;
+//ENDIF
+//IF option("net-tools")
+
+pub mod net;
+//ENDIF
+//IF option("metrics")
+
+pub mod metrics;
+//ENDIF
+//IF option("remote-logging")
+
+pub mod remote_log;
+//ENDIF
+//ANCHOR modules
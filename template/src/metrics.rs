@@ -0,0 +1,190 @@
+//INCLUDEFILE metrics
+//! Counters and gauges, served over HTTP in the Prometheus text format.
+//!
+//! A [`Counter`] only goes up, e.g. the number of requests handled, while a
+//! [`Gauge`] is set to the current value of something, e.g. a temperature.
+//! Declare them as `static`s, list them in [`COUNTERS`] or [`GAUGES`], and
+//! update them from any task or interrupt handler.
+//!
+//! [`serve`] answers `GET /metrics` on [`PORT`], for Prometheus to scrape the
+//! device with e.g.
+//!
+//! ```yaml
+//! scrape_configs:
+//!   - job_name: esp
+//!     static_configs:
+//!       - targets: ["<address of the device>:80"]
+//! ```
+
+use core::{
+    cell::Cell,
+    fmt::{self, Display, Write as _},
+};
+
+use critical_section::Mutex;
+use embassy_net::{
+    tcp::{Error, TcpSocket},
+    Stack,
+};
+use embassy_time::{Duration, Instant};
+use embedded_io_async::Write;
+use heapless::String;
+//IF option("probe-rs")
+//+use defmt::warn;
+//ELSE
+use log::warn;
+//ENDIF
+
+/// The port of the HTTP server.
+pub const PORT: u16 = 80;
+/// How long a client may take to send its request.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The number of requests to the HTTP server.
+pub static REQUESTS: Counter = Counter::new("http_requests_total", "Requests to the HTTP server");
+/// The number of times the Wi-Fi station lost its connection.
+pub static WIFI_DISCONNECTS: Counter = Counter::new(
+    "wifi_disconnects_total",
+    "Times the Wi-Fi station lost its connection",
+);
+/// The time since boot, updated when the metrics are read.
+pub static UPTIME: Gauge = Gauge::new("uptime_seconds", "Time since boot");
+
+/// The counters served.
+pub static COUNTERS: &[&Counter] = &[&REQUESTS, &WIFI_DISCONNECTS];
+/// The gauges served.
+pub static GAUGES: &[&Gauge] = &[&UPTIME];
+
+/// A value which only goes up.
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: Mutex<Cell<u64>>,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, amount: u64) {
+        critical_section::with(|cs| {
+            let value = self.value.borrow(cs);
+            value.set(value.get().wrapping_add(amount));
+        });
+    }
+
+    pub fn get(&self) -> u64 {
+        critical_section::with(|cs| self.value.borrow(cs).get())
+    }
+}
+
+/// A value which goes up and down.
+pub struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    value: Mutex<Cell<i64>>,
+}
+
+impl Gauge {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    pub fn set(&self, value: i64) {
+        critical_section::with(|cs| self.value.borrow(cs).set(value));
+    }
+
+    pub fn get(&self) -> i64 {
+        critical_section::with(|cs| self.value.borrow(cs).get())
+    }
+}
+
+/// Write all metrics in the Prometheus text format.
+pub fn render(out: &mut impl fmt::Write) -> fmt::Result {
+    for counter in COUNTERS {
+        write_metric(out, counter.name, counter.help, "counter", counter.get())?;
+    }
+    for gauge in GAUGES {
+        write_metric(out, gauge.name, gauge.help, "gauge", gauge.get())?;
+    }
+
+    Ok(())
+}
+
+fn write_metric(
+    out: &mut impl fmt::Write,
+    name: &str,
+    help: &str,
+    kind: &str,
+    value: impl Display,
+) -> fmt::Result {
+    write!(out, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+}
+
+/// Answers one HTTP request at a time on [`PORT`].
+#[embassy_executor::task]
+pub async fn serve(stack: Stack<'static>) {
+    let mut rx_buffer = [0; 1024];
+    let mut tx_buffer = [0; 1024];
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        socket.set_timeout(Some(TIMEOUT));
+        if socket.accept(PORT).await.is_err() {
+            continue;
+        }
+
+        if let Err(error) = respond(&mut socket).await {
+            warn!("Failed to answer a request: {:?}", error);
+        }
+        socket.close();
+        let _ = socket.flush().await;
+    }
+}
+
+async fn respond(socket: &mut TcpSocket<'_>) -> Result<(), Error> {
+    // Read up to the end of the headers, only the request line matters
+    let mut request = [0; 512];
+    let mut len = 0;
+    while len < request.len() && !request[..len].windows(4).any(|end| end == b"\r\n\r\n") {
+        match socket.read(&mut request[len..]).await? {
+            0 => return Ok(()),
+            read => len += read,
+        }
+    }
+    REQUESTS.increment();
+
+    if !request.starts_with(b"GET /metrics ") {
+        return socket.write_all(b"HTTP/1.0 404 Not Found\r\n\r\n").await;
+    }
+
+    UPTIME.set(Instant::now().as_secs() as i64);
+    // Grow the body along with the metrics
+    let mut body = String::<1024>::new();
+    if render(&mut body).is_err() {
+        return socket
+            .write_all(b"HTTP/1.0 500 Internal Server Error\r\n\r\n")
+            .await;
+    }
+
+    let mut head = String::<128>::new();
+    let _ = write!(
+        head,
+        "HTTP/1.0 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    );
+    socket.write_all(head.as_bytes()).await?;
+    socket.write_all(body.as_bytes()).await
+}
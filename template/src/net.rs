@@ -0,0 +1,178 @@
+//INCLUDEFILE net-tools
+//! Checking that the network stack works on the network it joined.
+//!
+//! [`ping`] sends an ICMP echo request and waits for the reply, [`resolve`]
+//! looks a name up with the DNS servers the DHCP server announced. Both can
+//! be called from any task with the [`Stack`]. [`diagnostics`] runs them
+//! every [`PERIOD`] once the stack is configured and logs the results: a
+//! failing ping of the gateway points at the Wi-Fi or DHCP, a failing lookup
+//! at the DNS servers, and a failing ping of [`HOST`] at the way out of the
+//! local network.
+
+use embassy_net::{
+    dns::{DnsQueryType, IpAddress},
+    raw::{IpProtocol, IpVersion, PacketMetadata, RawSocket},
+    Ipv4Address, Stack,
+};
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use esp_wifi::wifi::{WifiDevice, WifiStaDevice};
+use smoltcp::{
+    phy::ChecksumCapabilities,
+    wire::{Icmpv4Packet, Icmpv4Repr, Ipv4Packet, Ipv4Repr},
+};
+//IF option("probe-rs")
+//+use defmt::{info, warn};
+//ELSE
+use log::{info, warn};
+//ENDIF
+
+/// The name looked up and pinged by [`diagnostics`].
+pub const HOST: &str = "example.com";
+/// The interval of [`diagnostics`].
+pub const PERIOD: Duration = Duration::from_secs(30);
+/// How long [`ping`] waits for the reply.
+pub const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Identifies the echo requests of this device in the replies.
+const IDENT: u16 = 0xe5e5;
+const PAYLOAD: [u8; 32] = [0xaa; 32];
+const IPV4_HEADER_LEN: usize = 20;
+const ECHO_HEADER_LEN: usize = 8;
+
+/// Why a [`ping`] failed.
+#[derive(Clone, Copy, Debug)]
+//IF option("probe-rs")
+//+#[derive(defmt::Format)]
+//ENDIF
+pub enum PingError {
+    /// The stack has no IPv4 address yet.
+    NotConfigured,
+    /// No reply within [`PING_TIMEOUT`].
+    Timeout,
+}
+
+/// Pings the gateway, resolves [`HOST`] and pings it every [`PERIOD`].
+#[embassy_executor::task]
+pub async fn diagnostics(stack: Stack<'static>) {
+    let mut seq_no = 0u16;
+    loop {
+        stack.wait_config_up().await;
+
+        if let Some(gateway) = stack.config_v4().and_then(|config| config.gateway) {
+            log_ping(stack, "the gateway", gateway, seq_no).await;
+        }
+        match resolve(stack, HOST).await {
+            Some(address) => {
+                info!("{} is {}", HOST, address);
+                log_ping(stack, HOST, address, seq_no).await;
+            }
+            None => warn!("Failed to resolve {}", HOST),
+        }
+
+        seq_no = seq_no.wrapping_add(1);
+        Timer::after(PERIOD).await;
+    }
+}
+
+async fn log_ping(stack: Stack<'_>, name: &str, address: Ipv4Address, seq_no: u16) {
+    match ping(stack, address, seq_no).await {
+        Ok(round_trip) => info!("Reply from {} in {} ms", name, round_trip.as_millis()),
+        Err(error) => warn!("No reply from {}: {:?}", name, error),
+    }
+}
+
+/// The first IPv4 address of `name`, or `None` if the lookup failed.
+pub async fn resolve(stack: Stack<'_>, name: &str) -> Option<Ipv4Address> {
+    let addresses = stack.dns_query(name, DnsQueryType::A).await.ok()?;
+    addresses.iter().find_map(|address| match address {
+        IpAddress::Ipv4(address) => Some(*address),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    })
+}
+
+/// Sends an ICMP echo request to `address`, returning the round-trip time of
+/// the reply.
+pub async fn ping(
+    stack: Stack<'_>,
+    address: Ipv4Address,
+    seq_no: u16,
+) -> Result<Duration, PingError> {
+    let source = stack
+        .config_v4()
+        .ok_or(PingError::NotConfigured)?
+        .address
+        .address();
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 2];
+    let mut rx_buffer = [0; 256];
+    let mut tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut tx_buffer = [0; 128];
+    let socket = RawSocket::new::<WifiDevice<'static, WifiStaDevice>>(
+        stack,
+        IpVersion::Ipv4,
+        IpProtocol::Icmp,
+        &mut rx_meta,
+        &mut rx_buffer,
+        &mut tx_meta,
+        &mut tx_buffer,
+    );
+
+    // Raw sockets send and receive whole IP packets, header included
+    let checksums = ChecksumCapabilities::default();
+    let echo = Icmpv4Repr::EchoRequest {
+        ident: IDENT,
+        seq_no,
+        data: &PAYLOAD,
+    };
+    let header = Ipv4Repr {
+        src_addr: source,
+        dst_addr: address,
+        next_header: IpProtocol::Icmp,
+        payload_len: echo.buffer_len(),
+        hop_limit: 64,
+    };
+    let mut request = [0; IPV4_HEADER_LEN + ECHO_HEADER_LEN + PAYLOAD.len()];
+    header.emit(&mut Ipv4Packet::new_unchecked(&mut request[..]), &checksums);
+    echo.emit(
+        &mut Icmpv4Packet::new_unchecked(&mut request[IPV4_HEADER_LEN..]),
+        &checksums,
+    );
+
+    let sent = Instant::now();
+    socket.send(&request).await;
+
+    with_timeout(PING_TIMEOUT, async {
+        let mut reply = [0; 256];
+        loop {
+            // Truncated packets are larger than any echo reply
+            let Ok(len) = socket.recv(&mut reply).await else {
+                continue;
+            };
+            if is_reply(&reply[..len], address, seq_no) {
+                return sent.elapsed();
+            }
+        }
+    })
+    .await
+    .map_err(|_| PingError::Timeout)
+}
+
+/// Whether `packet` is the reply of `address` to our echo request `seq_no`.
+fn is_reply(packet: &[u8], address: Ipv4Address, seq_no: u16) -> bool {
+    let Ok(packet) = Ipv4Packet::new_checked(packet) else {
+        return false;
+    };
+    if packet.src_addr() != address {
+        return false;
+    }
+    let Ok(icmp) = Icmpv4Packet::new_checked(packet.payload()) else {
+        return false;
+    };
+
+    matches!(
+        Icmpv4Repr::parse(&icmp, &ChecksumCapabilities::default()),
+        Ok(Icmpv4Repr::EchoReply { ident, seq_no: reply_seq_no, .. })
+            if ident == IDENT && reply_seq_no == seq_no
+    )
+}
@@ -0,0 +1,55 @@
+//INCLUDEFILE panic-policy
+//! What happens after a panic, once `esp-backtrace` has printed it.
+//!
+//! A device in the field should recover on its own rather than hang until it
+//! is power cycled. The policy is chosen at build time by
+//! `APP_CONFIG_PANIC_POLICY` in `.cargo/config.toml`:
+//!
+//! - `halt`: Stops, e.g. to inspect the device with a debugger.
+//! - `reboot`: Reboots after `APP_CONFIG_PANIC_DELAY_MS`.
+//IF option("has-deep-sleep")
+//! - `deep-sleep`: Spends the delay in deep sleep before rebooting, which
+//!   saves power when the panic happens again at every boot.
+//ENDIF
+
+/// The policy applied after a panic.
+pub const POLICY: &str = esp_config::esp_config_str!("APP_CONFIG_PANIC_POLICY");
+
+/// How long to wait after a panic before rebooting, in milliseconds.
+#[cfg(not(panic_policy_halt))]
+const DELAY_MS: u32 = esp_config::esp_config_int!(u32, "APP_CONFIG_PANIC_DELAY_MS");
+
+#[cfg(panic_policy_halt)]
+#[no_mangle]
+extern "Rust" fn custom_halt() -> ! {
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+#[cfg(panic_policy_reboot)]
+#[no_mangle]
+extern "Rust" fn custom_halt() -> ! {
+    esp_hal::delay::Delay::new().delay_millis(DELAY_MS);
+    esp_hal::reset::software_reset();
+
+    loop {
+        core::hint::spin_loop();
+    }
+}
+//IF option("has-deep-sleep")
+
+#[cfg(panic_policy_deep_sleep)]
+#[no_mangle]
+extern "Rust" fn custom_halt() -> ! {
+    use core::time::Duration;
+
+    use esp_hal::peripherals::LPWR;
+    use esp_hal::rtc_cntl::{sleep::TimerWakeupSource, Rtc};
+
+    // Nothing else runs after a panic, the RTC is not in use anymore
+    let mut rtc = Rtc::new(unsafe { LPWR::steal() });
+    let timer = TimerWakeupSource::new(Duration::from_millis(DELAY_MS.into()));
+    rtc.sleep_deep(&[&timer])
+}
+//ENDIF
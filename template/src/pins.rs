@@ -0,0 +1,50 @@
+//INCLUDEFILE devkit
+//! The pins of the board, by function. Moving to a different board only
+//! requires changing this file.
+
+use esp_hal::gpio::GpioPin;
+
+//REPLACE board-name board-name
+/// The pins of the board-name used by the application.
+pub struct Pins {
+    /// The on-board LED, an addressable RGB LED on most Espressif DevKits.
+    //REPLACE 8 pin-led
+    pub led: GpioPin<8>,
+    /// The BOOT button, usable by the application once booted.
+    //REPLACE 9 pin-button
+    pub button: GpioPin<9>,
+    /// I2C data.
+    //REPLACE 4 pin-sda
+    pub sda: GpioPin<4>,
+    /// I2C clock.
+    //REPLACE 5 pin-scl
+    pub scl: GpioPin<5>,
+    /// UART transmit, connected to the USB-to-UART bridge.
+    //REPLACE 21 pin-tx
+    pub tx: GpioPin<21>,
+    /// UART receive, connected to the USB-to-UART bridge.
+    //REPLACE 20 pin-rx
+    pub rx: GpioPin<20>,
+}
+
+/// Moves the board's pins out of the `Peripherals` returned by `esp_hal::init`,
+/// leaving all other peripherals usable.
+#[macro_export]
+macro_rules! pins {
+    ($peripherals:ident) => {
+        $crate::pins::Pins {
+            //REPLACE 8 pin-led
+            led: $peripherals.GPIO8,
+            //REPLACE 9 pin-button
+            button: $peripherals.GPIO9,
+            //REPLACE 4 pin-sda
+            sda: $peripherals.GPIO4,
+            //REPLACE 5 pin-scl
+            scl: $peripherals.GPIO5,
+            //REPLACE 21 pin-tx
+            tx: $peripherals.GPIO21,
+            //REPLACE 20 pin-rx
+            rx: $peripherals.GPIO20,
+        }
+    };
+}
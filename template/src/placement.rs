@@ -0,0 +1,62 @@
+//INCLUDEFILE ram-placement
+//! Where code and data are placed in memory, and when to choose.
+//!
+//! By default, code runs from flash through the instruction cache, constants
+//! stay in flash and `static`s are in internal RAM (DRAM). That suits most of
+//! the firmware. The exceptions:
+//!
+//! - Code which must not wait for a cache miss, e.g. an interrupt handler with
+//!   a tight deadline or a loop timing a protocol in software, or which runs
+//!   while the flash is being written, when the cache is disabled. `#[ram]`
+//!   places a function in IRAM, see [`checksum`].
+//! - Buffers handed to DMA. DMA only reaches internal RAM: constants in flash,
+//!   and on most chips PSRAM, are out of its reach. `dma_buffers!` and
+//!   `dma_tx_buffer!` allocate buffers and their descriptors in `static`s in
+//!   internal RAM, copy the data into them as `main` does.
+//IF option("has-rtc-ram")
+//! - Data which survives deep sleep. `#[ram(rtc_fast)]` places a `static` in
+//!   RTC fast memory, which stays powered. With `persistent`, it also keeps
+//!   its value across software and watchdog resets, see [`count_boot`]; with
+//!   `zeroed`, it starts at zero without taking room in the image.
+//ENDIF
+//!
+//! IRAM and RTC memory are small, so only place there what needs to be. When
+//! a region is full, linking fails with an error naming it.
+
+use esp_hal::ram;
+
+/// Data to send over DMA, a constant in flash.
+pub const PATTERN: [u8; 16] = *b"esp-generate DMA";
+
+//IF option("has-rtc-ram")
+/// The number of boots since power-on, which clears RTC memory.
+#[ram(rtc_fast, persistent)]
+static mut BOOT_COUNT: u32 = 0;
+
+/// Count this boot, returning the number of boots since power-on. Call it
+/// once at boot.
+pub fn count_boot() -> u32 {
+    let count = core::ptr::addr_of_mut!(BOOT_COUNT);
+    unsafe {
+        let boots = count.read_volatile().wrapping_add(1);
+        count.write_volatile(boots);
+        boots
+    }
+}
+
+//ENDIF
+/// The CRC-32 of `data`, computed by code in RAM.
+///
+/// To run while the flash is busy, everything it calls must be in RAM too.
+/// This loop calls nothing, check the disassembly of anything larger.
+#[ram]
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
@@ -0,0 +1,135 @@
+//INCLUDEFILE provisioning
+//! The data provisioned to each device when flashing it, e.g. its ID and keys.
+//!
+//! `scripts/manufacturing/provision.py` turns the rows of
+//! `manufacturing/provisioning.csv` into one image per device, which
+//! `scripts/manufacturing/flash-device.sh` writes along with the firmware. See
+//! docs/manufacturing.md.
+//!
+//! The image starts with the `PROV` magic and a format version, followed by
+//! the number of entries and each entry as a length-prefixed key and value.
+//! It ends with the CRC-32 of everything before it.
+
+use embedded_storage::ReadStorage;
+use esp_storage::FlashStorage;
+
+/// Where the image is written, the NVS partition of espflash's default
+/// partition table, which the firmware does not use otherwise.
+pub const OFFSET: u32 = 0x9000;
+
+/// The largest image `provision.py` writes.
+pub const MAX_SIZE: usize = 1024;
+
+const MAGIC: &[u8; 4] = b"PROV";
+const VERSION: u8 = 1;
+
+/// Why the provisioning data could not be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Reading the flash failed.
+    Flash,
+    /// There is no image, the device has not been provisioned.
+    NotProvisioned,
+    /// The image was written by a newer version of `provision.py`.
+    UnsupportedVersion,
+    /// The image is truncated or its CRC does not match.
+    Corrupted,
+}
+
+impl Error {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Error::Flash => "reading the flash failed",
+            Error::NotProvisioned => "the device has not been provisioned",
+            Error::UnsupportedVersion => "unsupported provisioning data version",
+            Error::Corrupted => "the provisioning data is corrupted",
+        }
+    }
+}
+
+/// The provisioned entries, as read from the flash.
+pub struct Provisioning {
+    image: [u8; MAX_SIZE],
+    /// The length of the entries, after the header.
+    len: usize,
+}
+
+impl Provisioning {
+    /// Read and verify the provisioning data.
+    pub fn read() -> Result<Self, Error> {
+        let mut image = [0; MAX_SIZE];
+        FlashStorage::new()
+            .read(OFFSET, &mut image)
+            .map_err(|_| Error::Flash)?;
+
+        if &image[..4] != MAGIC {
+            return Err(Error::NotProvisioned);
+        }
+        if image[4] != VERSION {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        // Walk the entries to find where the CRC is
+        let mut provisioning = Self { image, len: 0 };
+        let mut end = 6;
+        for _ in 0..image[5] {
+            end = provisioning.entry_end(end).ok_or(Error::Corrupted)?;
+        }
+        let crc = image.get(end..end + 4).ok_or(Error::Corrupted)?;
+        if u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]) != crc32(&image[..end]) {
+            return Err(Error::Corrupted);
+        }
+
+        provisioning.len = end - 6;
+        Ok(provisioning)
+    }
+
+    /// The value of an entry, if it is there and valid UTF-8.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries()
+            .find(|(k, _)| *k == key.as_bytes())
+            .and_then(|(_, value)| core::str::from_utf8(value).ok())
+    }
+
+    /// Every entry as its key and value.
+    pub fn entries(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        let mut start = 6;
+        core::iter::from_fn(move || {
+            if start >= 6 + self.len {
+                return None;
+            }
+            let end = self.entry_end(start)?;
+
+            let key_len = usize::from(self.image[start]);
+            let key = &self.image[start + 1..start + 1 + key_len];
+            let value = &self.image[start + 3 + key_len..end];
+            start = end;
+
+            Some((key, value))
+        })
+    }
+
+    /// The end of the entry starting at `start`, if it is within the image.
+    fn entry_end(&self, start: usize) -> Option<usize> {
+        let key_len = usize::from(*self.image.get(start)?);
+        let value_len = self.image.get(start + 1 + key_len..start + 3 + key_len)?;
+        let value_len = usize::from(u16::from_le_bytes([value_len[0], value_len[1]]));
+
+        let end = start + 3 + key_len + value_len;
+        (end <= MAX_SIZE).then_some(end)
+    }
+}
+
+/// The CRC-32 (IEEE) of the data, as computed by Python's `zlib.crc32`.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+
+    !crc
+}
@@ -0,0 +1,128 @@
+//INCLUDEFILE remote-logging
+//! Forwarding the log to a syslog server, besides printing it.
+//!
+//! [`init`] installs a logger which prints every record like `esp-println`
+//! does and queues it as an RFC 5424 syslog message. [`forward`] sends the
+//! queued messages over UDP to the server set with `REMOTE_LOG_HOST` (and
+//! optionally `REMOTE_LOG_PORT`, 514 by default) at build time, e.g.
+//! `REMOTE_LOG_HOST=192.168.1.10 cargo run --release`.
+//!
+//! Logging never waits for the network: records logged while the queue is
+//! full, e.g. before the station is connected, are only printed. UDP does not
+//! retransmit either, so a lossy network loses messages.
+//!
+//! To receive them on a computer, e.g. `nc -kluw 0 514` or rsyslog with
+//! `module(load="imudp") input(type="imudp" port="514")`.
+
+use core::fmt::Write as _;
+
+use embassy_net::{
+    udp::{PacketMetadata, UdpSocket},
+    Ipv4Address, Stack,
+};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel};
+use heapless::String;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// The syslog server, from `REMOTE_LOG_HOST` at build time.
+pub const HOST: Option<&str> = option_env!("REMOTE_LOG_HOST");
+/// The port of the syslog server, from `REMOTE_LOG_PORT` at build time.
+pub const PORT: Option<&str> = option_env!("REMOTE_LOG_PORT");
+const DEFAULT_PORT: u16 = 514;
+
+/// The APP-NAME of the messages.
+const APP_NAME: &str = env!("CARGO_PKG_NAME");
+/// Longer messages are truncated.
+const MAX_MESSAGE_LEN: usize = 256;
+
+/// The messages waiting to be sent.
+static MESSAGES: Channel<CriticalSectionRawMutex, String<MAX_MESSAGE_LEN>, 8> = Channel::new();
+
+static LOGGER: RemoteLogger = RemoteLogger;
+
+/// Install the logger, at the level of `ESP_LOG` or `info`.
+///
+/// Unlike `esp-println`, only a single level is supported, not filters per
+/// module.
+pub fn init() {
+    let level = option_env!("ESP_LOG")
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(LevelFilter::Info);
+    unsafe {
+        log::set_logger_racy(&LOGGER).unwrap();
+        log::set_max_level_racy(level);
+    }
+}
+
+/// Sends the queued messages to the syslog server once the network is up.
+#[embassy_executor::task]
+pub async fn forward(stack: Stack<'static>) {
+    let Some(host) = HOST.and_then(|host| host.parse::<Ipv4Address>().ok()) else {
+        log::warn!("REMOTE_LOG_HOST is not set to an IPv4 address, the log is not forwarded");
+        return;
+    };
+    let port = PORT
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+
+    stack.wait_config_up().await;
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 1];
+    let mut rx_buffer = [0; 16];
+    let mut tx_meta = [PacketMetadata::EMPTY; 4];
+    let mut tx_buffer = [0; 4 * MAX_MESSAGE_LEN];
+    let mut socket = UdpSocket::new(
+        stack,
+        &mut rx_meta,
+        &mut rx_buffer,
+        &mut tx_meta,
+        &mut tx_buffer,
+    );
+    socket.bind(0).unwrap();
+
+    loop {
+        let message = MESSAGES.receive().await;
+        // Messages sent while the network is down are lost
+        let _ = socket.send_to(message.as_bytes(), (host, port)).await;
+    }
+}
+
+struct RemoteLogger;
+
+impl Log for RemoteLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        esp_println::println!("{} - {}", record.level(), record.args());
+
+        // The facility is user-level messages, with neither a timestamp nor a
+        // hostname. A message too long for the buffer is cut short.
+        let mut message = String::new();
+        let _ = write!(
+            message,
+            "<{}>1 - - {} - - - {}",
+            8 + severity(record.level()),
+            APP_NAME,
+            record.args()
+        );
+        let _ = MESSAGES.try_send(message);
+    }
+
+    fn flush(&self) {}
+}
+
+/// The syslog severity of `level`.
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
@@ -0,0 +1,90 @@
+//INCLUDEFILE reset-reason
+//! Why the chip last reset, telling a clean start from a crash, a brownout or
+//! a watchdog when the device restarts unexpectedly.
+//IF option("has-rtc-ram")
+//!
+//! Panics are recorded in RTC fast memory, which keeps its contents across
+//! every reset but a power-on one.
+//ENDIF
+
+/// Why the chip last reset, grouping the reasons reported by the chip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetReason {
+    /// The chip was powered on, or reset through its EN pin.
+    PowerOn,
+    /// The supply voltage dropped too low, e.g. because of a weak power supply
+    /// or battery, or the current drawn when the radio starts.
+    Brownout,
+    /// A watchdog timer expired, i.e. the firmware got stuck.
+    Watchdog,
+    //IF option("has-rtc-ram")
+    /// The firmware panicked, or raised an exception, before the reset.
+    Panic,
+    //ENDIF
+    /// The firmware reset the chip itself.
+    Software,
+    /// The chip woke up from deep sleep.
+    DeepSleep,
+    /// Another reason, by its code, e.g. a reset over USB by the flasher.
+    Other(u8),
+}
+
+//IF option("has-rtc-ram")
+/// The value of [`PANIC`] after a panic.
+const PANIC_MARKER: u32 = 0x5041_4E43;
+
+#[esp_hal::ram(rtc_fast, persistent)]
+static mut PANIC: u32 = 0;
+
+/// Called by `esp-backtrace` before printing a panic or exception.
+#[no_mangle]
+extern "Rust" fn custom_pre_backtrace() {
+    unsafe { core::ptr::addr_of_mut!(PANIC).write_volatile(PANIC_MARKER) };
+}
+
+//ENDIF
+impl ResetReason {
+    /// Read why the chip last reset. Call it once at boot, as it clears the
+    /// record of a panic.
+    pub fn read() -> Self {
+        // The codes of the ROM, which are the same on every chip
+        let reason = match esp_hal::reset::reset_reason().map(|reason| reason as u8) {
+            Some(0x01) => Self::PowerOn,
+            Some(0x03 | 0x0C) => Self::Software,
+            Some(0x05) => Self::DeepSleep,
+            Some(0x07 | 0x08 | 0x09 | 0x0B | 0x0D | 0x10 | 0x11 | 0x12) => Self::Watchdog,
+            Some(0x0F) => Self::Brownout,
+            Some(code) => Self::Other(code),
+            None => Self::Other(0),
+        };
+        //IF option("has-rtc-ram")
+
+        let panicked = unsafe {
+            let panic = core::ptr::addr_of_mut!(PANIC);
+            let marker = panic.read_volatile();
+            panic.write_volatile(0);
+            marker == PANIC_MARKER
+        };
+        // The record does not survive power cycling, a stale one is ignored
+        if panicked && reason != Self::PowerOn {
+            return Self::Panic;
+        }
+        //ENDIF
+
+        reason
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PowerOn => "power-on",
+            Self::Brownout => "brownout",
+            Self::Watchdog => "watchdog",
+            //IF option("has-rtc-ram")
+            Self::Panic => "panic",
+            //ENDIF
+            Self::Software => "software",
+            Self::DeepSleep => "deep sleep",
+            Self::Other(_) => "other",
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//INCLUDEFILE sync
+//! Sharing data between the two cores of the chip.
+//!
+//! `main` runs on the first core (PRO_CPU). [`start_app_core`] runs
+//! [`app_core`] on the second core (APP_CPU), and both update the state below
+//! at the same time. What is safe to share between them:
+//!
+//! - Atomics, e.g. [`AtomicU32`], are lock-free and safe across cores and
+//!   interrupts. Use read-modify-write operations like `fetch_add`: a `load`
+//!   followed by a `store` loses the updates the other core makes in between.
+//! - A [`critical_section::Mutex`] guards anything else. `critical_section::with`
+//!   disables interrupts on the current core and takes a spinlock shared by
+//!   both cores, so only one of them runs the closure at a time. Keep it short,
+//!   the other core spins while waiting.
+//! - The primitives of `embassy-sync` are only safe across cores with the
+//!   [`CriticalSectionRawMutex`]. `NoopRawMutex` and `ThreadModeRawMutex` only
+//!   exclude the other tasks of the same executor, on the same core.
+//!
+//! On single-core chips, `critical_section::with` only disables interrupts,
+//! so code written this way is correct, and no slower, there too.
+//!
+//! A `static mut`, or a `Cell` or `RefCell` shared without a mutex, is never
+//! safe: the compiler assumes no other code changes it concurrently, and the
+//! other core or an interrupt does.
+
+use core::{
+    cell::{Cell, RefCell},
+    ptr::addr_of_mut,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use critical_section::Mutex;
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex as BlockingMutex};
+use esp_hal::{
+    cpu_control::{CpuControl, Stack},
+    delay::Delay,
+    peripherals::CPU_CTRL,
+    Cpu,
+};
+//IF option("probe-rs")
+//+use defmt::info;
+//ELSE
+use log::info;
+//ENDIF
+
+/// The stack of the second core, which does not share the first one's.
+static mut APP_CORE_STACK: Stack<8192> = Stack::new();
+
+/// The number of times either core called [`record`]. An atomic, as nothing
+/// else needs updating with it.
+pub static RECORDS: AtomicU32 = AtomicU32::new(0);
+
+/// The records of each core, updated together.
+static PER_CORE: Mutex<RefCell<PerCore>> = Mutex::new(RefCell::new(PerCore {
+    pro_core: 0,
+    app_core: 0,
+}));
+
+/// The last value the second core computed, as an `embassy-sync` mutex.
+pub static LATEST: BlockingMutex<CriticalSectionRawMutex, Cell<u32>> =
+    BlockingMutex::new(Cell::new(0));
+
+/// The number of records per core.
+#[derive(Clone, Copy, Debug)]
+pub struct PerCore {
+    pub pro_core: u32,
+    pub app_core: u32,
+}
+
+/// Start [`app_core`] on the second core.
+pub fn start_app_core(cpu_ctrl: CPU_CTRL) {
+    let mut cpu_control = CpuControl::new(cpu_ctrl);
+    let guard = cpu_control
+        .start_app_core(unsafe { &mut *addr_of_mut!(APP_CORE_STACK) }, || app_core())
+        .unwrap();
+    // Dropping the guard would stop the second core again
+    core::mem::forget(guard);
+}
+
+/// Record one unit of work done by the current core.
+pub fn record() {
+    RECORDS.fetch_add(1, Ordering::Relaxed);
+
+    critical_section::with(|cs| {
+        let mut per_core = PER_CORE.borrow_ref_mut(cs);
+        match Cpu::current() {
+            Cpu::ProCpu => per_core.pro_core += 1,
+            Cpu::AppCpu => per_core.app_core += 1,
+        }
+    });
+}
+
+/// The records of each core so far.
+pub fn per_core() -> PerCore {
+    critical_section::with(|cs| *PER_CORE.borrow_ref(cs))
+}
+
+/// Runs on the second core: records work every 100 ms and logs the state
+/// shared with the first core every second.
+pub fn app_core() -> ! {
+    let delay = Delay::new();
+    let mut iterations = 0u32;
+    loop {
+        record();
+        LATEST.lock(|latest| latest.set(iterations));
+
+        if iterations % 10 == 0 {
+            let per_core = per_core();
+            info!(
+                "{} records, {} on the PRO core and {} on the APP core",
+                RECORDS.load(Ordering::Relaxed),
+                per_core.pro_core,
+                per_core.app_core
+            );
+        }
+
+        iterations = iterations.wrapping_add(1);
+        delay.delay_millis(100);
+    }
+}
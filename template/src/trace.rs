@@ -0,0 +1,41 @@
+//INCLUDEFILE trace
+//! Trace events of the embassy executor.
+//!
+//! With its `trace` feature, `embassy-executor` calls these functions when a
+//! task is spawned, woken and polled, and when the executor has nothing left
+//! to run. Each event is logged at the `trace` level along with the time in
+//! microseconds, so they are compiled out unless `DEFMT_LOG` enables this
+//! module, as `cargo trace` does. See docs/tracing.md.
+//!
+//! Executors and tasks are identified by their address.
+
+use defmt::trace;
+
+fn now() -> u64 {
+    esp_hal::time::now().ticks()
+}
+
+#[no_mangle]
+fn _embassy_trace_task_new(executor_id: u32, task_id: u32) {
+    trace!("{=u64} executor {=u32:x} spawned task {=u32:x}", now(), executor_id, task_id);
+}
+
+#[no_mangle]
+fn _embassy_trace_task_ready_begin(executor_id: u32, task_id: u32) {
+    trace!("{=u64} executor {=u32:x} woke task {=u32:x}", now(), executor_id, task_id);
+}
+
+#[no_mangle]
+fn _embassy_trace_task_exec_begin(executor_id: u32, task_id: u32) {
+    trace!("{=u64} executor {=u32:x} polls task {=u32:x}", now(), executor_id, task_id);
+}
+
+#[no_mangle]
+fn _embassy_trace_task_exec_end(executor_id: u32, task_id: u32) {
+    trace!("{=u64} executor {=u32:x} polled task {=u32:x}", now(), executor_id, task_id);
+}
+
+#[no_mangle]
+fn _embassy_trace_executor_idle(executor_id: u32) {
+    trace!("{=u64} executor {=u32:x} is idle", now(), executor_id);
+}
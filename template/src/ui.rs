@@ -0,0 +1,127 @@
+//INCLUDEFILE ui-slint
+//! The Slint UI, rendered by the software renderer line by line to any
+//! `embedded-graphics` display.
+//!
+//! Rendering a line at a time only needs a buffer of one line, the UI itself
+//! is allocated from the heap which includes the PSRAM.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::ops::Range;
+use core::time::Duration;
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::pixelcolor::raw::RawU16;
+use embedded_graphics_core::pixelcolor::Rgb565;
+use embedded_graphics_core::prelude::{Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+use slint::platform::software_renderer::{
+    LineBufferProvider, MinimalSoftwareWindow, RepaintBufferType, Rgb565Pixel,
+};
+use slint::platform::{Platform, PointerEventButton, WindowAdapter, WindowEvent};
+use slint::{LogicalPosition, PhysicalSize, PlatformError};
+
+slint::include_modules!();
+
+/// The width of the display in pixels.
+pub const WIDTH: u32 = 240;
+/// The height of the display in pixels.
+pub const HEIGHT: u32 = 320;
+
+struct EspPlatform {
+    window: Rc<MinimalSoftwareWindow>,
+}
+
+impl Platform for EspPlatform {
+    fn create_window_adapter(&self) -> Result<Rc<dyn WindowAdapter>, PlatformError> {
+        Ok(self.window.clone())
+    }
+
+    fn duration_since_start(&self) -> Duration {
+        Duration::from_micros(esp_hal::time::now().duration_since_epoch().to_micros())
+    }
+}
+
+/// Passes each rendered line on to the display.
+struct DisplayLines<'a, D> {
+    display: &'a mut D,
+    buffer: &'a mut [Rgb565Pixel],
+}
+
+impl<D: DrawTarget<Color = Rgb565>> LineBufferProvider for DisplayLines<'_, D> {
+    type TargetPixel = Rgb565Pixel;
+
+    fn process_line(
+        &mut self,
+        line: usize,
+        range: Range<usize>,
+        render_fn: impl FnOnce(&mut [Rgb565Pixel]),
+    ) {
+        let buffer = &mut self.buffer[range.clone()];
+        render_fn(buffer);
+
+        let area = Rectangle::new(
+            Point::new(range.start as i32, line as i32),
+            Size::new(range.len() as u32, 1),
+        );
+        // A line failing to draw is fixed by the next repaint
+        let _ = self.display.fill_contiguous(
+            &area,
+            buffer.iter().map(|pixel| Rgb565::from(RawU16::new(pixel.0))),
+        );
+    }
+}
+
+/// Show the `MainWindow` of `ui/main.slint` on the display, forever.
+///
+/// `touch` returns the touched position in pixels, if the display is being
+/// touched.
+pub fn run<D: DrawTarget<Color = Rgb565>>(
+    mut display: D,
+    mut touch: impl FnMut() -> Option<(f32, f32)>,
+) -> ! {
+    let window = MinimalSoftwareWindow::new(RepaintBufferType::ReusedBuffer);
+    window.set_size(PhysicalSize::new(WIDTH, HEIGHT));
+    slint::platform::set_platform(Box::new(EspPlatform {
+        window: window.clone(),
+    }))
+    .unwrap();
+
+    let main_window = MainWindow::new().unwrap();
+    main_window.show().unwrap();
+
+    let mut buffer = [Rgb565Pixel(0); WIDTH as usize];
+    let mut last_position = None;
+    loop {
+        slint::platform::update_timers_and_animations();
+
+        let position = touch().map(|(x, y)| LogicalPosition::new(x, y));
+        match (last_position, position) {
+            (None, Some(position)) => window.dispatch_event(WindowEvent::PointerPressed {
+                position,
+                button: PointerEventButton::Left,
+            }),
+            (Some(_), Some(position)) => {
+                window.dispatch_event(WindowEvent::PointerMoved { position })
+            }
+            (Some(position), None) => {
+                window.dispatch_event(WindowEvent::PointerReleased {
+                    position,
+                    button: PointerEventButton::Left,
+                });
+                window.dispatch_event(WindowEvent::PointerExited);
+            }
+            (None, None) => {}
+        }
+        last_position = position;
+
+        window.draw_if_needed(|renderer| {
+            renderer.render_by_line(DisplayLines {
+                display: &mut display,
+                buffer: &mut buffer,
+            });
+        });
+    }
+}
@@ -0,0 +1,97 @@
+//INCLUDEFILE wifi-sta
+//! Keeping the Wi-Fi station connected.
+//!
+//! [`connection`] owns the Wi-Fi controller and supervises the connection:
+//! it connects, waits for the access point to drop it, e.g. when it restarts
+//! or the signal fades, and connects again. Failed attempts are retried after
+//! a delay which doubles every time, from [`MIN_BACKOFF`] up to
+//! [`MAX_BACKOFF`], so an access point which is gone for a while is not
+//! flooded with requests.
+//!
+//! Tasks which need the network do not start using it blindly, they wait
+//! for [`IS_CONNECTED`] to be `true`:
+//!
+//! ```rust,ignore
+//! let mut connected = IS_CONNECTED.receiver().unwrap();
+//! connected.get_and(|connected| *connected).await;
+//! ```
+//!
+//! and can stop on `connected.changed_and(|connected| !*connected)`.
+//!
+//! The network is set when building, e.g.
+//! `WIFI_SSID=ssid WIFI_PASSWORD=password cargo run --release`.
+
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, watch::Watch};
+use embassy_time::{Duration, Timer};
+use esp_wifi::wifi::{ClientConfiguration, Configuration, WifiController, WifiEvent};
+//IF option("probe-rs")
+//+use defmt::{info, warn};
+//ELSE
+use log::{info, warn};
+//ENDIF
+
+/// The network to join, from `WIFI_SSID` at build time.
+pub const SSID: &str = match option_env!("WIFI_SSID") {
+    Some(ssid) => ssid,
+    None => "",
+};
+const PASSWORD: &str = match option_env!("WIFI_PASSWORD") {
+    Some(password) => password,
+    None => "",
+};
+
+/// The delay after the first failed attempt to connect.
+pub const MIN_BACKOFF: Duration = Duration::from_secs(1);
+/// The longest delay between two attempts to connect.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Whether the station is connected, observed by up to 4 tasks.
+pub static IS_CONNECTED: Watch<CriticalSectionRawMutex, bool, 4> = Watch::new_with(false);
+
+/// Connects to [`SSID`], and reconnects whenever the connection is lost.
+#[embassy_executor::task]
+pub async fn connection(mut controller: WifiController<'static>) {
+    if SSID.is_empty() {
+        warn!("WIFI_SSID was not set when building, connecting will fail");
+    }
+    let config = Configuration::Client(ClientConfiguration {
+        ssid: SSID.try_into().unwrap(),
+        password: PASSWORD.try_into().unwrap(),
+        ..Default::default()
+    });
+    controller.set_configuration(&config).unwrap();
+    controller.start_async().await.unwrap();
+
+    let connected = IS_CONNECTED.sender();
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        match controller.connect_async().await {
+            Ok(()) => {
+                info!("Connected to {}", SSID);
+                connected.send(true);
+                backoff = MIN_BACKOFF;
+
+                // A disconnection right after connecting is still pending
+                // rather than missed
+                controller
+                    .wait_for_events(WifiEvent::StaDisconnected.into(), false)
+                    .await;
+                warn!("Disconnected from {}, reconnecting", SSID);
+                connected.send(false);
+                //IF option("metrics")
+                crate::metrics::WIFI_DISCONNECTS.increment();
+                //ENDIF
+            }
+            Err(error) => {
+                warn!(
+                    "Failed to connect to {}: {:?}, retrying in {} s",
+                    SSID,
+                    error,
+                    backoff.as_secs()
+                );
+                Timer::after(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use std::{
+    fs,
     path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
 use anyhow::{bail, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use esp_metadata::Chip;
 
 #[derive(Debug, Parser)]
@@ -25,8 +26,65 @@ enum Commands {
         #[arg(short, long)]
         all_combinations: bool,
     },
+    /// Generate a project for each combination of options and list the lint
+    /// warnings clippy reports for it, instead of failing at the first one
+    LintAudit {
+        /// Target chip to audit
+        #[arg(value_enum)]
+        chip: Chip,
+        /// Audit all possible options combinations
+        #[arg(short, long)]
+        all_combinations: bool,
+    },
+    /// Generate a project for each combination of options and ensure that it
+    /// builds with the minimum Rust version of `requirements.toml`, rather
+    /// than only with the latest one
+    Msrv {
+        /// Target chip to check
+        #[arg(value_enum)]
+        chip: Chip,
+        /// Verify all possible options combinations
+        #[arg(short, long)]
+        all_combinations: bool,
+    },
+    /// Generate a project for each combination of options with `--timings`
+    /// and ensure that rendering the templates stays within its budget
+    Perf {
+        /// Target chip to measure
+        #[arg(value_enum)]
+        chip: Chip,
+        /// Budget for rendering the templates of a single project, in
+        /// milliseconds
+        #[arg(long, default_value_t = 100.0)]
+        max_render_ms: f64,
+        /// Number of runs per combination, of which the fastest counts
+        #[arg(long, default_value_t = 3)]
+        runs: usize,
+    },
+    /// Export the graph of the options, grouped by category, with the options
+    /// each requires and conflicts with
+    Graph {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = GraphFormat::Mermaid)]
+        format: GraphFormat,
+        /// Replace the code block between `<!-- option-graph -->` and
+        /// `<!-- /option-graph -->` in this Markdown file with the graph,
+        /// instead of printing it
+        #[arg(long)]
+        embed: Option<PathBuf>,
+    },
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+/// Lints which generated projects are allowed to trigger, with the reason.
+/// Every other warning fails `check`.
+const ALLOWED_LINTS: &[(&str, &str)] = &[];
+
 fn main() -> Result<()> {
     env_logger::Builder::new()
         .filter_module("xtask", log::LevelFilter::Info)
@@ -42,6 +100,20 @@ fn main() -> Result<()> {
             chip,
             all_combinations,
         } => check(&workspace, chip, all_combinations),
+        Commands::LintAudit {
+            chip,
+            all_combinations,
+        } => lint_audit(&workspace, chip, all_combinations),
+        Commands::Msrv {
+            chip,
+            all_combinations,
+        } => msrv(&workspace, chip, all_combinations),
+        Commands::Perf {
+            chip,
+            max_render_ms,
+            runs,
+        } => perf(&workspace, chip, max_render_ms, runs),
+        Commands::Graph { format, embed } => graph(&workspace, format, embed.as_deref()),
     }
 }
 
@@ -52,7 +124,7 @@ fn check(workspace: &Path, chip: Chip, all_combinations: bool) -> Result<()> {
     log::info!("CHECK: {chip}");
 
     const PROJECT_NAME: &str = "test";
-    for options in options_for_chip(chip, all_combinations) {
+    for options in options_for_chip(workspace, chip, all_combinations)? {
         log::info!("WITH OPTIONS: {options:?}");
 
         // We will generate the project in a temporary directory, to avoid
@@ -63,11 +135,16 @@ fn check(workspace: &Path, chip: Chip, all_combinations: bool) -> Result<()> {
 
         // Generate a project targeting the specified chip and using the
         // specified generation options:
-        generate(workspace, &project_path, PROJECT_NAME, chip, &options)?;
+        generate(workspace, project_path, PROJECT_NAME, chip, &options)?;
 
         // Ensure that the generated project builds without errors:
+        let mut check_args = vec!["check", "--release"];
+        if options.iter().any(|option| option == "examples") {
+            // Examples are only checked when selected explicitly
+            check_args.extend(["--lib", "--bins", "--examples"]);
+        }
         let output = Command::new("cargo")
-            .args(["check", "--release"])
+            .args(check_args)
             .current_dir(project_path.join(PROJECT_NAME))
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
@@ -79,7 +156,9 @@ fn check(workspace: &Path, chip: Chip, all_combinations: bool) -> Result<()> {
 
         // Run clippy against the generated project to check for lint errors:
         let output = Command::new("cargo")
-            .args(["clippy", "--no-deps", "--", "-Dwarnings"])
+            .args(["clippy", "--no-deps", "--"])
+            .args(lint_args())
+            .arg("-Dwarnings")
             .current_dir(project_path.join(PROJECT_NAME))
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
@@ -107,52 +186,785 @@ fn check(workspace: &Path, chip: Chip, all_combinations: bool) -> Result<()> {
     Ok(())
 }
 
-fn options_for_chip(chip: Chip, all_combinations: bool) -> Vec<Vec<String>> {
+/// The options combined in every possible way with `--all-combinations`. The
+/// number of combinations doubles with each option, so only the main ones are
+/// listed rather than those of every default configuration.
+const COMBINED_OPTIONS: &[&str] = &[
+    "alloc",
+    "wifi",
+    "ble",
+    "embassy",
+    "probe-rs",
+    "unstable-hal",
+];
+
+fn options_for_chip(
+    workspace: &Path,
+    chip: Chip,
+    all_combinations: bool,
+) -> Result<Vec<Vec<String>>> {
     let default_options: Vec<Vec<String>> = vec![
         vec![], // No options
         vec!["alloc".into()],
-        vec!["alloc".into(), "wifi".into()],
-        vec!["alloc".into(), "ble".into()],
-        vec!["embassy".into()],
+        vec!["unstable-hal".into()],
+        vec!["alloc".into(), "wifi".into(), "unstable-hal".into()],
+        vec!["alloc".into(), "ble".into(), "unstable-hal".into()],
+        vec!["alloc".into(), "heap-dram2".into()],
+        vec!["alloc".into(), "heap-psram".into(), "unstable-hal".into()],
+        vec!["embassy".into(), "unstable-hal".into()],
+        vec![
+            "embassy".into(),
+            "stack-usage".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "embassy".into(),
+            "probe-rs".into(),
+            "trace".into(),
+            "unstable-hal".into(),
+        ],
+        vec!["release-small".into()],
+        vec!["flip-link".into()],
+        vec!["examples".into(), "unstable-hal".into()],
+        vec!["heartbeat".into(), "unstable-hal".into()],
+        vec!["panic-policy".into(), "unstable-hal".into()],
+        vec![
+            "embassy".into(),
+            "panic-policy".into(),
+            "reset-reason".into(),
+            "unstable-hal".into(),
+        ],
+        vec!["interrupts".into(), "unstable-hal".into()],
+        vec!["sync".into(), "unstable-hal".into()],
+        vec!["ram-placement".into(), "unstable-hal".into()],
+        vec![
+            "embassy".into(),
+            "ram-placement".into(),
+            "unstable-hal".into(),
+        ],
+        vec!["map-file".into()],
+        vec!["app-config".into()],
+        vec!["hal-facade".into()],
+        vec![
+            "alloc".into(),
+            "hal-facade".into(),
+            "unstable-hal".into(),
+            "wifi".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "embassy".into(),
+            "hal-facade".into(),
+            "unstable-hal".into(),
+            "wifi".into(),
+        ],
+        vec![
+            "app-config".into(),
+            "embassy".into(),
+            "panic-policy".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "channels".into(),
+            "embassy".into(),
+            "sync".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "devkit".into(),
+            "interrupts".into(),
+            "unstable-hal".into(),
+            "wifi".into(),
+        ],
+        vec![
+            "embassy".into(),
+            "devkit".into(),
+            "heartbeat".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "wifi".into(),
+            "examples".into(),
+            "unstable-hal".into(),
+        ],
+        vec!["devkit".into()],
+        vec!["embassy".into(), "devkit".into(), "unstable-hal".into()],
+        vec!["embassy".into(), "channels".into(), "unstable-hal".into()],
+        vec![
+            "embassy".into(),
+            "channels".into(),
+            "battery".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "heap-psram".into(),
+            "ui-slint".into(),
+            "unstable-hal".into(),
+        ],
+        vec!["audio-out".into(), "unstable-hal".into()],
+        vec!["embassy".into(), "audio-out".into(), "unstable-hal".into()],
+        vec!["hw-crypto".into(), "unstable-hal".into()],
+        vec!["ident".into(), "unstable-hal".into()],
+        vec!["embassy".into(), "ident".into(), "unstable-hal".into()],
+        vec![
+            "alloc".into(),
+            "ble".into(),
+            "beacon".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "ble".into(),
+            "beacon".into(),
+            "beacon-eddystone".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "embassy".into(),
+            "wifi".into(),
+            "wifi-sta".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "embassy".into(),
+            "wifi".into(),
+            "wifi-sta".into(),
+            "net-tools".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "embassy".into(),
+            "wifi".into(),
+            "wifi-sta".into(),
+            "metrics".into(),
+            "unstable-hal".into(),
+        ],
+        vec![
+            "alloc".into(),
+            "embassy".into(),
+            "wifi".into(),
+            "wifi-sta".into(),
+            "remote-logging".into(),
+            "unstable-hal".into(),
+        ],
         vec!["probe-rs".into()],
     ];
 
-    let available_options = match chip {
-        Chip::Esp32h2 => default_options
-            .iter()
-            .filter(|opts| !opts.contains(&"wifi".to_string()))
-            .cloned()
-            .collect::<Vec<_>>(),
-        Chip::Esp32s2 => default_options
-            .iter()
-            .filter(|opts| !opts.contains(&"ble".to_string()))
-            .cloned()
-            .collect::<Vec<_>>(),
-        _ => default_options,
-    };
+    // The generator knows which options are available for the chip, and how
+    // they may be combined:
     if !all_combinations {
-        return available_options;
-    } else {
-        // Return all the combination of availble options
-        let mut result = vec![];
-        for i in 0..(1 << available_options.len()) {
-            let mut options = vec![];
-            for j in 0..available_options.len() {
-                if i & (1 << j) != 0 {
-                    options.extend(available_options[j].clone());
-                }
+        let mut valid = Vec::new();
+        for options in default_options {
+            if !configurations(workspace, chip, &options, true)?.is_empty() {
+                valid.push(options);
             }
-            result.push(options);
         }
+        Ok(valid)
+    } else {
+        let options = COMBINED_OPTIONS
+            .iter()
+            .map(|option| option.to_string())
+            .collect::<Vec<_>>();
+
         // Filter all the items that contains wifi and ble
-        let result = result
+        Ok(configurations(workspace, chip, &options, false)?
             .into_iter()
             .filter(|opts| {
                 !opts.contains(&"wifi".to_string()) || !opts.contains(&"ble".to_string())
             })
-            .collect::<Vec<_>>();
-        return result;
+            .collect())
+    }
+}
+
+/// The arguments allowing the lints of [ALLOWED_LINTS].
+fn lint_args() -> Vec<String> {
+    ALLOWED_LINTS
+        .iter()
+        .flat_map(|(lint, _)| ["-A".to_string(), lint.to_string()])
+        .collect()
+}
+
+// ----------------------------------------------------------------------------
+// LINT AUDIT
+
+fn lint_audit(workspace: &Path, chip: Chip, all_combinations: bool) -> Result<()> {
+    log::info!("LINT AUDIT: {chip}");
+
+    const PROJECT_NAME: &str = "test";
+    let mut report = Vec::new();
+    for options in options_for_chip(workspace, chip, all_combinations)? {
+        log::info!("WITH OPTIONS: {options:?}");
+
+        let project_dir = tempfile::tempdir()?;
+        let project_path = project_dir.path();
+        generate(workspace, project_path, PROJECT_NAME, chip, &options)?;
+
+        let output = Command::new("cargo")
+            .args(["clippy", "--no-deps", "--message-format=json", "--"])
+            .args(lint_args())
+            .current_dir(project_path.join(PROJECT_NAME))
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        let warnings = lint_warnings(&String::from_utf8_lossy(&output.stdout));
+        if !output.status.success() {
+            report.push((
+                options,
+                vec!["error: the project does not build".to_string()],
+            ));
+        } else if !warnings.is_empty() {
+            report.push((options, warnings));
+        }
+
+        project_dir.close()?;
     }
+
+    if report.is_empty() {
+        log::info!("No lint warnings for any combination");
+        return Ok(());
+    }
+
+    for (options, warnings) in &report {
+        println!("{chip} {}:", options.join(" "));
+        for warning in warnings {
+            println!("  {warning}");
+        }
+    }
+
+    bail!("{} combination(s) have lint warnings", report.len())
+}
+
+/// The lint warnings in the JSON output of `cargo clippy`, as `lint: message`.
+fn lint_warnings(output: &str) -> Vec<String> {
+    let mut warnings = output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-message")
+        .filter_map(|message| {
+            let message = &message["message"];
+            let lint = message["code"]["code"].as_str()?;
+            let text = message["message"].as_str()?;
+            (message["level"] == "warning").then(|| format!("{lint}: {text}"))
+        })
+        .collect::<Vec<_>>();
+    warnings.sort();
+    warnings.dedup();
+
+    warnings
+}
+
+// ----------------------------------------------------------------------------
+// MSRV
+
+fn msrv(workspace: &Path, chip: Chip, all_combinations: bool) -> Result<()> {
+    log::info!("MSRV: {chip}");
+
+    let requirements = fs::read_to_string(workspace.join("requirements.toml"))?;
+    let requirements = RustRequirement::parse(&requirements)?;
+
+    const PROJECT_NAME: &str = "test";
+    let mut failures = Vec::new();
+    for options in options_for_chip(workspace, chip, all_combinations)? {
+        let version = requirements.min_version(chip, &options);
+        let toolchain = msrv_toolchain(chip, &version)?;
+        log::info!("WITH OPTIONS: {options:?}, RUST {version}");
+
+        let project_dir = tempfile::tempdir()?;
+        let project_path = project_dir.path();
+        generate(workspace, project_path, PROJECT_NAME, chip, &options)?;
+
+        let mut check_args = vec![format!("+{toolchain}"), "check".into(), "--release".into()];
+        if options.iter().any(|option| option == "examples") {
+            check_args.extend(["--lib", "--bins", "--examples"].map(String::from));
+        }
+        // The toolchain given on the command line takes precedence over the
+        // project's rust-toolchain.toml
+        let output = Command::new("cargo")
+            .args(check_args)
+            .current_dir(project_path.join(PROJECT_NAME))
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .output()?;
+        if !output.status.success() {
+            failures.push((options, version));
+        }
+
+        project_dir.close()?;
+    }
+
+    if failures.is_empty() {
+        log::info!("Every combination builds with its minimum Rust version");
+        return Ok(());
+    }
+
+    for (options, version) in &failures {
+        println!(
+            "{chip} {}: does not build with Rust {version}",
+            options.join(" ")
+        );
+    }
+
+    bail!(
+        "{} combination(s) require a newer Rust than requirements.toml declares",
+        failures.len()
+    )
+}
+
+/// The minimum Rust version of `requirements.toml`, and its stricter
+/// minimums for some chips or options.
+struct RustRequirement {
+    min_version: String,
+    when: Vec<(Option<String>, Option<String>, String)>,
+}
+
+impl RustRequirement {
+    fn parse(requirements: &str) -> Result<Self> {
+        let document = requirements.parse::<toml_edit::DocumentMut>()?;
+        let Some(rust) = document.get("tools").and_then(|tools| tools.get("rust")) else {
+            bail!("requirements.toml has no minimum Rust version");
+        };
+
+        let string = |item: &toml_edit::Item, key: &str| item.get(key)?.as_str().map(String::from);
+        let Some(min_version) = string(rust, "min-version") else {
+            bail!("requirements.toml has no minimum Rust version");
+        };
+        let when = rust
+            .get("when")
+            .and_then(|when| when.as_array_of_tables())
+            .into_iter()
+            .flatten()
+            .filter_map(|when| {
+                let when = toml_edit::Item::Table(when.clone());
+                Some((
+                    string(&when, "chip"),
+                    string(&when, "option"),
+                    string(&when, "min-version")?,
+                ))
+            })
+            .collect();
+
+        Ok(Self { min_version, when })
+    }
+
+    /// The highest minimum version applying to the chip and options, as
+    /// `esp-generate` checks it.
+    fn min_version(&self, chip: Chip, options: &[String]) -> String {
+        self.when
+            .iter()
+            .filter(|(c, _, _)| c.as_ref().is_none_or(|c| *c == chip.to_string()))
+            .filter(|(_, o, _)| o.as_ref().is_none_or(|o| options.contains(o)))
+            .map(|(_, _, version)| version)
+            .chain([&self.min_version])
+            .max_by_key(|version| parse_version(version))
+            .unwrap()
+            .clone()
+    }
+}
+
+/// The numeric components of a version, e.g. `[1, 84, 0]`.
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// The toolchain to check the projects for the chip with, at exactly the
+/// given version.
+///
+/// RISC-V projects use the stable channel, whose releases `rustup` installs on
+/// demand. Xtensa projects need the `esp` toolchain, which must have been
+/// installed at this version with `espup`.
+fn msrv_toolchain(chip: Chip, version: &str) -> Result<String> {
+    if chip.is_xtensa() {
+        let output = Command::new("rustc")
+            .args(["+esp", "--version"])
+            .stderr(Stdio::inherit())
+            .output()?;
+        let installed = String::from_utf8_lossy(&output.stdout);
+        // e.g. `rustc 1.84.0-nightly (...)`
+        let installed = installed
+            .split_whitespace()
+            .nth(1)
+            .and_then(|v| v.split('-').next())
+            .unwrap_or_default();
+        if !output.status.success() || parse_version(installed) != parse_version(version) {
+            bail!(
+                "The esp toolchain is at version '{installed}', not {version}: install it with `espup install --toolchain-version {version}.0`"
+            );
+        }
+
+        return Ok("esp".to_string());
+    }
+
+    let status = Command::new("rustup")
+        .args(["toolchain", "install", version, "--profile", "minimal"])
+        .args(["--component", "rust-src", "--target", chip.target()])
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+    if !status.success() {
+        bail!("Failed to install Rust {version}");
+    }
+
+    Ok(version.to_string())
+}
+
+// ----------------------------------------------------------------------------
+// PERF
+
+fn perf(workspace: &Path, chip: Chip, max_render_ms: f64, runs: usize) -> Result<()> {
+    log::info!("PERF: {chip}");
+
+    // Measure the release build, as installed by users, rather than timing
+    // `cargo run` itself:
+    let status = Command::new("cargo")
+        .args(["build", "--release"])
+        .current_dir(workspace)
+        .status()?;
+    if !status.success() {
+        bail!("Failed to build esp-generate");
+    }
+    let binary = workspace
+        .join("target")
+        .join("release")
+        .join(format!("esp-generate{}", std::env::consts::EXE_SUFFIX));
+
+    const PROJECT_NAME: &str = "test";
+    let mut over_budget = Vec::new();
+    for options in options_for_chip(workspace, chip, false)? {
+        let mut fastest: Option<Vec<(String, f64)>> = None;
+        for _ in 0..runs.max(1) {
+            let project_dir = tempfile::tempdir()?;
+            let mut command = Command::new(&binary);
+            command.args([
+                "--headless",
+                "--experimental",
+                "--skip-update-check",
+                "--timings",
+                &format!("--chip={chip}"),
+                &format!("--output-path={}", project_dir.path().display()),
+            ]);
+            for option in &options {
+                command.args(["-o", option]);
+            }
+            let output = command
+                .arg(PROJECT_NAME)
+                .stderr(Stdio::inherit())
+                .output()?;
+            project_dir.close()?;
+            if !output.status.success() {
+                bail!("Failed to generate a project with options {options:?}");
+            }
+
+            let timings = parse_timings(&String::from_utf8_lossy(&output.stdout));
+            if fastest
+                .as_ref()
+                .is_none_or(|fastest| phase(&timings, "render") < phase(fastest, "render"))
+            {
+                fastest = Some(timings);
+            }
+        }
+
+        let timings = fastest.unwrap_or_default();
+        let render = phase(&timings, "render");
+        log::info!(
+            "{options:?}: render {render:.1} ms, total {:.1} ms",
+            phase(&timings, "total")
+        );
+        if render > max_render_ms {
+            over_budget.push((options, render));
+        }
+    }
+
+    if over_budget.is_empty() {
+        log::info!("Rendering stays within {max_render_ms} ms for every combination");
+        return Ok(());
+    }
+
+    for (options, render) in &over_budget {
+        println!("{chip} {}: render {render:.1} ms", options.join(" "));
+    }
+
+    bail!(
+        "{} combination(s) take longer than {max_render_ms} ms to render",
+        over_budget.len()
+    )
+}
+
+/// The phases and their durations in milliseconds from the `--timings`
+/// report of esp-generate, whose lines are `<phase> <ms> ms`.
+fn parse_timings(output: &str) -> Vec<(String, f64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let [phase, ms, "ms"] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            Some((phase.to_string(), ms.parse().ok()?))
+        })
+        .collect()
+}
+
+/// The duration of a phase in milliseconds, zero if it did not run.
+fn phase(timings: &[(String, f64)], name: &str) -> f64 {
+    timings
+        .iter()
+        .find(|(phase, _)| phase == name)
+        .map_or(0.0, |(_, ms)| *ms)
+}
+
+// ----------------------------------------------------------------------------
+// GRAPH
+
+const EMBED_START: &str = "<!-- option-graph -->";
+const EMBED_END: &str = "<!-- /option-graph -->";
+
+fn graph(workspace: &Path, format: GraphFormat, embed: Option<&Path>) -> Result<()> {
+    let nodes = option_graph(workspace)?;
+
+    let graph = match format {
+        GraphFormat::Dot => dot(&nodes),
+        GraphFormat::Mermaid => mermaid(&nodes),
+    };
+
+    let Some(path) = embed else {
+        print!("{graph}");
+        return Ok(());
+    };
+
+    let contents = fs::read_to_string(path)?;
+    let (Some(start), Some(end)) = (contents.find(EMBED_START), contents.find(EMBED_END)) else {
+        bail!(
+            "{} has no {EMBED_START} and {EMBED_END} markers",
+            path.display()
+        );
+    };
+    if end < start {
+        bail!("{EMBED_END} precedes {EMBED_START} in {}", path.display());
+    }
+
+    let language = match format {
+        GraphFormat::Dot => "dot",
+        GraphFormat::Mermaid => "mermaid",
+    };
+    let contents = format!(
+        "{}{EMBED_START}\n```{language}\n{graph}```\n{}",
+        &contents[..start],
+        &contents[end..]
+    );
+    fs::write(path, contents)?;
+    log::info!("Embedded the option graph in {}", path.display());
+
+    Ok(())
+}
+
+/// An option and its constraints, as printed by the generator.
+struct OptionNode {
+    name: String,
+    category: Option<String>,
+    enables: Vec<String>,
+    disables: Vec<String>,
+}
+
+impl OptionNode {
+    /// The categories in the order they first appear, with their options.
+    fn by_category(nodes: &[OptionNode]) -> Vec<(Option<&str>, Vec<&OptionNode>)> {
+        let mut categories: Vec<(Option<&str>, Vec<&OptionNode>)> = Vec::new();
+        for node in nodes {
+            let category = node.category.as_deref();
+            match categories.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, options)) => options.push(node),
+                None => categories.push((category, vec![node])),
+            }
+        }
+
+        categories
+    }
+
+    /// Every pair of conflicting options, once.
+    fn conflicts(nodes: &[OptionNode]) -> Vec<(&str, &str)> {
+        let mut conflicts = Vec::new();
+        for node in nodes {
+            for other in &node.disables {
+                let pair = if node.name < *other {
+                    (node.name.as_str(), other.as_str())
+                } else {
+                    (other.as_str(), node.name.as_str())
+                };
+                if !conflicts.contains(&pair) {
+                    conflicts.push(pair);
+                }
+            }
+        }
+
+        conflicts
+    }
+}
+
+/// Ask the generator for the options and their constraints.
+fn option_graph(workspace: &Path) -> Result<Vec<OptionNode>> {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--release",
+            "--",
+            "--skip-update-check",
+            "option-graph",
+        ])
+        .current_dir(workspace)
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        bail!("Failed to export the option graph")
+    }
+
+    let strings = |value: &serde_json::Value| {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect::<Vec<_>>()
+    };
+
+    let nodes: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)?;
+    Ok(nodes
+        .iter()
+        .filter_map(|node| {
+            let mut enables = strings(&node["enables"]);
+            // Only a requirement of bare-metal projects, drawn like the others
+            if node["requires_unstable_hal"].as_bool() == Some(true) {
+                enables.push("unstable-hal".to_string());
+            }
+
+            Some(OptionNode {
+                name: node["name"].as_str()?.to_string(),
+                category: node["category"].as_str().map(String::from),
+                enables,
+                disables: strings(&node["disables"]),
+            })
+        })
+        .collect())
+}
+
+fn dot(nodes: &[OptionNode]) -> String {
+    let mut graph = String::from("digraph options {\n    rankdir=LR;\n    node [shape=box];\n\n");
+
+    for (category, options) in OptionNode::by_category(nodes) {
+        let indent = if let Some(category) = category {
+            graph +=
+                &format!("    subgraph \"cluster_{category}\" {{\n        label=\"{category}\";\n");
+            "        "
+        } else {
+            "    "
+        };
+        for option in options {
+            graph += &format!("{indent}\"{}\";\n", option.name);
+        }
+        if category.is_some() {
+            graph += "    }\n";
+        }
+    }
+
+    graph += "\n";
+    for node in nodes {
+        for requirement in &node.enables {
+            graph += &format!("    \"{}\" -> \"{requirement}\";\n", node.name);
+        }
+    }
+    for (a, b) in OptionNode::conflicts(nodes) {
+        graph += &format!(
+            "    \"{a}\" -> \"{b}\" [dir=none, style=dashed, color=red, label=\"conflicts\"];\n"
+        );
+    }
+    graph += "}\n";
+
+    graph
+}
+
+fn mermaid(nodes: &[OptionNode]) -> String {
+    // Dashes would be taken for edges in node IDs
+    let id = |name: &str| name.replace('-', "_");
+
+    let mut graph = String::from("graph LR\n");
+
+    for (category, options) in OptionNode::by_category(nodes) {
+        let indent = if let Some(category) = category {
+            // Categories may be named like one of their options
+            graph += &format!("    subgraph category_{}[\"{category}\"]\n", id(category));
+            "        "
+        } else {
+            "    "
+        };
+        for option in options {
+            graph += &format!("{indent}{}[\"{}\"]\n", id(&option.name), option.name);
+        }
+        if category.is_some() {
+            graph += "    end\n";
+        }
+    }
+
+    for node in nodes {
+        for requirement in &node.enables {
+            graph += &format!("    {} --> {}\n", id(&node.name), id(requirement));
+        }
+    }
+    for (a, b) in OptionNode::conflicts(nodes) {
+        graph += &format!("    {} -. conflicts .- {}\n", id(a), id(b));
+    }
+
+    graph
+}
+
+/// Ask the generator for every valid configuration of the given options for a
+/// chip, with the options of each configuration in the order they were given.
+/// With `check`, the options are not combined: the result is the options
+/// themselves if they are valid together, and empty otherwise.
+fn configurations(
+    workspace: &Path,
+    chip: Chip,
+    options: &[String],
+    check: bool,
+) -> Result<Vec<Vec<String>>> {
+    let mut args = [
+        "run",
+        "--release",
+        "--",
+        "--skip-update-check",
+        "configurations",
+        &format!("--chip={chip}"),
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect::<Vec<_>>();
+    if check {
+        args.push("--check".to_string());
+    }
+
+    for option in options {
+        args.extend(["-o".to_string(), option.to_owned()]);
+    }
+
+    let output = Command::new("cargo")
+        .args(args)
+        .current_dir(workspace)
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        bail!("Failed to list the valid configurations")
+    }
+
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(|line| line.split_whitespace().map(String::from).collect())
+        .collect())
 }
 
 fn generate(
@@ -162,11 +974,12 @@ fn generate(
     chip: Chip,
     options: &[String],
 ) -> Result<()> {
-    let mut args = vec![
+    let mut args = [
         "run",
         "--release",
         "--",
         "--headless",
+        "--experimental",
         &format!("--chip={chip}"),
         &format!("--output-path={}", project_path.display()),
     ]